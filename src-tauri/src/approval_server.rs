@@ -6,11 +6,14 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager};
 use tokio::sync::{Mutex, oneshot};
 
+use crate::tasks::TaskRegistry;
 use crate::translator::translate_tool_event;
+use crate::util::truncate_chars;
 
 /// Approval request sent to the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,10 +43,269 @@ struct RespondPayload {
     approved: bool,
 }
 
+/// Approvals waiting for the user, oldest first (shown as a queue in the UI)
+pub type ApprovalQueue = Arc<Mutex<Vec<ApprovalRequest>>>;
+
 #[derive(Clone)]
 struct ServerState {
     pending: Arc<Mutex<HashMap<String, oneshot::Sender<bool>>>>,
     app_handle: AppHandle,
+    web_policy: Arc<Mutex<WebPolicy>>,
+    repo_rules: Arc<Mutex<RepoApprovalRules>>,
+    queue: ApprovalQueue,
+}
+
+/// Deliver the user's decision for `approval_id`. Approvals can be answered
+/// in any order. Returns false if the id is unknown (already answered or timed out).
+pub async fn resolve_pending(
+    pending: &Mutex<HashMap<String, oneshot::Sender<bool>>>,
+    approval_id: &str,
+    approved: bool,
+) -> bool {
+    let tx = pending.lock().await.remove(approval_id);
+    match tx {
+        Some(tx) => {
+            let _ = tx.send(approved);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Ids of approvals still waiting for an answer (in no particular order)
+pub async fn pending_ids(pending: &Mutex<HashMap<String, oneshot::Sender<bool>>>) -> Vec<String> {
+    pending.lock().await.keys().cloned().collect()
+}
+
+/// Emit the current queue so the UI can show every outstanding approval
+async fn emit_queue(state: &ServerState) {
+    let queue = state.queue.lock().await.clone();
+    let _ = state.app_handle.emit("claude:approval_queue", &queue);
+}
+
+/// User policy for web tools. The default auto-approves both WebFetch and
+/// WebSearch, matching the behavior before the policy existed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WebPolicy {
+    /// Domains (and their subdomains) fetched without asking
+    #[serde(default)]
+    pub allowed_domains: Vec<String>,
+    /// Domains that always require approval
+    #[serde(default)]
+    pub blocked_domains: Vec<String>,
+    /// Ask before fetching any domain not in `allowed_domains`
+    #[serde(default)]
+    pub require_allowlist: bool,
+    /// Ask before every WebSearch
+    #[serde(default)]
+    pub prompt_web_search: bool,
+}
+
+impl WebPolicy {
+    fn allows_fetch(&self, url: Option<&str>) -> bool {
+        // Can't tell where it goes, so let the user decide
+        let Some(host) = url.and_then(url_host) else {
+            return false;
+        };
+        if domain_listed(&host, &self.blocked_domains) {
+            return false;
+        }
+        !self.require_allowlist || domain_listed(&host, &self.allowed_domains)
+    }
+}
+
+/// Extract the lowercase host of an http(s) URL
+fn url_host(url: &str) -> Option<String> {
+    let url = url.trim();
+    let lower = url.to_lowercase();
+    let rest = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host_port = authority.rsplit('@').next()?;
+    let host = if let Some(v6) = host_port.strip_prefix('[') {
+        v6.split(']').next()?
+    } else {
+        host_port.split(':').next()?
+    };
+    let host = host.trim_end_matches('.');
+    if host.is_empty() {
+        None
+    } else {
+        Some(host.to_string())
+    }
+}
+
+/// Whether `host` equals or is a subdomain of any listed domain
+fn domain_listed(host: &str, domains: &[String]) -> bool {
+    domains.iter().any(|d| {
+        let d = d.trim().trim_start_matches("*.").to_lowercase();
+        !d.is_empty() && (host == d || host.ends_with(&format!(".{}", d)))
+    })
+}
+
+/// Auto-approve rules a project commits in `.cowork/approvals.json`:
+///
+/// ```json
+/// { "commands": ["npm test", "cargo build"], "write_paths": ["docs/", "out"] }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepoApprovalRules {
+    /// Bash command prefixes that run without asking
+    #[serde(default)]
+    pub commands: Vec<String>,
+    /// Paths (relative to the working dir) that may be written without asking
+    #[serde(default)]
+    pub write_paths: Vec<String>,
+    /// Whether the user accepted this exact file; until then nothing in it
+    /// is auto-approved (a cloned repository must not vouch for itself)
+    #[serde(default, skip_deserializing)]
+    pub accepted: bool,
+    /// Working directory the rules were loaded from
+    #[serde(skip)]
+    base: PathBuf,
+    /// SHA-256 of the file the rules came from
+    #[serde(skip)]
+    hash: Option<String>,
+}
+
+/// Per working dir, the hash of the `.cowork/approvals.json` the user accepted
+const ACCEPTED_RULES_FILE: &str = "accepted_repo_rules.json";
+
+fn rules_hash(content: &str) -> String {
+    use sha2::Digest;
+    sha2::Sha256::digest(content.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+fn load_accepted(data_dir: &Path) -> HashMap<String, String> {
+    std::fs::read_to_string(data_dir.join(ACCEPTED_RULES_FILE))
+        .ok()
+        .and_then(|c| serde_json::from_str(&c).ok())
+        .unwrap_or_default()
+}
+
+impl RepoApprovalRules {
+    /// Load `.cowork/approvals.json` from `working_dir`. Missing or invalid
+    /// files yield empty rules (nothing extra is auto-approved). The rules
+    /// only apply if their file's hash was accepted in `data_dir`.
+    pub fn load(working_dir: &str, data_dir: Option<&Path>) -> Self {
+        if working_dir.is_empty() {
+            return Self::default();
+        }
+        let path = Path::new(working_dir).join(".cowork").join("approvals.json");
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        let mut rules = match Self::parse(Path::new(working_dir), &content) {
+            Ok(rules) => rules,
+            Err(e) => {
+                log::warn!("Ignoring invalid {}: {}", path.display(), e);
+                return Self::default();
+            }
+        };
+        rules.accepted = data_dir.is_some_and(|dir| {
+            load_accepted(dir).get(working_dir) == rules.hash.as_ref()
+        });
+        if !rules.accepted {
+            log::info!("{} is not accepted yet; its rules are ignored", path.display());
+        }
+        rules
+    }
+
+    fn parse(base: &Path, content: &str) -> Result<Self, String> {
+        let mut rules: Self = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        rules.base = base.to_path_buf();
+        rules.hash = Some(rules_hash(content));
+        Ok(rules)
+    }
+
+    /// Whether the working dir has an approvals file (accepted or not)
+    pub fn is_present(&self) -> bool {
+        self.hash.is_some()
+    }
+
+    /// Record these rules' file as accepted for their working dir, so they
+    /// apply until the file changes
+    pub fn accept(&mut self, data_dir: &Path) -> Result<(), String> {
+        let hash = self
+            .hash
+            .clone()
+            .ok_or("このフォルダには .cowork/approvals.json がありません")?;
+        let mut accepted = load_accepted(data_dir);
+        accepted.insert(self.base.to_string_lossy().to_string(), hash);
+        let content = serde_json::to_string_pretty(&accepted)
+            .map_err(|e| format!("データの変換に失敗しました: {}", e))?;
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+        std::fs::write(data_dir.join(ACCEPTED_RULES_FILE), content)
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        self.accepted = true;
+        Ok(())
+    }
+
+    fn allows(&self, tool_name: &str, tool_input: &serde_json::Value) -> bool {
+        if !self.accepted {
+            return false;
+        }
+        let str_field = |key: &str| tool_input.get(key).and_then(|v| v.as_str());
+        match tool_name {
+            "Bash" => str_field("command").is_some_and(|cmd| self.allows_command(cmd)),
+            "Write" | "Edit" | "MultiEdit" => {
+                str_field("file_path").is_some_and(|p| self.allows_write(p))
+            }
+            "NotebookEdit" => str_field("notebook_path").is_some_and(|p| self.allows_write(p)),
+            _ => false,
+        }
+    }
+
+    fn allows_command(&self, cmd: &str) -> bool {
+        let cmd = cmd.trim();
+        // A listed prefix must not vouch for whatever is chained after it
+        let chained = ["&", "|", ";", "`", "$(", ">", "<", "\n"]
+            .iter()
+            .any(|op| cmd.contains(op));
+        if chained {
+            return false;
+        }
+        self.commands.iter().any(|prefix| {
+            let prefix = prefix.trim();
+            !prefix.is_empty()
+                && (cmd == prefix
+                    || cmd
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with(char::is_whitespace)))
+        })
+    }
+
+    fn allows_write(&self, file_path: &str) -> bool {
+        if self.write_paths.is_empty() || self.base.as_os_str().is_empty() {
+            return false;
+        }
+        let Ok(target) = crate::paths::resolve_within(&self.base, file_path) else {
+            return false;
+        };
+        self.write_paths.iter().any(|prefix| {
+            !prefix.trim().is_empty()
+                && crate::paths::resolve_within(&self.base, prefix)
+                    .is_ok_and(|allowed| target.starts_with(allowed))
+        })
+    }
+}
+
+/// Auto-approval decision including the user's web policy
+fn should_auto_approve(
+    tool_name: &str,
+    tool_input: &serde_json::Value,
+    policy: &WebPolicy,
+) -> bool {
+    match tool_name {
+        "WebFetch" => policy.allows_fetch(tool_input.get("url").and_then(|v| v.as_str())),
+        "WebSearch" => !policy.prompt_web_search,
+        _ => is_auto_approved(tool_name, tool_input),
+    }
 }
 
 /// Tools that are always auto-approved (read-only or safe)
@@ -113,8 +375,7 @@ fn build_details(tool_name: &str, tool_input: &serde_json::Value) -> Vec<String>
                 details.push(format!("場所: {}", friendly_path(path)));
             }
             if let Some(old) = tool_input.get("old_string").and_then(|v| v.as_str()) {
-                let preview: String = old.chars().take(80).collect();
-                details.push(format!("変更箇所: {}...", preview));
+                details.push(format!("変更箇所: {}", truncate_chars(old, 80)));
             }
         }
         "NotebookEdit" => {
@@ -124,8 +385,7 @@ fn build_details(tool_name: &str, tool_input: &serde_json::Value) -> Vec<String>
         }
         _ => {
             if let Ok(json) = serde_json::to_string_pretty(tool_input) {
-                let truncated: String = json.chars().take(300).collect();
-                details.push(truncated);
+                details.push(truncate_chars(&json, 300));
             }
         }
     }
@@ -230,12 +490,18 @@ async fn handle_approval(
 ) -> Result<String, StatusCode> {
     let payload: HookPayload = serde_json::from_str(&body)
         .map_err(|e| {
-            log::error!("Failed to parse hook payload: {} - body: {}", e, &body[..body.len().min(200)]);
+            log::error!("Failed to parse hook payload: {} - body: {}", e, truncate_chars(&body, 200));
             StatusCode::BAD_REQUEST
         })?;
 
     // Auto-approve safe tools
-    if is_auto_approved(&payload.tool_name, &payload.tool_input) {
+    let policy = state.web_policy.lock().await.clone();
+    let repo_allowed = state
+        .repo_rules
+        .lock()
+        .await
+        .allows(&payload.tool_name, &payload.tool_input);
+    if repo_allowed || should_auto_approve(&payload.tool_name, &payload.tool_input, &policy) {
         return Ok(approval_response(true));
     }
 
@@ -252,16 +518,18 @@ async fn handle_approval(
         details,
     };
 
-    // Send to frontend
-    let _ = state.app_handle.emit("claude:approval_request", &approval_request);
-
-    // Create oneshot channel and wait
+    // Register the channel first so an answer can never arrive before it
     let (tx, rx) = oneshot::channel::<bool>();
     {
         let mut pending = state.pending.lock().await;
         pending.insert(approval_id.clone(), tx);
     }
 
+    // Send to frontend
+    let _ = state.app_handle.emit("claude:approval_request", &approval_request);
+    state.queue.lock().await.push(approval_request);
+    emit_queue(&state).await;
+
     log::info!("Waiting for approval: {} ({})", payload.tool_name, approval_id);
 
     // Wait for response with timeout
@@ -270,6 +538,10 @@ async fn handle_approval(
         rx,
     ).await;
 
+    // However it ended, this approval is no longer outstanding
+    state.queue.lock().await.retain(|r| r.id != approval_id);
+    emit_queue(&state).await;
+
     match result {
         Ok(Ok(approved)) => {
             log::info!("Approval response: approved={} for {}", approved, approval_id);
@@ -294,13 +566,7 @@ async fn handle_respond(
     let payload: RespondPayload = serde_json::from_str(&body)
         .map_err(|_| StatusCode::BAD_REQUEST)?;
 
-    let tx = {
-        let mut pending = state.pending.lock().await;
-        pending.remove(&payload.approval_id)
-    };
-
-    if let Some(tx) = tx {
-        let _ = tx.send(payload.approved);
+    if resolve_pending(&state.pending, &payload.approval_id, payload.approved).await {
         Ok(format!("{{\"ok\":true,\"approved\":{}}}", payload.approved))
     } else {
         log::warn!("No pending approval found for id: {}", payload.approval_id);
@@ -308,14 +574,65 @@ async fn handle_respond(
     }
 }
 
-/// Start the approval HTTP server and return the port
+/// Forgets the cached port when the server task ends (including when it is
+/// cancelled from the task list), so the next run starts a new server
+struct PortRelease {
+    slot: Arc<Mutex<Option<u16>>>,
+    port: u16,
+}
+
+impl PortRelease {
+    fn release(slot: &Mutex<Option<u16>>, port: u16) -> bool {
+        match slot.try_lock() {
+            Ok(mut cached) => {
+                if *cached == Some(port) {
+                    *cached = None;
+                }
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl Drop for PortRelease {
+    fn drop(&mut self) {
+        if Self::release(&self.slot, self.port) {
+            return;
+        }
+        // Someone holds the slot right now; clear it once they are done
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let slot = Arc::clone(&self.slot);
+            let port = self.port;
+            handle.spawn(async move {
+                let mut cached = slot.lock().await;
+                if *cached == Some(port) {
+                    *cached = None;
+                }
+            });
+        }
+    }
+}
+
+/// Start the approval HTTP server and return the port. `port_slot` is where
+/// the caller caches the port; it is cleared when the server stops.
 pub async fn start_approval_server(
     app_handle: AppHandle,
     pending: Arc<Mutex<HashMap<String, oneshot::Sender<bool>>>>,
+    web_policy: Arc<Mutex<WebPolicy>>,
+    repo_rules: Arc<Mutex<RepoApprovalRules>>,
+    queue: ApprovalQueue,
+    port_slot: Arc<Mutex<Option<u16>>>,
 ) -> Result<u16, String> {
+    let task_registry = app_handle
+        .try_state::<Arc<TaskRegistry>>()
+        .map(|s| Arc::clone(s.inner()));
     let state = ServerState {
         pending,
         app_handle,
+        web_policy,
+        repo_rules,
+        queue,
     };
 
     let app = Router::new()
@@ -333,11 +650,21 @@ pub async fn start_approval_server(
 
     log::info!("Approval server started on port {}", port);
 
-    tokio::spawn(async move {
+    let release = PortRelease { slot: port_slot, port };
+    let serve = async move {
+        let _release = release;
         if let Err(e) = axum::serve(listener, app).await {
             log::error!("Approval server error: {}", e);
         }
-    });
+    };
+    match task_registry {
+        Some(tasks) => {
+            tasks.spawn(&format!("承認サーバー (ポート {})", port), serve);
+        }
+        None => {
+            tokio::spawn(serve);
+        }
+    }
 
     Ok(port)
 }
@@ -347,6 +674,30 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[tokio::test]
+    async fn test_cancelled_server_task_clears_cached_port() {
+        let slot = Arc::new(Mutex::new(Some(4242)));
+        let registry = TaskRegistry::default();
+        let release = PortRelease { slot: Arc::clone(&slot), port: 4242 };
+        let id = registry.spawn("承認サーバー (ポート 4242)", async move {
+            let _release = release;
+            std::future::pending::<()>().await;
+        });
+        assert!(registry.cancel(&id));
+        for _ in 0..50 {
+            if slot.lock().await.is_none() {
+                break;
+            }
+            tokio::task::yield_now().await;
+        }
+        assert_eq!(*slot.lock().await, None);
+
+        // A newer server's port is left alone
+        *slot.lock().await = Some(5000);
+        drop(PortRelease { slot: Arc::clone(&slot), port: 4242 });
+        assert_eq!(*slot.lock().await, Some(5000));
+    }
+
     #[test]
     fn test_auto_approve_read_tools() {
         assert!(is_auto_approved("Read", &json!({})));
@@ -411,4 +762,195 @@ mod tests {
         assert_eq!(details.len(), 1);
         assert!(details[0].contains("file.txt"));
     }
+
+    // ── Web policy ──
+
+    #[test]
+    fn test_default_web_policy_keeps_auto_approval() {
+        let policy = WebPolicy::default();
+        assert!(should_auto_approve("WebFetch", &json!({"url": "https://example.com/a"}), &policy));
+        assert!(should_auto_approve("WebSearch", &json!({"query": "rust"}), &policy));
+    }
+
+    #[test]
+    fn test_web_policy_allowlist_auto_approves() {
+        let policy = WebPolicy {
+            allowed_domains: vec!["docs.rs".to_string()],
+            require_allowlist: true,
+            ..Default::default()
+        };
+        assert!(should_auto_approve("WebFetch", &json!({"url": "https://docs.rs/tokio"}), &policy));
+        assert!(should_auto_approve("WebFetch", &json!({"url": "https://api.docs.rs:443/x"}), &policy));
+        assert!(!should_auto_approve("WebFetch", &json!({"url": "https://example.com"}), &policy));
+        assert!(!should_auto_approve("WebFetch", &json!({"url": "https://notdocs.rs"}), &policy));
+    }
+
+    #[test]
+    fn test_web_policy_blocklist_prompts() {
+        let policy = WebPolicy {
+            blocked_domains: vec!["internal.example.com".to_string()],
+            ..Default::default()
+        };
+        assert!(!should_auto_approve(
+            "WebFetch",
+            &json!({"url": "http://wiki.internal.example.com/page"}),
+            &policy
+        ));
+        assert!(should_auto_approve("WebFetch", &json!({"url": "https://example.com"}), &policy));
+    }
+
+    #[test]
+    fn test_web_policy_unparseable_url_prompts() {
+        let policy = WebPolicy::default();
+        assert!(!should_auto_approve("WebFetch", &json!({"url": "not a url"}), &policy));
+        assert!(!should_auto_approve("WebFetch", &json!({"url": "ftp://example.com"}), &policy));
+        assert!(!should_auto_approve("WebFetch", &json!({}), &policy));
+    }
+
+    #[test]
+    fn test_web_policy_prompt_web_search() {
+        let policy = WebPolicy {
+            prompt_web_search: true,
+            ..Default::default()
+        };
+        assert!(!should_auto_approve("WebSearch", &json!({"query": "rust"}), &policy));
+        // Other tools are unaffected
+        assert!(should_auto_approve("Read", &json!({}), &policy));
+        assert!(!should_auto_approve("Write", &json!({}), &policy));
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(url_host("https://User@Example.COM:8080/path?q=1"), Some("example.com".to_string()));
+        assert_eq!(url_host("http://[::1]:3000/"), Some("::1".to_string()));
+        assert_eq!(url_host("https:///nohost"), None);
+        assert_eq!(url_host("example.com"), None);
+    }
+
+    // ── Repository rules ──
+
+    /// Accepted rules from a fresh working dir (its data dir is `dir/data`)
+    fn repo_rules() -> (PathBuf, RepoApprovalRules) {
+        let dir = std::env::temp_dir().join(format!("cowork-approval-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(".cowork")).unwrap();
+        std::fs::write(
+            dir.join(".cowork").join("approvals.json"),
+            r#"{"commands": ["npm test", "cargo build"], "write_paths": ["docs/"]}"#,
+        )
+        .unwrap();
+        let data_dir = dir.join("data");
+        let mut rules = RepoApprovalRules::load(dir.to_str().unwrap(), Some(&data_dir));
+        rules.accept(&data_dir).unwrap();
+        (dir, rules)
+    }
+
+    #[test]
+    fn test_repo_rules_ignored_until_accepted() {
+        let dir = std::env::temp_dir().join(format!("cowork-approval-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join(".cowork")).unwrap();
+        let file = dir.join(".cowork").join("approvals.json");
+        std::fs::write(&file, r#"{"commands": ["python", "npm test"]}"#).unwrap();
+        let data_dir = dir.join("data");
+        let wd = dir.to_str().unwrap();
+        let attack = json!({"command": "python -c \"__import__('os').system('id')\""});
+
+        let mut rules = RepoApprovalRules::load(wd, Some(&data_dir));
+        assert!(rules.is_present());
+        assert!(!rules.accepted);
+        assert!(!rules.allows("Bash", &attack));
+        assert!(!rules.allows("Bash", &json!({"command": "npm test"})));
+        assert!(!RepoApprovalRules::load(wd, None).allows("Bash", &json!({"command": "npm test"})));
+
+        rules.accept(&data_dir).unwrap();
+        let reloaded = RepoApprovalRules::load(wd, Some(&data_dir));
+        assert!(reloaded.accepted);
+        assert!(reloaded.allows("Bash", &json!({"command": "npm test"})));
+
+        // Any change to the file needs a new acceptance
+        std::fs::write(&file, r#"{"commands": ["python", "npm test", "rm"]}"#).unwrap();
+        let changed = RepoApprovalRules::load(wd, Some(&data_dir));
+        assert!(!changed.accepted);
+        assert!(!changed.allows("Bash", &json!({"command": "npm test"})));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_repo_rules_commands() {
+        let (dir, rules) = repo_rules();
+        assert_eq!(rules.commands.len(), 2);
+        assert!(rules.allows("Bash", &json!({"command": "npm test"})));
+        assert!(rules.allows("Bash", &json!({"command": "cargo build --release"})));
+        // Unlisted, look-alike and chained commands still prompt
+        assert!(!rules.allows("Bash", &json!({"command": "npm publish"})));
+        assert!(!rules.allows("Bash", &json!({"command": "npm tester"})));
+        assert!(!rules.allows("Bash", &json!({"command": "npm test && rm -rf /"})));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_repo_rules_write_paths() {
+        let (dir, rules) = repo_rules();
+        let inside = dir.join("docs").join("guide.md");
+        assert!(rules.allows("Write", &json!({"file_path": inside.to_str().unwrap()})));
+        assert!(rules.allows("Edit", &json!({"file_path": "docs/sub/a.md"})));
+        assert!(!rules.allows("Write", &json!({"file_path": "src/main.rs"})));
+        assert!(!rules.allows("Write", &json!({"file_path": "docs/../src/main.rs"})));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_repo_rules_missing_or_invalid() {
+        let dir = std::env::temp_dir().join(format!("cowork-approval-test-{}", uuid::Uuid::new_v4()));
+        let rules = RepoApprovalRules::load(dir.to_str().unwrap(), None);
+        assert!(!rules.allows("Bash", &json!({"command": "npm test"})));
+        assert!(!rules.is_present());
+
+        std::fs::create_dir_all(dir.join(".cowork")).unwrap();
+        std::fs::write(dir.join(".cowork").join("approvals.json"), "{ not json").unwrap();
+        let rules = RepoApprovalRules::load(dir.to_str().unwrap(), None);
+        assert!(rules.commands.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    // ── Pending approvals ──
+
+    #[tokio::test]
+    async fn test_resolve_pending_out_of_order() {
+        let pending = Mutex::new(HashMap::new());
+        let (tx1, mut rx1) = oneshot::channel::<bool>();
+        let (tx2, rx2) = oneshot::channel::<bool>();
+        pending.lock().await.insert("first".to_string(), tx1);
+        pending.lock().await.insert("second".to_string(), tx2);
+
+        // Answer the second request first
+        assert!(resolve_pending(&pending, "second", false).await);
+        assert!(!rx2.await.unwrap());
+        assert!(rx1.try_recv().is_err());
+        assert!(pending.lock().await.contains_key("first"));
+
+        assert!(resolve_pending(&pending, "first", true).await);
+        assert!(rx1.await.unwrap());
+        assert!(pending.lock().await.is_empty());
+
+        // Answering again is reported as unknown
+        assert!(!resolve_pending(&pending, "first", true).await);
+    }
+
+    #[tokio::test]
+    async fn test_pending_ids() {
+        let pending = Mutex::new(HashMap::new());
+        assert!(pending_ids(&pending).await.is_empty());
+
+        let (tx1, _rx1) = oneshot::channel::<bool>();
+        let (tx2, _rx2) = oneshot::channel::<bool>();
+        pending.lock().await.insert("a".to_string(), tx1);
+        pending.lock().await.insert("b".to_string(), tx2);
+
+        let mut ids = pending_ids(&pending).await;
+        ids.sort();
+        assert_eq!(ids, vec!["a", "b"]);
+
+        resolve_pending(&pending, "a", true).await;
+        assert_eq!(pending_ids(&pending).await, vec!["b"]);
+    }
 }
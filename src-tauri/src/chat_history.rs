@@ -0,0 +1,157 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::claude::ChatMessage;
+
+/// How long the chat has to be quiet before it is written to disk
+pub const AUTOSAVE_IDLE: Duration = Duration::from_secs(1);
+
+/// Backend copy of the current workspace's chat, saved automatically a short
+/// while after the last new message so a crash loses at most that window.
+pub struct ChatHistory {
+    messages: Mutex<Vec<ChatMessage>>,
+    /// File of the current workspace; nothing is saved until it is set
+    path: Mutex<Option<PathBuf>>,
+    idle: Duration,
+    /// Bumped on every change; a scheduled save only runs if it is still current
+    generation: AtomicU64,
+    saves: AtomicU64,
+}
+
+impl ChatHistory {
+    pub fn new(idle: Duration) -> Self {
+        Self {
+            messages: Mutex::new(Vec::new()),
+            path: Mutex::new(None),
+            idle,
+            generation: AtomicU64::new(0),
+            saves: AtomicU64::new(0),
+        }
+    }
+
+    /// Switch to another workspace's history (already loaded or saved by
+    /// the caller). Pending auto-saves for the previous one are dropped.
+    pub fn reset(&self, path: PathBuf, messages: Vec<ChatMessage>) {
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        *self.path.lock().unwrap() = Some(path);
+        *self.messages.lock().unwrap() = messages;
+    }
+
+    /// Record a message and schedule a save. Like the chat view, an assistant
+    /// message directly after another replaces it (final result vs. stream).
+    pub fn append(self: &Arc<Self>, message: ChatMessage) {
+        {
+            let mut messages = self.messages.lock().unwrap();
+            let replaces_last = message.role == "assistant"
+                && messages.last().is_some_and(|m| m.role == "assistant");
+            if replaces_last {
+                messages.pop();
+            }
+            messages.push(message);
+        }
+        self.schedule_save();
+    }
+
+    fn schedule_save(self: &Arc<Self>) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let history = Arc::clone(self);
+        tokio::spawn(async move {
+            tokio::time::sleep(history.idle).await;
+            if history.generation.load(Ordering::SeqCst) != generation {
+                // Superseded by a newer change, which saves in turn
+                return;
+            }
+            if let Err(e) = history.save_now().await {
+                log::warn!("Chat history auto-save failed: {}", e);
+            }
+        });
+    }
+
+    /// Write the current messages to the workspace's history file
+    pub async fn save_now(&self) -> Result<(), String> {
+        let Some(path) = self.path.lock().unwrap().clone() else {
+            return Ok(());
+        };
+        let content = serde_json::to_string(&*self.messages.lock().unwrap())
+            .map_err(|e| format!("チャット履歴のシリアライズに失敗: {}", e))?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("ディレクトリ作成に失敗: {}", e))?;
+        }
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| format!("チャット履歴の保存に失敗: {}", e))?;
+        self.saves.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    #[cfg(test)]
+    fn save_count(&self) -> u64 {
+        self.saves.load(Ordering::SeqCst)
+    }
+}
+
+/// Emit a chat message to the frontend and record it for auto-save
+pub(crate) fn emit_message(app: &AppHandle, message: &ChatMessage) {
+    let _ = app.emit("claude:message", message);
+    if let Some(history) = app.try_state::<Arc<ChatHistory>>() {
+        history.append(message.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: &str, content: &str) -> ChatMessage {
+        ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: role.to_string(),
+            content: content.to_string(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rapid_appends_coalesce_into_one_save() {
+        let dir = std::env::temp_dir().join(format!("cowork-chat-test-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("chat.json");
+        let history = Arc::new(ChatHistory::new(Duration::from_millis(100)));
+        history.reset(path.clone(), Vec::new());
+
+        for i in 0..5 {
+            history.append(message("user", &format!("メッセージ{}", i)));
+        }
+        assert_eq!(history.save_count(), 0);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(history.save_count(), 1);
+        let saved: Vec<ChatMessage> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.len(), 5);
+        assert_eq!(saved[4].content, "メッセージ4");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_reset_drops_pending_save() {
+        let dir = std::env::temp_dir().join(format!("cowork-chat-test-{}", uuid::Uuid::new_v4()));
+        let history = Arc::new(ChatHistory::new(Duration::from_millis(50)));
+        history.reset(dir.join("a.json"), Vec::new());
+        history.append(message("user", "こんにちは"));
+        history.append(message("assistant", "途中"));
+        history.append(message("assistant", "最終回答"));
+        assert_eq!(history.messages.lock().unwrap().len(), 2);
+
+        history.reset(dir.join("b.json"), Vec::new());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(history.save_count(), 0);
+        assert!(!dir.join("a.json").exists());
+    }
+}
@@ -1,18 +1,21 @@
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager};
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
 use crate::approval_server;
+use crate::chat_history;
+use crate::config_snapshot;
 use crate::translator::translate_tool_event;
+use crate::util::{no_working_dir_error, truncate_chars};
 
-fn home_dir() -> Option<PathBuf> {
+pub(crate) fn home_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
         std::env::var_os("USERPROFILE")
@@ -25,11 +28,115 @@ fn home_dir() -> Option<PathBuf> {
     }
 }
 
+/// Where the PreToolUse hook script is installed
+pub(crate) fn hook_script_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("cowork-hook.cjs")
+}
+
+/// The user's Claude Code settings.json (where the hook is registered)
+pub(crate) fn claude_settings_path() -> Option<PathBuf> {
+    home_dir().map(|home| claude_settings_path_in(&home))
+}
+
+fn claude_settings_path_in(home: &Path) -> PathBuf {
+    home.join(".claude").join("settings.json")
+}
+
+/// Read a Claude Code settings.json for display, pretty-printed.
+/// Content that isn't valid JSON is returned as-is so it can still be inspected.
+pub(crate) fn read_claude_settings_file(path: &Path) -> Result<String, String> {
+    if !path.exists() {
+        return Err(format!("settings.jsonが見つかりません: {}", path.display()));
+    }
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("settings.json読み込みエラー: {}", e))?;
+    Ok(serde_json::from_str::<serde_json::Value>(&content)
+        .ok()
+        .and_then(|v| serde_json::to_string_pretty(&v).ok())
+        .unwrap_or(content))
+}
+
+/// The hook command registered in settings.json (quoted for paths with
+/// spaces, e.g. "Application Support")
+fn hook_command_for(hook_path: &Path) -> String {
+    let hook_path = hook_path.to_string_lossy();
+    if hook_path.contains(' ') {
+        format!("node '{}'", hook_path)
+    } else {
+        format!("node {}", hook_path)
+    }
+}
+
+/// Index of the PreToolUse entry that runs the Cowork hook, if any
+fn cowork_hook_index(settings: &serde_json::Value) -> Option<usize> {
+    settings.get("hooks")
+        .and_then(|h| h.get("PreToolUse"))
+        .and_then(|p| p.as_array())
+        .and_then(|arr| arr.iter().position(|item| cowork_hook_command(item).is_some()))
+}
+
+/// The Cowork hook command inside one PreToolUse entry
+fn cowork_hook_command(entry: &serde_json::Value) -> Option<&str> {
+    entry.get("hooks")?
+        .as_array()?
+        .iter()
+        .filter_map(|hook| hook.get("command").and_then(|c| c.as_str()))
+        .find(|c| c.contains("cowork-hook"))
+}
+
+/// State of the approval hook registration
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct HookStatus {
+    /// settings.json has a PreToolUse entry running cowork-hook
+    pub entry_exists: bool,
+    /// The script that entry points at exists on disk
+    pub script_exists: bool,
+    /// The entry points at this install's script
+    pub path_matches: bool,
+    /// The registered command, as found in settings.json
+    pub command: Option<String>,
+}
+
+impl HookStatus {
+    pub fn is_ok(&self) -> bool {
+        self.entry_exists && self.script_exists && self.path_matches
+    }
+}
+
+/// Check the hook registration in `settings` against the expected script path
+fn check_hook(settings: &serde_json::Value, expected_script: &Path) -> HookStatus {
+    let command = cowork_hook_index(settings)
+        .and_then(|idx| cowork_hook_command(&settings["hooks"]["PreToolUse"][idx]))
+        .map(|c| c.to_string());
+    let script_exists = command
+        .as_deref()
+        .and_then(|c| c.strip_prefix("node "))
+        .map(|p| p.trim().trim_matches(|c| c == '\'' || c == '"'))
+        .is_some_and(|p| Path::new(p).is_file());
+    let path_matches = command.as_deref() == Some(hook_command_for(expected_script).as_str());
+
+    HookStatus {
+        entry_exists: command.is_some(),
+        script_exists,
+        path_matches,
+        command,
+    }
+}
+
+/// Map a failure to spawn the `claude` binary to a user-facing message
+fn spawn_error_message(e: &std::io::Error) -> String {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        "Claude Codeが見つかりません。Claude Codeをインストールし、`claude` コマンドが実行できることを確認してください。".to_string()
+    } else {
+        format!("Claude Codeを起動できませんでした: {}", e)
+    }
+}
+
 /// A single message in the chat history (sent to frontend)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub id: String,
-    pub role: String, // "user" | "assistant" | "system"
+    pub role: String, // "user" | "assistant" | "system" | "tool"
     pub content: String,
     pub timestamp: String,
 }
@@ -72,6 +179,12 @@ pub enum ClaudeStreamEvent {
         subtype: Option<String>,
         result: Option<String>,
         is_error: Option<bool>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+        #[serde(default)]
+        num_turns: Option<u32>,
+        #[serde(default)]
+        total_cost_usd: Option<f64>,
         #[serde(flatten)]
         extra: serde_json::Value,
     },
@@ -113,6 +226,824 @@ pub enum ContentBlock {
     },
 }
 
+/// Maximum characters of a tool result shown inline in the chat
+const TOOL_RESULT_MAX_CHARS: usize = 500;
+
+/// Per-run state carried across stream lines
+#[derive(Default)]
+struct StreamState {
+    /// Last assistant text, so the final result isn't shown twice
+    current_text: String,
+    /// tool_use id -> tool name, for labelling tool results
+    tool_names: HashMap<String, String>,
+    /// Also forward every raw line on `claude:raw`
+    debug_raw: bool,
+    /// `total_cost_usd` reported by this run's result event
+    turn_cost: Option<f64>,
+    /// Which tools show up in the activity panel
+    activity_level: ActivityLevel,
+    /// Whether a result event arrived in this run
+    saw_result: bool,
+    /// Tool uses seen in this run, by tool name
+    tool_counts: HashMap<String, u32>,
+    /// Text deltas waiting to be sent as one batch
+    deltas: DeltaBatcher,
+    /// Id of the assistant message being streamed, shared by its deltas
+    /// and the final `claude:message`
+    message_id: Option<String>,
+}
+
+/// Activity panel verbosity
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityLevel {
+    /// Every tool use
+    #[default]
+    All,
+    /// Hide read-only tools (Read, Glob, Grep, ...)
+    WritesOnly,
+}
+
+/// Tools that only look at files and never change anything
+fn is_read_only_tool(name: &str) -> bool {
+    matches!(name, "Read" | "Glob" | "Grep" | "LS" | "NotebookRead")
+}
+
+impl ActivityLevel {
+    fn shows(self, tool_name: &str) -> bool {
+        match self {
+            ActivityLevel::All => true,
+            ActivityLevel::WritesOnly => !is_read_only_tool(tool_name),
+        }
+    }
+}
+
+/// Build a condensed "tool"-role chat message from a `tool_result` content item
+fn tool_result_message(tool_name: &str, item: &serde_json::Value) -> ChatMessage {
+    let text = match item.get("content") {
+        Some(serde_json::Value::String(s)) => s.clone(),
+        Some(serde_json::Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    };
+    let is_error = item.get("is_error").and_then(|v| v.as_bool()).unwrap_or(false);
+    let label = if is_error {
+        format!("{}（エラー）", tool_name)
+    } else {
+        tool_name.to_string()
+    };
+    ChatMessage {
+        id: uuid::Uuid::new_v4().to_string(),
+        role: "tool".to_string(),
+        content: format!("{}: {}", label, truncate_chars(text.trim(), TOOL_RESULT_MAX_CHARS)),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// Start of a streamed assistant message (`claude:message_start`)
+#[derive(Debug, Clone, Serialize)]
+struct MessageStart {
+    id: String,
+}
+
+/// One streamed text chunk (`claude:text_delta`), tagged with its message id
+#[derive(Debug, Clone, Serialize)]
+struct TextDelta {
+    id: String,
+    text: String,
+}
+
+/// Default coalescing window for `claude:text_delta`
+const DEFAULT_DELTA_BATCH_MS: u64 = 50;
+/// A batch is sent early once it holds this many bytes
+const DELTA_BATCH_MAX_BYTES: usize = 4096;
+
+/// Coalesces text deltas so a fast stream isn't one IPC message per token.
+/// A zero interval sends every delta as it arrives.
+#[derive(Default)]
+struct DeltaBatcher {
+    interval: std::time::Duration,
+    pending: Option<TextDelta>,
+    since: Option<std::time::Instant>,
+}
+
+impl DeltaBatcher {
+    fn new(interval: std::time::Duration) -> Self {
+        Self {
+            interval,
+            ..Default::default()
+        }
+    }
+
+    /// Add a delta; returns the batches that are due
+    fn push(&mut self, id: String, text: &str, now: std::time::Instant) -> Vec<TextDelta> {
+        let mut out = Vec::new();
+        if self.pending.as_ref().is_some_and(|p| p.id != id) {
+            out.extend(self.flush());
+        }
+        match &mut self.pending {
+            Some(pending) => pending.text.push_str(text),
+            None => {
+                self.pending = Some(TextDelta {
+                    id,
+                    text: text.to_string(),
+                });
+                self.since = Some(now);
+            }
+        }
+        let expired = self
+            .since
+            .map_or(true, |since| now.duration_since(since) >= self.interval);
+        let full = self
+            .pending
+            .as_ref()
+            .is_some_and(|p| p.text.len() >= DELTA_BATCH_MAX_BYTES);
+        if expired || full {
+            out.extend(self.flush());
+        }
+        out
+    }
+
+    fn flush(&mut self) -> Option<TextDelta> {
+        self.since = None;
+        self.pending.take()
+    }
+
+    /// The pending batch if its window has passed, for the flush timer
+    fn take_due(&mut self, now: std::time::Instant) -> Option<TextDelta> {
+        let since = self.since?;
+        if now.duration_since(since) >= self.interval {
+            self.flush()
+        } else {
+            None
+        }
+    }
+}
+
+/// Run `run` while sending batched text whose window has passed, so text
+/// isn't held back when the stream goes quiet (e.g. during a long tool call)
+async fn flush_deltas_while<F: std::future::Future>(
+    run: F,
+    state: &std::sync::Mutex<StreamState>,
+    interval: std::time::Duration,
+    mut emit: impl FnMut(TextDelta),
+) -> F::Output {
+    if interval.is_zero() {
+        return run.await;
+    }
+    let timer = async {
+        let mut ticks = tokio::time::interval(interval);
+        loop {
+            ticks.tick().await;
+            let due = state.lock().unwrap().deltas.take_due(std::time::Instant::now());
+            if let Some(delta) = due {
+                emit(delta);
+            }
+        }
+    };
+    tokio::select! {
+        output = run => output,
+        _ = timer => unreachable!("the flush timer never finishes"),
+    }
+}
+
+/// Claude Code entering or leaving plan mode (`EnterPlanMode`/`ExitPlanMode`)
+#[derive(Debug, Clone, Serialize)]
+struct PlanModeEvent {
+    tool_use_id: String,
+    /// "entered" | "exited"
+    state: &'static str,
+    /// The proposed plan, carried by `ExitPlanMode`
+    plan: Option<String>,
+}
+
+fn plan_mode_event(id: &str, name: &str, input: &serde_json::Value) -> Option<PlanModeEvent> {
+    let state = match name {
+        "EnterPlanMode" => "entered",
+        "ExitPlanMode" => "exited",
+        _ => return None,
+    };
+    let plan = input
+        .get("plan")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.trim().is_empty())
+        .map(|s| s.to_string());
+    Some(PlanModeEvent {
+        tool_use_id: id.to_string(),
+        state,
+        plan,
+    })
+}
+
+/// A stream line that didn't match `ClaudeStreamEvent` (new event type or
+/// changed shape), reduced to whatever could be recovered generically
+#[derive(Debug, Clone, Serialize)]
+struct UnknownStreamEvent {
+    event_type: Option<String>,
+    text: Option<String>,
+    raw: serde_json::Value,
+}
+
+/// Recover the `type` and any text from a line that failed typed parsing.
+/// Returns None when the line isn't a JSON object at all.
+fn parse_unknown_event(line: &str) -> Option<UnknownStreamEvent> {
+    let raw: serde_json::Value = serde_json::from_str(line).ok()?;
+    if !raw.is_object() {
+        return None;
+    }
+    let event_type = raw.get("type").and_then(|v| v.as_str()).map(|s| s.to_string());
+
+    let direct = ["result", "text"]
+        .iter()
+        .find_map(|key| raw.get(*key).and_then(|v| v.as_str()))
+        .map(|s| s.to_string());
+    // Assistant messages containing an unsupported block type still carry
+    // their plain text blocks
+    let from_content = || {
+        let parts: Vec<&str> = raw
+            .get("message")?
+            .get("content")?
+            .as_array()?
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("text"))
+            .filter_map(|b| b.get("text").and_then(|t| t.as_str()))
+            .collect();
+        (!parts.is_empty()).then(|| parts.join("\n"))
+    };
+    let text = direct.or_else(from_content).filter(|t| !t.trim().is_empty());
+
+    Some(UnknownStreamEvent {
+        event_type,
+        text,
+        raw,
+    })
+}
+
+/// A frontend event produced from a stream line
+#[derive(Debug, Clone)]
+enum StreamEmit {
+    /// Sent as-is under its event name
+    Event {
+        event: &'static str,
+        payload: serde_json::Value,
+    },
+    /// A finished chat message, which also goes into the chat history
+    Message(ChatMessage),
+}
+
+fn push_emit<T: Serialize + ?Sized>(out: &mut Vec<StreamEmit>, event: &'static str, payload: &T) {
+    match serde_json::to_value(payload) {
+        Ok(payload) => out.push(StreamEmit::Event { event, payload }),
+        Err(e) => log::warn!("Failed to serialize {} payload: {}", event, e),
+    }
+}
+
+/// Emit the frontend events for one NDJSON line from Claude Code's stdout
+fn emit_stream(app: &AppHandle, emits: Vec<StreamEmit>) {
+    for emit in emits {
+        match emit {
+            StreamEmit::Event { event, payload } => {
+                let _ = app.emit(event, payload);
+            }
+            StreamEmit::Message(msg) => chat_history::emit_message(app, &msg),
+        }
+    }
+}
+
+/// Everything done with one line of Claude's stdout: copy it to the
+/// transcript (if capturing) and turn it into frontend events
+fn on_stdout_line(
+    line: &str,
+    transcript: Option<&Transcript>,
+    state: &mut StreamState,
+) -> Vec<StreamEmit> {
+    if let Some(t) = transcript {
+        t.write_line(line);
+    }
+    process_stream_line(line, state)
+}
+
+/// Turn one NDJSON line into the frontend events it should produce
+fn process_stream_line(line: &str, state: &mut StreamState) -> Vec<StreamEmit> {
+    let mut out = Vec::new();
+    if line.trim().is_empty() {
+        return out;
+    }
+    if state.debug_raw {
+        push_emit(&mut out, "claude:raw", line);
+    }
+
+    let parsed: Result<ClaudeStreamEvent, _> = serde_json::from_str(line);
+
+    // Anything other than more text sends the batched text first, keeping order
+    let is_text_delta = matches!(
+        &parsed,
+        Ok(ClaudeStreamEvent::StreamEvent { event, .. }) if event.pointer("/delta/text").is_some()
+    );
+    if !is_text_delta {
+        if let Some(delta) = state.deltas.flush() {
+            push_emit(&mut out, "claude:text_delta", &delta);
+        }
+    }
+
+    match parsed {
+        Ok(event) => {
+            match &event {
+                ClaudeStreamEvent::System { .. } => {
+                    push_emit(&mut out, "claude:system", &event);
+                }
+
+                ClaudeStreamEvent::Assistant { message, .. } => {
+                    // Reuse the id announced by message_start so the final
+                    // message replaces the streamed text
+                    let streamed_id = state.message_id.take();
+                    let message_id = message.id.clone().or(streamed_id);
+                    let mut text_blocks = 0;
+                    // Process content blocks
+                    for block in &message.content {
+                        match block {
+                            ContentBlock::Text { text } => {
+                                state.current_text = text.clone();
+                                let id = match (&message_id, text_blocks) {
+                                    (Some(id), 0) => id.clone(),
+                                    (Some(id), n) => format!("{}-{}", id, n),
+                                    (None, _) => uuid::Uuid::new_v4().to_string(),
+                                };
+                                text_blocks += 1;
+                                let msg = ChatMessage {
+                                    id,
+                                    role: "assistant".to_string(),
+                                    content: text.clone(),
+                                    timestamp: chrono::Utc::now().to_rfc3339(),
+                                };
+                                out.push(StreamEmit::Message(msg));
+                            }
+                            ContentBlock::ToolUse { id, name, input } => {
+                                state.tool_names.insert(id.clone(), name.clone());
+                                *state.tool_counts.entry(name.clone()).or_insert(0) += 1;
+                                let translated = translate_tool_event(name, input);
+                                let activity = ActivityItem {
+                                    id: id.clone(),
+                                    description: translated.description,
+                                    raw_command: Some(translated.raw),
+                                    status: "running".to_string(),
+                                    timestamp: chrono::Utc::now().to_rfc3339(),
+                                };
+                                if state.activity_level.shows(name) {
+                                    push_emit(&mut out, "claude:activity", &activity);
+                                }
+                                if let Some(plan_mode) = plan_mode_event(id, name, input) {
+                                    push_emit(&mut out, "claude:plan_mode", &plan_mode);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+
+                ClaudeStreamEvent::User { message, .. } => {
+                    // Tool results - mark activity as done
+                    if let Some(content) = message.get("content") {
+                        if let Some(arr) = content.as_array() {
+                            for item in arr {
+                                if let Some(tool_id) = item.get("tool_use_id").and_then(|v| v.as_str()) {
+                                    let tool_name = state
+                                        .tool_names
+                                        .remove(tool_id)
+                                        .unwrap_or_else(|| "ツール".to_string());
+
+                                    if state.activity_level.shows(&tool_name) {
+                                        let activity = ActivityItem {
+                                            id: tool_id.to_string(),
+                                            description: "完了".to_string(),
+                                            raw_command: None,
+                                            status: "done".to_string(),
+                                            timestamp: chrono::Utc::now().to_rfc3339(),
+                                        };
+                                        push_emit(&mut out, "claude:activity_done", &activity);
+                                    }
+
+                                    let msg = tool_result_message(&tool_name, item);
+                                    out.push(StreamEmit::Message(msg));
+                                }
+                            }
+                        }
+                    }
+                }
+
+                ClaudeStreamEvent::Result { result, total_cost_usd, .. } => {
+                    push_emit(&mut out, "claude:result", &event);
+                    state.saw_result = true;
+                    if total_cost_usd.is_some() {
+                        state.turn_cost = *total_cost_usd;
+                    }
+
+                    if let Some(text) = result {
+                        if !text.is_empty() && *text != state.current_text {
+                            let msg = ChatMessage {
+                                id: uuid::Uuid::new_v4().to_string(),
+                                role: "assistant".to_string(),
+                                content: text.clone(),
+                                timestamp: chrono::Utc::now().to_rfc3339(),
+                            };
+                            out.push(StreamEmit::Message(msg));
+                        }
+                    }
+                }
+
+                ClaudeStreamEvent::StreamEvent { event: evt, .. } => {
+                    if evt.get("type").and_then(|t| t.as_str()) == Some("message_start") {
+                        let id = evt
+                            .pointer("/message/id")
+                            .and_then(|v| v.as_str())
+                            .map(|s| s.to_string())
+                            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+                        state.message_id = Some(id.clone());
+                        push_emit(&mut out, "claude:message_start", &MessageStart { id });
+                    }
+                    // Forward text deltas for real-time streaming
+                    if let Some(delta) = evt.get("delta") {
+                        if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
+                            let id = state
+                                .message_id
+                                .get_or_insert_with(|| uuid::Uuid::new_v4().to_string())
+                                .clone();
+                            let now = std::time::Instant::now();
+                            for delta in state.deltas.push(id, text, now) {
+                                push_emit(&mut out, "claude:text_delta", &delta);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            log::warn!("Failed to parse stream line: {} - line: {}", e, truncate_chars(line, 200));
+            if let Some(unknown) = parse_unknown_event(line) {
+                if let Some(text) = &unknown.text {
+                    if *text != state.current_text {
+                        state.current_text = text.clone();
+                        let msg = ChatMessage {
+                            id: uuid::Uuid::new_v4().to_string(),
+                            role: "assistant".to_string(),
+                            content: text.clone(),
+                            timestamp: chrono::Utc::now().to_rfc3339(),
+                        };
+                        out.push(StreamEmit::Message(msg));
+                    }
+                }
+                push_emit(&mut out, "claude:unknown_event", &unknown);
+            }
+        }
+    }
+    out
+}
+
+/// Default overall limit for a single Claude run before the process is killed
+const DEFAULT_RUN_TIMEOUT_MINS: u64 = 60;
+
+/// The run limit from `settings`; `None` when the user turned it off
+fn run_timeout(settings: &ClaudeSettings) -> Option<std::time::Duration> {
+    match settings.run_timeout_mins.unwrap_or(DEFAULT_RUN_TIMEOUT_MINS) {
+        0 => None,
+        mins => Some(std::time::Duration::from_secs(mins * 60)),
+    }
+}
+
+/// Terminate a process by PID
+fn kill_process(pid: u32) {
+    #[cfg(unix)]
+    {
+        unsafe {
+            libc::kill(pid as i32, libc::SIGTERM);
+        }
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/T", "/F"])
+            .output();
+    }
+}
+
+/// Drain stdout and stderr concurrently while waiting for the child to exit,
+/// so a chatty process can never block on a full pipe. The child is killed
+/// if reading stdout fails or the run exceeds `timeout` (if any).
+async fn drive_process<F, G>(
+    mut child: Child,
+    mut on_stdout: F,
+    mut on_stderr: G,
+    timeout: Option<std::time::Duration>,
+) -> Result<std::process::ExitStatus, String>
+where
+    F: FnMut(String),
+    G: FnMut(String),
+{
+    let stdout = child.stdout.take().ok_or("Claude Codeの出力を取得できませんでした")?;
+    let stderr = child.stderr.take().ok_or("Claude Codeのエラー出力を取得できませんでした")?;
+    let pid = child.id();
+
+    let drain_stdout = async {
+        let mut lines = BufReader::new(stdout).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => on_stdout(line),
+                Ok(None) => break,
+                Err(e) => {
+                    log::error!("Failed to read claude stdout: {}", e);
+                    if let Some(pid) = pid {
+                        kill_process(pid);
+                    }
+                    break;
+                }
+            }
+        }
+    };
+    let drain_stderr = async {
+        let mut lines = BufReader::new(stderr).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            on_stderr(line);
+        }
+    };
+    let run = async {
+        let (_, _, status) = tokio::join!(drain_stdout, drain_stderr, child.wait());
+        status
+    };
+
+    let result = match timeout {
+        Some(limit) => tokio::time::timeout(limit, run).await,
+        None => Ok(run.await),
+    };
+    match result {
+        Ok(status) => status.map_err(|e| format!("プロセスエラー: {}", e)),
+        Err(_) => {
+            log::error!("Claude process timed out after {:?}, killing it", timeout.unwrap_or_default());
+            let _ = child.kill().await;
+            Err("Claude Codeの応答がタイムアウトしました".to_string())
+        }
+    }
+}
+
+/// Default seconds between `claude:heartbeat` events
+const DEFAULT_HEARTBEAT_SECS: u64 = 5;
+
+/// `claude:heartbeat` payload
+#[derive(Debug, Clone, Serialize)]
+struct HeartbeatEvent {
+    /// Seconds since the run started
+    elapsed_secs: u64,
+}
+
+/// Calls `tick` with the elapsed time every `interval` until dropped, so the
+/// UI can tell a long tool run from a hung process
+struct Heartbeat {
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl Heartbeat {
+    /// Start ticking; `None` for a zero interval (heartbeat disabled)
+    fn start<F>(interval: std::time::Duration, mut tick: F) -> Option<Self>
+    where
+        F: FnMut(std::time::Duration) + Send + 'static,
+    {
+        if interval.is_zero() {
+            return None;
+        }
+        let started = std::time::Instant::now();
+        let handle = tokio::spawn(async move {
+            let mut timer = tokio::time::interval(interval);
+            // The first tick completes immediately
+            timer.tick().await;
+            loop {
+                timer.tick().await;
+                tick(started.elapsed());
+            }
+        });
+        Some(Self { handle })
+    }
+}
+
+impl Drop for Heartbeat {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+/// Run `run` with a heartbeat calling `tick` every `interval`. The heartbeat
+/// stops as soon as `run` finishes, before the caller reports completion.
+async fn with_heartbeat<F, T>(run: F, interval: std::time::Duration, tick: T) -> F::Output
+where
+    F: std::future::Future,
+    T: FnMut(std::time::Duration) + Send + 'static,
+{
+    let heartbeat = Heartbeat::start(interval, tick);
+    let output = run.await;
+    drop(heartbeat);
+    output
+}
+
+/// Built-in instruction telling Claude Code how approvals work inside the app
+const APP_SYSTEM_PROMPT: &str = "You are running inside the Cowork desktop app. Tool permissions are handled automatically by the app's approval UI dialog. Do not ask the user for text-based permission or approval. Just use tools (Bash, Write, Edit, etc.) directly as needed. The app will show an approval dialog to the user when necessary.";
+
+/// User-configurable settings for the Claude Code path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ClaudeSettings {
+    /// App-wide guidance appended to the system prompt (e.g. "always respond in Japanese")
+    #[serde(default)]
+    pub append_system_prompt: Option<String>,
+    /// Approval policy for WebFetch/WebSearch
+    #[serde(default)]
+    pub web_policy: approval_server::WebPolicy,
+    /// Forward raw stdout lines on `claude:raw` for troubleshooting
+    #[serde(default)]
+    pub debug_raw_events: bool,
+    /// Activity panel verbosity ("all" | "writes_only")
+    #[serde(default)]
+    pub activity_level: ActivityLevel,
+    /// Whether stdout is also saved as a transcript ("stream" | "capture_raw")
+    #[serde(default)]
+    pub output_mode: OutputMode,
+    /// Minutes a run may take before it is killed; 0 disables the limit.
+    /// Unset means `DEFAULT_RUN_TIMEOUT_MINS`.
+    #[serde(default)]
+    pub run_timeout_mins: Option<u64>,
+    /// Seconds between `claude:heartbeat` events during a run; 0 disables
+    /// them. Unset means `DEFAULT_HEARTBEAT_SECS`.
+    #[serde(default)]
+    pub heartbeat_secs: Option<u64>,
+    /// Window (ms) over which text deltas are batched; 0 sends each one.
+    /// Unset means `DEFAULT_DELTA_BATCH_MS`.
+    #[serde(default)]
+    pub delta_batch_ms: Option<u64>,
+}
+
+/// What happens to Claude Code's stream-json output besides the live UI
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutputMode {
+    /// Only drive the UI
+    #[default]
+    Stream,
+    /// Also append every NDJSON line to a per-session transcript file
+    CaptureRaw,
+}
+
+/// Per-session NDJSON transcript under `<data_dir>/transcripts/`. Lines go
+/// to a writer task, so stream handling never waits on the disk.
+struct Transcript {
+    tx: tokio::sync::mpsc::UnboundedSender<String>,
+    writer: tokio::task::JoinHandle<()>,
+}
+
+impl Transcript {
+    fn path(data_dir: &Path, session_id: &str) -> PathBuf {
+        data_dir.join("transcripts").join(format!("{}.ndjson", session_id))
+    }
+
+    /// Open the session's transcript for appending (resumed turns share a file)
+    async fn open(data_dir: &Path, session_id: &str) -> Result<Self, String> {
+        let path = Self::path(data_dir, session_id);
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+        }
+        let file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .await
+            .map_err(|e| format!("トランスクリプトを開けませんでした: {}", e))?;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn(async move {
+            let mut out = tokio::io::BufWriter::new(file);
+            while let Some(line) = rx.recv().await {
+                if let Err(e) = out.write_all(line.as_bytes()).await {
+                    log::warn!("Failed to write transcript: {}", e);
+                }
+            }
+            if let Err(e) = out.flush().await {
+                log::warn!("Failed to flush transcript: {}", e);
+            }
+        });
+        Ok(Self { tx, writer })
+    }
+
+    fn write_line(&self, line: &str) {
+        let _ = self.tx.send(format!("{}\n", line));
+    }
+
+    /// Wait until every line has been written
+    async fn finish(self) {
+        drop(self.tx);
+        if let Err(e) = self.writer.await {
+            log::warn!("Transcript writer failed: {}", e);
+        }
+    }
+}
+
+/// Messages longer than this (in bytes) are sent on stdin instead of argv.
+/// Windows caps the whole command line at 32,767 characters.
+const MAX_ARG_MESSAGE_BYTES: usize = 16 * 1024;
+
+/// How the user's message is handed to the CLI
+#[derive(Debug, PartialEq)]
+enum PromptInput {
+    Arg(String),
+    Stdin(String),
+}
+
+impl PromptInput {
+    fn for_message(message: String) -> Self {
+        if message.len() > MAX_ARG_MESSAGE_BYTES {
+            PromptInput::Stdin(message)
+        } else {
+            PromptInput::Arg(message)
+        }
+    }
+}
+
+/// Write the prompt to the child's stdin and close it so `claude -p` sees EOF
+fn feed_stdin(child: &mut Child, text: String) -> Result<(), String> {
+    let mut stdin = child.stdin.take().ok_or("Claude Codeへの入力を開けませんでした")?;
+    tokio::spawn(async move {
+        if let Err(e) = stdin.write_all(text.as_bytes()).await {
+            log::error!("Failed to write prompt to claude stdin: {}", e);
+        }
+        // Dropping stdin closes the pipe
+    });
+    Ok(())
+}
+
+/// The working dir's `.cowork/mcp.json`, if present. Malformed JSON is an
+/// error rather than being handed to the CLI.
+fn find_mcp_config(working_dir: &str) -> Result<Option<PathBuf>, String> {
+    let path = Path::new(working_dir).join(".cowork").join("mcp.json");
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return Ok(None);
+    };
+    serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| format!("MCP設定ファイル({})の形式が正しくありません: {}", path.display(), e))?;
+    Ok(Some(path))
+}
+
+/// Build the CLI arguments for a single `claude -p` invocation.
+/// The CLI only honors one `--append-system-prompt`, so user guidance is
+/// appended to the app's built-in prompt.
+fn build_args(
+    prompt: &PromptInput,
+    session_id: &str,
+    is_resume: bool,
+    settings: &ClaudeSettings,
+    mcp_config: Option<&Path>,
+) -> Vec<String> {
+    let mut system_prompt = APP_SYSTEM_PROMPT.to_string();
+    if let Some(extra) = settings
+        .append_system_prompt
+        .as_deref()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        system_prompt.push_str("\n\n");
+        system_prompt.push_str(extra);
+    }
+
+    let mut args = vec![
+        "-p".to_string(),
+        "--output-format".to_string(),
+        "stream-json".to_string(),
+        "--verbose".to_string(),
+        // stream_event lines carry message_start and text deltas
+        "--include-partial-messages".to_string(),
+        "--append-system-prompt".to_string(),
+        system_prompt,
+    ];
+
+    // --mcp-config takes several values, so it must be followed by another flag
+    if let Some(path) = mcp_config {
+        args.push("--mcp-config".to_string());
+        args.push(path.to_string_lossy().to_string());
+    }
+
+    // Session management: first message uses --session-id, subsequent use --resume
+    if is_resume {
+        args.push("--resume".to_string());
+    } else {
+        args.push("--session-id".to_string());
+    }
+    args.push(session_id.to_string());
+
+    // Large prompts are read from stdin when no inline prompt is given
+    if let PromptInput::Arg(message) = prompt {
+        args.push(message.clone());
+    }
+    args
+}
+
 pub struct ClaudeManager {
     /// App-controlled session UUID (not parsed from Claude output)
     managed_session_id: Mutex<String>,
@@ -121,10 +1052,22 @@ pub struct ClaudeManager {
     working_dir: Mutex<String>,
     approval_port: Arc<Mutex<Option<u16>>>,
     approval_pending: Arc<Mutex<HashMap<String, tokio::sync::oneshot::Sender<bool>>>>,
+    approval_queue: approval_server::ApprovalQueue,
     /// App data directory for persisting session ID
     data_dir: Mutex<Option<PathBuf>>,
     /// PID of the running Claude process (for cancellation)
     child_pid: Mutex<Option<u32>>,
+    settings: Mutex<ClaudeSettings>,
+    /// Shared with the approval server so policy changes apply immediately
+    web_policy: Arc<Mutex<approval_server::WebPolicy>>,
+    /// Auto-approve rules from the working dir's `.cowork/approvals.json`
+    repo_rules: Arc<Mutex<approval_server::RepoApprovalRules>>,
+    /// Running total of `total_cost_usd` over this session's turns
+    session_cost: Mutex<f64>,
+    /// Tool uses over this session's turns, by tool name
+    tool_stats: Mutex<HashMap<String, u32>>,
+    /// Set by `cancel_and_discard`; the running turn is dropped from the session
+    discard_run: std::sync::atomic::AtomicBool,
 }
 
 impl ClaudeManager {
@@ -137,8 +1080,15 @@ impl ClaudeManager {
             working_dir: Mutex::new(String::new()),
             approval_port: Arc::new(Mutex::new(None)),
             approval_pending,
+            approval_queue: Arc::new(Mutex::new(Vec::new())),
             data_dir: Mutex::new(None),
             child_pid: Mutex::new(None),
+            settings: Mutex::new(ClaudeSettings::default()),
+            web_policy: Arc::new(Mutex::new(approval_server::WebPolicy::default())),
+            repo_rules: Arc::new(Mutex::new(approval_server::RepoApprovalRules::default())),
+            session_cost: Mutex::new(0.0),
+            tool_stats: Mutex::new(HashMap::new()),
+            discard_run: std::sync::atomic::AtomicBool::new(false),
         }
     }
 
@@ -146,11 +1096,45 @@ impl ClaudeManager {
         self.working_dir.lock().await.clone()
     }
 
+    /// The working directory, or the `NO_WORKING_DIR` error if none is set
+    async fn require_working_dir(&self) -> Result<String, String> {
+        let working_dir = self.working_dir.lock().await.clone();
+        if working_dir.is_empty() {
+            return Err(no_working_dir_error());
+        }
+        Ok(working_dir)
+    }
+
     pub async fn set_working_dir(&self, dir: String) {
+        let data_dir = self.data_dir.lock().await.clone();
+        *self.repo_rules.lock().await =
+            approval_server::RepoApprovalRules::load(&dir, data_dir.as_deref());
         let mut wd = self.working_dir.lock().await;
         *wd = dir;
     }
 
+    /// The working dir's `.cowork/approvals.json` rules and whether they apply
+    pub async fn get_repo_rules(&self) -> approval_server::RepoApprovalRules {
+        self.repo_rules.lock().await.clone()
+    }
+
+    /// Accept the working dir's current `.cowork/approvals.json` so its rules
+    /// auto-approve until the file changes
+    pub async fn accept_repo_rules(&self) -> Result<approval_server::RepoApprovalRules, String> {
+        let working_dir = self.require_working_dir().await?;
+        let data_dir = self
+            .data_dir
+            .lock()
+            .await
+            .clone()
+            .ok_or("データフォルダが設定されていません")?;
+        // Re-read so what gets accepted is the file as it is now
+        let mut rules = approval_server::RepoApprovalRules::load(&working_dir, Some(&data_dir));
+        rules.accept(&data_dir)?;
+        *self.repo_rules.lock().await = rules.clone();
+        Ok(rules)
+    }
+
     /// Set the data directory and restore a saved session ID if available
     pub async fn set_data_dir(&self, dir: PathBuf) {
         let session_file = dir.join("session_id.txt");
@@ -165,8 +1149,44 @@ impl ClaudeManager {
                 }
             }
         }
-        let mut dd = self.data_dir.lock().await;
-        *dd = Some(dir);
+        *self.data_dir.lock().await = Some(dir);
+        self.reload_settings().await;
+    }
+
+    /// Re-read `claude_settings.json` (e.g. after a settings import). A
+    /// missing or unreadable file keeps the current settings.
+    pub async fn reload_settings(&self) {
+        let Some(dir) = self.data_dir.lock().await.clone() else {
+            return;
+        };
+        let Ok(content) = std::fs::read_to_string(dir.join("claude_settings.json")) else {
+            return;
+        };
+        match serde_json::from_str::<ClaudeSettings>(&content) {
+            Ok(s) => {
+                *self.web_policy.lock().await = s.web_policy.clone();
+                *self.settings.lock().await = s;
+            }
+            Err(e) => log::warn!("Invalid claude_settings.json: {}", e),
+        }
+    }
+
+    pub async fn get_settings(&self) -> ClaudeSettings {
+        self.settings.lock().await.clone()
+    }
+
+    pub async fn save_settings(&self, settings: ClaudeSettings) -> Result<(), String> {
+        if let Some(ref dir) = *self.data_dir.lock().await {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+            let content = serde_json::to_string_pretty(&settings)
+                .map_err(|e| format!("データの変換に失敗しました: {}", e))?;
+            std::fs::write(dir.join("claude_settings.json"), content)
+                .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        }
+        *self.web_policy.lock().await = settings.web_policy.clone();
+        *self.settings.lock().await = settings;
+        Ok(())
     }
 
     /// Save the current session ID to disk
@@ -181,11 +1201,72 @@ impl ClaudeManager {
         }
     }
 
+    /// Zip the working dir's `.claude/` into `<data_dir>/snapshots/`;
+    /// returns the archive path
+    pub async fn snapshot_claude_config(&self) -> Result<String, String> {
+        let working_dir = self.require_working_dir().await?;
+        let data_dir = self
+            .data_dir
+            .lock()
+            .await
+            .clone()
+            .ok_or("データフォルダが設定されていません")?;
+        let archive = config_snapshot::snapshot_path(&data_dir.join("snapshots"));
+        let src = Path::new(&working_dir).join(".claude");
+        let target = archive.clone();
+        tokio::task::spawn_blocking(move || config_snapshot::snapshot_dir(&src, &target))
+            .await
+            .map_err(|e| format!("スナップショットの作成に失敗: {}", e))??;
+        Ok(archive.to_string_lossy().to_string())
+    }
+
+    /// Replace the working dir's `.claude/` with a snapshot's contents
+    pub async fn restore_claude_config(&self, archive: &str) -> Result<usize, String> {
+        let working_dir = self.require_working_dir().await?;
+        let archive = PathBuf::from(archive);
+        let dest = Path::new(&working_dir).join(".claude");
+        tokio::task::spawn_blocking(move || config_snapshot::restore_dir(&archive, &dest))
+            .await
+            .map_err(|e| format!("設定の復元に失敗: {}", e))?
+    }
+
+    /// Cumulative cost (USD) of the current session
+    pub async fn get_session_cost(&self) -> f64 {
+        *self.session_cost.lock().await
+    }
+
+    /// Add one turn's cost to the session total and return the new total
+    pub async fn add_session_cost(&self, cost: f64) -> f64 {
+        let mut total = self.session_cost.lock().await;
+        *total += cost;
+        *total
+    }
+
+    /// How often each tool was used in the current session
+    pub async fn get_tool_stats(&self) -> HashMap<String, u32> {
+        self.tool_stats.lock().await.clone()
+    }
+
+    /// Add one turn's tool counts to the session tallies
+    async fn add_tool_counts(&self, counts: &HashMap<String, u32>) {
+        let mut stats = self.tool_stats.lock().await;
+        for (name, count) in counts {
+            *stats.entry(name.clone()).or_insert(0) += count;
+        }
+    }
+
     pub async fn reset_session(&self) {
+        *self.session_cost.lock().await = 0.0;
+        self.tool_stats.lock().await.clear();
         let mut id = self.managed_session_id.lock().await;
         *id = uuid::Uuid::new_v4().to_string();
         self.first_message_sent.store(false, std::sync::atomic::Ordering::Relaxed);
-        // Delete saved session file
+        drop(id);
+        self.remove_saved_session_id().await;
+    }
+
+    /// Delete the saved session file
+    async fn remove_saved_session_id(&self) {
         let dd = self.data_dir.lock().await;
         if let Some(ref dir) = *dd {
             let session_file = dir.join("session_id.txt");
@@ -193,26 +1274,40 @@ impl ClaudeManager {
         }
     }
 
+    /// Cancel the running process and, if it hadn't produced a result yet,
+    /// put the session back to how it was before the run
+    pub async fn cancel_and_discard(&self) -> Result<(), String> {
+        self.discard_run.store(true, std::sync::atomic::Ordering::SeqCst);
+        self.cancel().await
+    }
+
+    /// Called when a run ends with the session state from before it. A
+    /// discarded run without a result is rolled back: a resumed session keeps
+    /// its id, while a new one gets a fresh id since the CLI may already have
+    /// registered the old one.
+    async fn finish_run(&self, prev_session_id: &str, was_resume: bool, saw_result: bool) {
+        let discard = self.discard_run.swap(false, std::sync::atomic::Ordering::SeqCst);
+        if !discard || saw_result {
+            return;
+        }
+        if was_resume {
+            *self.managed_session_id.lock().await = prev_session_id.to_string();
+            return;
+        }
+        *self.managed_session_id.lock().await = uuid::Uuid::new_v4().to_string();
+        self.first_message_sent.store(false, std::sync::atomic::Ordering::Relaxed);
+        self.remove_saved_session_id().await;
+        log::info!("Discarded cancelled first turn of session {}", prev_session_id);
+    }
+
     /// Cancel the currently running Claude process
     pub async fn cancel(&self) -> Result<(), String> {
         let pid = self.child_pid.lock().await.take();
         if let Some(pid) = pid {
-            #[cfg(unix)]
-            {
-                unsafe {
-                    libc::kill(pid as i32, libc::SIGTERM);
-                }
-            }
-            #[cfg(windows)]
-            {
-                let _ = std::process::Command::new("taskkill")
-                    .args(["/PID", &pid.to_string(), "/T", "/F"])
-                    .output();
-            }
-            Ok(())
-        } else {
-            Ok(()) // No process running, not an error
+            kill_process(pid);
         }
+        // No process running is not an error
+        Ok(())
     }
 
     /// Ensure the approval server is running, return port
@@ -224,11 +1319,32 @@ impl ClaudeManager {
         let port = approval_server::start_approval_server(
             app.clone(),
             Arc::clone(&self.approval_pending),
+            Arc::clone(&self.web_policy),
+            Arc::clone(&self.repo_rules),
+            Arc::clone(&self.approval_queue),
+            Arc::clone(&self.approval_port),
         ).await?;
         *port_guard = Some(port);
         Ok(port)
     }
 
+    /// Run `claude --version` to check that the CLI is installed
+    pub async fn check_cli_available() -> Result<String, String> {
+        let output = Command::new("claude")
+            .arg("--version")
+            .stdin(Stdio::null())
+            .output()
+            .await
+            .map_err(|e| spawn_error_message(&e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "Claude Codeの起動確認に失敗しました: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
     /// Install the hook script and configure Claude Code settings
     pub fn ensure_hook_installed(app: &AppHandle) -> Result<PathBuf, String> {
         let data_dir = app.path().app_data_dir()
@@ -237,15 +1353,14 @@ impl ClaudeManager {
             .map_err(|e| format!("ディレクトリ作成エラー: {}", e))?;
 
         // Copy hook script to app data directory
-        let hook_dest = data_dir.join("cowork-hook.cjs");
+        let hook_dest = hook_script_path(&data_dir);
         let hook_source = include_str!("../resources/cowork-hook.cjs");
         std::fs::write(&hook_dest, hook_source)
             .map_err(|e| format!("hookスクリプト書き込みエラー: {}", e))?;
 
         // Configure Claude Code settings
-        let home = home_dir().ok_or("ホームディレクトリが見つかりません")?;
-        let claude_dir = home.join(".claude");
-        let settings_path = claude_dir.join("settings.json");
+        let settings_path = claude_settings_path().ok_or("ホームディレクトリが見つかりません")?;
+        let claude_dir = settings_path.parent().map(Path::to_path_buf).unwrap_or_default();
 
         // Read existing settings or create new
         let mut settings: serde_json::Value = if settings_path.exists() {
@@ -258,29 +1373,10 @@ impl ClaudeManager {
             serde_json::json!({})
         };
 
-        // Build the hook command (quote the path for spaces in "Application Support" etc.)
-        let hook_path = hook_dest.to_string_lossy();
-        let hook_command = if hook_path.contains(' ') {
-            format!("node '{}'", hook_path)
-        } else {
-            format!("node {}", hook_path)
-        };
+        let hook_command = hook_command_for(&hook_dest);
 
         // Check if hook is already configured and if the command needs updating
-        let existing_hook_index = settings.get("hooks")
-            .and_then(|h| h.get("PreToolUse"))
-            .and_then(|p| p.as_array())
-            .and_then(|arr| arr.iter().position(|item| {
-                item.get("hooks")
-                    .and_then(|h| h.as_array())
-                    .map(|hooks| hooks.iter().any(|hook| {
-                        hook.get("command")
-                            .and_then(|c| c.as_str())
-                            .map(|c| c.contains("cowork-hook"))
-                            .unwrap_or(false)
-                    }))
-                    .unwrap_or(false)
-            }));
+        let existing_hook_index = cowork_hook_index(&settings);
 
         let hook_config = serde_json::json!({
             "matcher": "",
@@ -331,42 +1427,38 @@ impl ClaudeManager {
         Ok(hook_dest)
     }
 
+    /// Report whether the hook is registered and points at this install's script
+    pub fn verify_hook(app: &AppHandle) -> Result<HookStatus, String> {
+        let data_dir = app.path().app_data_dir()
+            .map_err(|e| format!("アプリデータディレクトリ取得エラー: {}", e))?;
+        let settings_path = claude_settings_path().ok_or("ホームディレクトリが見つかりません")?;
+        // A missing or unreadable settings.json simply has no hook entry
+        let settings = std::fs::read_to_string(&settings_path)
+            .ok()
+            .and_then(|c| serde_json::from_str(&c).ok())
+            .unwrap_or_else(|| serde_json::json!({}));
+        Ok(check_hook(&settings, &hook_script_path(&data_dir)))
+    }
+
     /// Send a user message to Claude Code and stream the response
     pub async fn send_message(
         &self,
         app: &AppHandle,
         message: String,
     ) -> Result<(), String> {
-        let working_dir = self.working_dir.lock().await.clone();
-        if working_dir.is_empty() {
-            return Err("作業フォルダが設定されていません".to_string());
-        }
+        let working_dir = self.require_working_dir().await?;
 
         // Ensure approval server is running
         let approval_port = self.ensure_approval_server(app).await?;
 
         // Build command args
-        let mut args = vec![
-            "-p".to_string(),
-            "--output-format".to_string(),
-            "stream-json".to_string(),
-            "--verbose".to_string(),
-            "--append-system-prompt".to_string(),
-            "You are running inside the Cowork desktop app. Tool permissions are handled automatically by the app's approval UI dialog. Do not ask the user for text-based permission or approval. Just use tools (Bash, Write, Edit, etc.) directly as needed. The app will show an approval dialog to the user when necessary.".to_string(),
-        ];
-
-        // Session management: first message uses --session-id, subsequent use --resume
         let session_id = self.managed_session_id.lock().await.clone();
         let is_resume = self.first_message_sent.load(std::sync::atomic::Ordering::Relaxed);
-        if is_resume {
-            args.push("--resume".to_string());
-            args.push(session_id.clone());
-        } else {
-            args.push("--session-id".to_string());
-            args.push(session_id.clone());
-        }
-
-        args.push(message);
+        self.discard_run.store(false, std::sync::atomic::Ordering::SeqCst);
+        let settings = self.settings.lock().await.clone();
+        let prompt = PromptInput::for_message(message);
+        let mcp_config = find_mcp_config(&working_dir)?;
+        let args = build_args(&prompt, &session_id, is_resume, &settings, mcp_config.as_deref());
 
         log::info!(
             "Spawning claude: session_id={}, is_resume={}, approval_port={}, args_count={}",
@@ -382,7 +1474,10 @@ impl ClaudeManager {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
-            .map_err(|e| format!("Claude Codeを起動できませんでした: {}", e))?;
+            .map_err(|e| spawn_error_message(&e))?;
+        if let PromptInput::Stdin(text) = prompt {
+            feed_stdin(&mut child, text)?;
+        }
 
         // Store PID for cancellation
         if let Some(pid) = child.id() {
@@ -395,132 +1490,77 @@ impl ClaudeManager {
         }
         self.first_message_sent.store(true, std::sync::atomic::Ordering::Relaxed);
 
-        let stdout = child.stdout.take().ok_or("Claude Codeの出力を取得できませんでした")?;
-        let stderr = child.stderr.take().ok_or("Claude Codeのエラー出力を取得できませんでした")?;
-
-        // Read stdout line by line (NDJSON)
-        let app_handle = app.clone();
-
-        let stdout_task = tokio::spawn(async move {
-            let reader = BufReader::new(stdout);
-            let mut lines = reader.lines();
-            let mut current_text = String::new();
-
-            while let Ok(Some(line)) = lines.next_line().await {
-                if line.trim().is_empty() {
-                    continue;
-                }
-
-                let parsed: Result<ClaudeStreamEvent, _> = serde_json::from_str(&line);
-
-                match parsed {
-                    Ok(event) => {
-                        match &event {
-                            ClaudeStreamEvent::System { .. } => {
-                                let _ = app_handle.emit("claude:system", &event);
-                            }
-
-                            ClaudeStreamEvent::Assistant { message, .. } => {
-                                // Process content blocks
-                                for block in &message.content {
-                                    match block {
-                                        ContentBlock::Text { text } => {
-                                            current_text = text.clone();
-                                            let msg = ChatMessage {
-                                                id: uuid::Uuid::new_v4().to_string(),
-                                                role: "assistant".to_string(),
-                                                content: text.clone(),
-                                                timestamp: chrono::Utc::now().to_rfc3339(),
-                                            };
-                                            let _ = app_handle.emit("claude:message", &msg);
-                                        }
-                                        ContentBlock::ToolUse { id, name, input } => {
-                                            let translated = translate_tool_event(name, input);
-                                            let activity = ActivityItem {
-                                                id: id.clone(),
-                                                description: translated.description,
-                                                raw_command: Some(translated.raw),
-                                                status: "running".to_string(),
-                                                timestamp: chrono::Utc::now().to_rfc3339(),
-                                            };
-                                            let _ = app_handle.emit("claude:activity", &activity);
-                                        }
-                                        _ => {}
-                                    }
-                                }
-                            }
-
-                            ClaudeStreamEvent::User { message, .. } => {
-                                // Tool results - mark activity as done
-                                if let Some(content) = message.get("content") {
-                                    if let Some(arr) = content.as_array() {
-                                        for item in arr {
-                                            if let Some(tool_id) = item.get("tool_use_id").and_then(|v| v.as_str()) {
-                                                let activity = ActivityItem {
-                                                    id: tool_id.to_string(),
-                                                    description: "完了".to_string(),
-                                                    raw_command: None,
-                                                    status: "done".to_string(),
-                                                    timestamp: chrono::Utc::now().to_rfc3339(),
-                                                };
-                                                let _ = app_handle.emit("claude:activity_done", &activity);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-
-                            ClaudeStreamEvent::Result { result, .. } => {
-                                let _ = app_handle.emit("claude:result", &event);
-
-                                if let Some(text) = result {
-                                    if !text.is_empty() && text != &current_text {
-                                        let msg = ChatMessage {
-                                            id: uuid::Uuid::new_v4().to_string(),
-                                            role: "assistant".to_string(),
-                                            content: text.clone(),
-                                            timestamp: chrono::Utc::now().to_rfc3339(),
-                                        };
-                                        let _ = app_handle.emit("claude:message", &msg);
-                                    }
-                                }
-                            }
-
-                            ClaudeStreamEvent::StreamEvent { event: evt, .. } => {
-                                // Forward text deltas for real-time streaming
-                                if let Some(delta) = evt.get("delta") {
-                                    if let Some(text) = delta.get("text").and_then(|t| t.as_str()) {
-                                        let _ = app_handle.emit("claude:text_delta", text);
-                                    }
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        log::warn!("Failed to parse stream line: {} - line: {}", e, &line[..line.len().min(200)]);
-                    }
+        let transcript = match (settings.output_mode, self.data_dir.lock().await.clone()) {
+            (OutputMode::CaptureRaw, Some(dir)) => match Transcript::open(&dir, &session_id).await {
+                Ok(t) => Some(t),
+                Err(e) => {
+                    log::warn!("{}", e);
+                    None
                 }
-            }
+            },
+            _ => None,
+        };
+        let batch_window =
+            std::time::Duration::from_millis(settings.delta_batch_ms.unwrap_or(DEFAULT_DELTA_BATCH_MS));
+        let stream_state = std::sync::Mutex::new(StreamState {
+            debug_raw: settings.debug_raw_events,
+            activity_level: settings.activity_level,
+            deltas: DeltaBatcher::new(batch_window),
+            ..Default::default()
         });
-
-        // Read stderr for errors
-        let app_handle2 = app.clone();
-        let stderr_task = tokio::spawn(async move {
-            let reader = BufReader::new(stderr);
-            let mut lines = reader.lines();
-            while let Ok(Some(line)) = lines.next_line().await {
+        let run = drive_process(
+            child,
+            |line| {
+                let mut state = stream_state.lock().unwrap();
+                let emits = on_stdout_line(&line, transcript.as_ref(), &mut state);
+                drop(state);
+                emit_stream(app, emits);
+            },
+            |line| {
                 if !line.trim().is_empty() {
                     log::warn!("claude stderr: {}", line);
-                    let _ = app_handle2.emit("claude:stderr", &line);
+                    let _ = app.emit("claude:stderr", &line);
                 }
-            }
+            },
+            run_timeout(&settings),
+        );
+        let run = flush_deltas_while(run, &stream_state, batch_window, |delta| {
+            let _ = app.emit("claude:text_delta", &delta);
         });
-
-        // Wait for process to finish
-        let status = child.wait().await.map_err(|e| format!("プロセスエラー: {}", e))?;
+        let heartbeat_app = app.clone();
+        // Stopped before claude:done is emitted below
+        let result = with_heartbeat(
+            run,
+            std::time::Duration::from_secs(settings.heartbeat_secs.unwrap_or(DEFAULT_HEARTBEAT_SECS)),
+            move |elapsed| {
+                let payload = HeartbeatEvent {
+                    elapsed_secs: elapsed.as_secs(),
+                };
+                let _ = heartbeat_app.emit("claude:heartbeat", &payload);
+            },
+        )
+        .await;
+        let mut stream_state = stream_state.into_inner().unwrap_or_else(|e| e.into_inner());
         *self.child_pid.lock().await = None;
-        let _ = stdout_task.await;
-        let _ = stderr_task.await;
+        self.finish_run(&session_id, is_resume, stream_state.saw_result).await;
+        if let Some(delta) = stream_state.deltas.flush() {
+            let _ = app.emit("claude:text_delta", &delta);
+        }
+        if let Some(t) = transcript {
+            t.finish().await;
+        }
+        if let Some(cost) = stream_state.turn_cost {
+            let total = self.add_session_cost(cost).await;
+            let _ = app.emit("claude:session_cost", total);
+        }
+        self.add_tool_counts(&stream_state.tool_counts).await;
+        let status = match result {
+            Ok(status) => status,
+            Err(e) => {
+                let _ = app.emit("claude:done", false);
+                return Err(e);
+            }
+        };
 
         // Signal completion to frontend
         let _ = app.emit("claude:done", status.success());
@@ -635,10 +1675,21 @@ mod tests {
         }"#;
         let event: ClaudeStreamEvent = serde_json::from_str(json_str).unwrap();
         match event {
-            ClaudeStreamEvent::Result { subtype, result, is_error, .. } => {
+            ClaudeStreamEvent::Result {
+                subtype,
+                result,
+                is_error,
+                duration_ms,
+                num_turns,
+                total_cost_usd,
+                ..
+            } => {
                 assert_eq!(subtype.unwrap(), "success");
                 assert_eq!(result.unwrap(), "Done!");
                 assert_eq!(is_error.unwrap(), false);
+                assert_eq!(duration_ms, Some(1234));
+                assert_eq!(num_turns, Some(2));
+                assert_eq!(total_cost_usd, Some(0.01));
             }
             _ => panic!("Expected Result event"),
         }
@@ -654,9 +1705,10 @@ mod tests {
         }"#;
         let event: ClaudeStreamEvent = serde_json::from_str(json_str).unwrap();
         match event {
-            ClaudeStreamEvent::Result { subtype, is_error, .. } => {
+            ClaudeStreamEvent::Result { subtype, is_error, total_cost_usd, .. } => {
                 assert_eq!(subtype.unwrap(), "error_max_turns");
                 assert_eq!(is_error.unwrap(), true);
+                assert!(total_cost_usd.is_none());
             }
             _ => panic!("Expected Result event"),
         }
@@ -682,6 +1734,238 @@ mod tests {
         }
     }
 
+    // ── Stream processing ──
+
+    fn event_name(emit: &StreamEmit) -> &'static str {
+        match emit {
+            StreamEmit::Event { event, .. } => *event,
+            StreamEmit::Message(_) => "claude:message",
+        }
+    }
+
+    fn payload(emit: &StreamEmit) -> &serde_json::Value {
+        match emit {
+            StreamEmit::Event { payload, .. } => payload,
+            StreamEmit::Message(msg) => panic!("Expected an event, got message {}", msg.id),
+        }
+    }
+
+    #[test]
+    fn test_unknown_event_type_is_recovered() {
+        let line = r#"{"type":"rate_limit_notice","text":"しばらくお待ちください","retry_after":30}"#;
+        assert!(serde_json::from_str::<ClaudeStreamEvent>(line).is_err());
+
+        let unknown = parse_unknown_event(line).unwrap();
+        assert_eq!(unknown.event_type.as_deref(), Some("rate_limit_notice"));
+        assert_eq!(unknown.text.as_deref(), Some("しばらくお待ちください"));
+        assert_eq!(unknown.raw["retry_after"], 30);
+    }
+
+    #[test]
+    fn test_unknown_content_block_keeps_text() {
+        let line = r#"{"type":"assistant","message":{"content":[
+            {"type":"thinking","thinking":"..."},
+            {"type":"text","text":"結論です"}
+        ]}}"#;
+        assert!(serde_json::from_str::<ClaudeStreamEvent>(line).is_err());
+
+        let unknown = parse_unknown_event(line).unwrap();
+        assert_eq!(unknown.event_type.as_deref(), Some("assistant"));
+        assert_eq!(unknown.text.as_deref(), Some("結論です"));
+    }
+
+    #[test]
+    fn test_raw_event_only_in_debug_mode() {
+        let line = r#"{"type":"system","subtype":"init","session_id":"s1"}"#;
+
+        let mut state = StreamState::default();
+        let events: Vec<&str> = process_stream_line(line, &mut state).iter().map(event_name).collect();
+        assert_eq!(events, vec!["claude:system"]);
+
+        let mut state = StreamState {
+            debug_raw: true,
+            ..Default::default()
+        };
+        let emitted = process_stream_line(line, &mut state);
+        assert_eq!(event_name(&emitted[0]), "claude:raw");
+        assert_eq!(*payload(&emitted[0]), serde_json::Value::String(line.to_string()));
+        assert_eq!(event_name(&emitted[1]), "claude:system");
+    }
+
+    #[test]
+    fn test_exit_plan_mode_emits_plan() {
+        let line = r#"{"type":"assistant","message":{"content":[
+            {"type":"tool_use","id":"toolu_p1","name":"ExitPlanMode","input":{"plan":"1. 調査\n2. 実装"}}
+        ]}}"#;
+        let mut state = StreamState::default();
+        let emitted = process_stream_line(line, &mut state);
+
+        let plan_mode = emitted
+            .iter()
+            .find(|e| event_name(e) == "claude:plan_mode")
+            .expect("plan_mode event");
+        assert_eq!(payload(plan_mode)["state"], "exited");
+        assert_eq!(payload(plan_mode)["plan"], "1. 調査\n2. 実装");
+        assert_eq!(payload(plan_mode)["tool_use_id"], "toolu_p1");
+        // The regular activity is still emitted
+        assert!(emitted.iter().any(|e| event_name(e) == "claude:activity"));
+    }
+
+    #[test]
+    fn test_writes_only_hides_read_activity() {
+        let read = r#"{"type":"assistant","message":{"content":[
+            {"type":"tool_use","id":"t_read","name":"Read","input":{"file_path":"/tmp/a.txt"}}
+        ]}}"#;
+        let write = r#"{"type":"assistant","message":{"content":[
+            {"type":"tool_use","id":"t_write","name":"Write","input":{"file_path":"/tmp/b.txt","content":"x"}}
+        ]}}"#;
+        let read_done = r#"{"type":"user","message":{"content":[
+            {"type":"tool_result","tool_use_id":"t_read","content":"a"}
+        ]}}"#;
+
+        let mut state = StreamState {
+            activity_level: ActivityLevel::WritesOnly,
+            ..Default::default()
+        };
+        let events = |emitted: Vec<StreamEmit>| emitted.iter().map(event_name).collect::<Vec<_>>();
+
+        assert!(!events(process_stream_line(read, &mut state)).contains(&"claude:activity"));
+        assert!(events(process_stream_line(write, &mut state)).contains(&"claude:activity"));
+        // The result of a hidden tool is still recorded in the chat
+        assert_eq!(events(process_stream_line(read_done, &mut state)), vec!["claude:message"]);
+
+        let mut state = StreamState::default();
+        assert!(events(process_stream_line(read, &mut state)).contains(&"claude:activity"));
+    }
+
+    #[test]
+    fn test_message_start_and_final_message_share_id() {
+        let start = r#"{"type":"stream_event","event":{"type":"message_start","message":{"id":"msg_01"}}}"#;
+        let delta = r#"{"type":"stream_event","event":{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"こん"}}}"#;
+        let assistant = r#"{"type":"assistant","message":{"id":"msg_01","content":[{"type":"text","text":"こんにちは"}]}}"#;
+        let mut state = StreamState::default();
+
+        let emitted = process_stream_line(start, &mut state);
+        assert_eq!(event_name(&emitted[0]), "claude:message_start");
+        let id = payload(&emitted[0])["id"].as_str().unwrap().to_string();
+        assert_eq!(id, "msg_01");
+
+        let emitted = process_stream_line(delta, &mut state);
+        assert_eq!(event_name(&emitted[0]), "claude:text_delta");
+        assert_eq!(payload(&emitted[0])["id"], id.as_str());
+        assert_eq!(payload(&emitted[0])["text"], "こん");
+
+        let emitted = process_stream_line(assistant, &mut state);
+        match &emitted[0] {
+            StreamEmit::Message(msg) => assert_eq!(msg.id, id),
+            other => panic!("Expected a message, got {:?}", other),
+        }
+        assert!(state.message_id.is_none());
+    }
+
+    #[test]
+    fn test_rapid_deltas_are_batched() {
+        let delta = |text: &str| {
+            format!(
+                r#"{{"type":"stream_event","event":{{"type":"content_block_delta","index":0,"delta":{{"type":"text_delta","text":"{}"}}}}}}"#,
+                text
+            )
+        };
+        let stop = r#"{"type":"stream_event","event":{"type":"content_block_stop","index":0}}"#;
+        let mut state = StreamState {
+            deltas: DeltaBatcher::new(std::time::Duration::from_secs(60)),
+            message_id: Some("msg_01".to_string()),
+            ..Default::default()
+        };
+
+        for text in ["こん", "にち", "は"] {
+            assert!(process_stream_line(&delta(text), &mut state).is_empty());
+        }
+        let emitted = process_stream_line(stop, &mut state);
+        assert_eq!(emitted.len(), 1);
+        assert_eq!(event_name(&emitted[0]), "claude:text_delta");
+        assert_eq!(payload(&emitted[0])["text"], "こんにちは");
+        assert_eq!(payload(&emitted[0])["id"], "msg_01");
+    }
+
+    #[test]
+    fn test_delta_batcher_flushes_after_interval() {
+        let start = std::time::Instant::now();
+        let window = std::time::Duration::from_millis(50);
+        let mut batcher = DeltaBatcher::new(window);
+
+        assert!(batcher.push("m".to_string(), "a", start).is_empty());
+        assert!(batcher.push("m".to_string(), "b", start + window / 2).is_empty());
+        let flushed = batcher.push("m".to_string(), "c", start + window);
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].text, "abc");
+
+        // A new message id sends the previous message's text first
+        assert!(batcher.push("m".to_string(), "d", start + window * 2).is_empty());
+        let flushed = batcher.push("n".to_string(), "e", start + window * 2);
+        assert_eq!(flushed.len(), 1);
+        assert_eq!(flushed[0].text, "d");
+        assert_eq!(batcher.flush().unwrap().text, "e");
+
+        // Without a window every delta goes out at once
+        let mut batcher = DeltaBatcher::default();
+        assert_eq!(batcher.push("m".to_string(), "x", start).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_flush_timer_sends_text_while_stream_is_quiet() {
+        let window = std::time::Duration::from_millis(20);
+        let state = std::sync::Mutex::new(StreamState {
+            deltas: DeltaBatcher::new(window),
+            ..Default::default()
+        });
+        let now = std::time::Instant::now();
+        assert!(state.lock().unwrap().deltas.push("m".to_string(), "途中", now).is_empty());
+
+        // No further stream lines arrive while the run is still going
+        let mut sent = Vec::new();
+        let sent_before_end = flush_deltas_while(
+            async {
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+                state.lock().unwrap().deltas.flush().is_none()
+            },
+            &state,
+            window,
+            |delta| sent.push(delta.text),
+        )
+        .await;
+        assert!(sent_before_end);
+        assert_eq!(sent, vec!["途中".to_string()]);
+
+        let mut batcher = DeltaBatcher::new(window);
+        batcher.push("m".to_string(), "x", now);
+        assert!(batcher.take_due(now).is_none());
+        assert_eq!(batcher.take_due(now + window).unwrap().text, "x");
+        assert!(batcher.take_due(now + window * 2).is_none());
+    }
+
+    #[test]
+    fn test_activity_level_serde() {
+        let settings: ClaudeSettings = serde_json::from_str(r#"{"activity_level":"writes_only"}"#).unwrap();
+        assert_eq!(settings.activity_level, ActivityLevel::WritesOnly);
+        let settings: ClaudeSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.activity_level, ActivityLevel::All);
+    }
+
+    #[test]
+    fn test_plan_mode_event_only_for_plan_tools() {
+        let entered = plan_mode_event("t1", "EnterPlanMode", &serde_json::json!({})).unwrap();
+        assert_eq!(entered.state, "entered");
+        assert!(entered.plan.is_none());
+        assert!(plan_mode_event("t2", "Read", &serde_json::json!({"plan": "x"})).is_none());
+    }
+
+    #[test]
+    fn test_unknown_event_non_json_dropped() {
+        assert!(parse_unknown_event("not json").is_none());
+        assert!(parse_unknown_event("[1,2]").is_none());
+    }
+
     // ── ContentBlock ──
 
     #[test]
@@ -708,8 +1992,223 @@ mod tests {
         }
     }
 
+    // ── Spawn errors ──
+
+    #[test]
+    fn test_spawn_error_not_found_gives_install_guidance() {
+        let err = std::io::Error::new(std::io::ErrorKind::NotFound, "No such file");
+        let msg = spawn_error_message(&err);
+        assert!(msg.contains("インストール"));
+        assert!(!msg.contains("No such file"));
+    }
+
+    #[test]
+    fn test_spawn_error_other_is_generic() {
+        let err = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "denied");
+        let msg = spawn_error_message(&err);
+        assert!(msg.contains("起動できませんでした"));
+        assert!(msg.contains("denied"));
+    }
+
+    // ── Process driving ──
+
+    #[test]
+    fn test_large_message_routed_to_stdin() {
+        let small = PromptInput::for_message("短いメッセージ".to_string());
+        assert!(matches!(small, PromptInput::Arg(_)));
+        let args = build_args(&small, "s1", false, &ClaudeSettings::default(), None);
+        assert_eq!(args.last().unwrap(), "短いメッセージ");
+
+        let log = "ERROR something failed\n".repeat(2000);
+        let large = PromptInput::for_message(log.clone());
+        assert_eq!(large, PromptInput::Stdin(log.clone()));
+        let args = build_args(&large, "s1", false, &ClaudeSettings::default(), None);
+        assert!(!args.contains(&log));
+        assert_eq!(args.last().unwrap(), "s1");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_feed_stdin_reaches_child() {
+        let mut child = Command::new("cat")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let text: String = (0..5000).map(|i| format!("line {}\n", i)).collect();
+        feed_stdin(&mut child, text).unwrap();
+
+        let mut lines = Vec::new();
+        let status = drive_process(
+            child,
+            |line| lines.push(line),
+            |_| {},
+            Some(std::time::Duration::from_secs(30)),
+        )
+        .await
+        .unwrap();
+        assert!(status.success());
+        assert_eq!(lines.len(), 5000);
+        assert_eq!(lines[4999], "line 4999");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_capture_raw_writes_transcript() {
+        let dir = std::env::temp_dir().join(format!("cowork-transcript-test-{}", uuid::Uuid::new_v4()));
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(r#"echo '{"type":"system","subtype":"init"}'; echo '{"type":"result","result":"ok"}'"#)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let transcript = Transcript::open(&dir, "s1").await.unwrap();
+        let mut state = StreamState::default();
+        let mut emitted = 0;
+        drive_process(
+            child,
+            |line| emitted += on_stdout_line(&line, Some(&transcript), &mut state).len(),
+            |_| {},
+            Some(std::time::Duration::from_secs(30)),
+        )
+        .await
+        .unwrap();
+        transcript.finish().await;
+
+        assert!(emitted > 0);
+        let content = std::fs::read_to_string(Transcript::path(&dir, "s1")).unwrap();
+        assert_eq!(
+            content,
+            "{\"type\":\"system\",\"subtype\":\"init\"}\n{\"type\":\"result\",\"result\":\"ok\"}\n"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_output_mode_serde() {
+        let settings: ClaudeSettings = serde_json::from_str(r#"{"output_mode":"capture_raw"}"#).unwrap();
+        assert_eq!(settings.output_mode, OutputMode::CaptureRaw);
+        let settings: ClaudeSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(settings.output_mode, OutputMode::Stream);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_drive_process_large_output_completes() {
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(r#"i=0; while [ $i -lt 20000 ]; do echo '{"type":"system","subtype":"init"}'; echo "warn $i" >&2; i=$((i+1)); done"#)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let mut stdout_lines = 0;
+        let mut stderr_lines = 0;
+        let status = drive_process(
+            child,
+            |_| stdout_lines += 1,
+            |_| stderr_lines += 1,
+            Some(std::time::Duration::from_secs(60)),
+        )
+        .await
+        .unwrap();
+
+        assert!(status.success());
+        assert_eq!(stdout_lines, 20000);
+        assert_eq!(stderr_lines, 20000);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_drive_process_timeout_kills_child() {
+        let child = Command::new("sleep")
+            .arg("30")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        let result = drive_process(
+            child,
+            |_| {},
+            |_| {},
+            Some(std::time::Duration::from_millis(200)),
+        )
+        .await;
+        assert!(result.unwrap_err().contains("タイムアウト"));
+    }
+
+    #[test]
+    fn test_run_timeout_from_settings() {
+        let minutes = |m| std::time::Duration::from_secs(m * 60);
+        assert_eq!(run_timeout(&ClaudeSettings::default()), Some(minutes(DEFAULT_RUN_TIMEOUT_MINS)));
+        let custom = ClaudeSettings {
+            run_timeout_mins: Some(180),
+            ..Default::default()
+        };
+        assert_eq!(run_timeout(&custom), Some(minutes(180)));
+        let unlimited = ClaudeSettings {
+            run_timeout_mins: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(run_timeout(&unlimited), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_heartbeat_runs_only_while_process_is_active() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let ticks = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&ticks);
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg("sleep 0.3")
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+
+        with_heartbeat(
+            drive_process(child, |_| {}, |_| {}, Some(std::time::Duration::from_secs(10))),
+            std::time::Duration::from_millis(50),
+            move |_| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        )
+        .await
+        .unwrap();
+
+        let during_run = ticks.load(Ordering::SeqCst);
+        assert!(during_run >= 2, "only {} heartbeats", during_run);
+        tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+        assert_eq!(ticks.load(Ordering::SeqCst), during_run);
+
+        assert!(Heartbeat::start(std::time::Duration::ZERO, |_| {}).is_none());
+    }
+
     // ── ClaudeManager ──
 
+    #[tokio::test]
+    async fn test_reload_settings_after_import() {
+        let dir = std::env::temp_dir().join(format!("cowork-claude-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mgr = ClaudeManager::new(Arc::new(Mutex::new(HashMap::new())));
+        mgr.set_data_dir(dir.clone()).await;
+        assert_eq!(mgr.get_settings().await.run_timeout_mins, None);
+
+        // A restored bundle replaces the file behind the manager's back
+        std::fs::write(dir.join("claude_settings.json"), r#"{"run_timeout_mins":0}"#).unwrap();
+        mgr.reload_settings().await;
+        assert_eq!(mgr.get_settings().await.run_timeout_mins, Some(0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[tokio::test]
     async fn test_manager_working_dir() {
         let pending = Arc::new(Mutex::new(HashMap::new()));
@@ -729,6 +2228,104 @@ mod tests {
         assert_eq!(mgr.get_working_dir().await, "/second");
     }
 
+    #[tokio::test]
+    async fn test_session_cost_accumulates_and_resets() {
+        let mgr = ClaudeManager::new(Arc::new(Mutex::new(HashMap::new())));
+        let lines = [
+            r#"{"type":"result","subtype":"success","result":"a","is_error":false,"total_cost_usd":0.0125}"#,
+            r#"{"type":"result","subtype":"success","result":"b","is_error":false,"total_cost_usd":0.0075}"#,
+        ];
+        for line in lines {
+            let mut state = StreamState::default();
+            process_stream_line(line, &mut state);
+            mgr.add_session_cost(state.turn_cost.unwrap()).await;
+        }
+        assert!((mgr.get_session_cost().await - 0.02).abs() < 1e-9);
+
+        mgr.reset_session().await;
+        assert_eq!(mgr.get_session_cost().await, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_no_working_dir_error_is_typed() {
+        let mgr = ClaudeManager::new(Arc::new(Mutex::new(HashMap::new())));
+        let err = mgr.require_working_dir().await.unwrap_err();
+        assert!(crate::util::is_no_working_dir_error(&err));
+        assert!(crate::util::is_no_working_dir_error(&mgr.snapshot_claude_config().await.unwrap_err()));
+
+        mgr.set_working_dir("/tmp".to_string()).await;
+        assert_eq!(mgr.require_working_dir().await.unwrap(), "/tmp");
+    }
+
+    #[tokio::test]
+    async fn test_cancel_and_discard_before_result_rolls_back_session() {
+        let mgr = ClaudeManager::new(Arc::new(Mutex::new(HashMap::new())));
+        let resumed_id = mgr.managed_session_id.lock().await.clone();
+        mgr.first_message_sent.store(true, std::sync::atomic::Ordering::Relaxed);
+
+        // Resumed session cancelled before any result keeps its id
+        *mgr.managed_session_id.lock().await = "changed-during-run".to_string();
+        mgr.cancel_and_discard().await.unwrap();
+        mgr.finish_run(&resumed_id, true, false).await;
+        assert_eq!(*mgr.managed_session_id.lock().await, resumed_id);
+        assert!(mgr.first_message_sent.load(std::sync::atomic::Ordering::Relaxed));
+
+        // A new session's cancelled first turn leaves nothing to resume
+        let first_id = "new-session".to_string();
+        *mgr.managed_session_id.lock().await = first_id.clone();
+        mgr.cancel_and_discard().await.unwrap();
+        mgr.finish_run(&first_id, false, false).await;
+        assert_ne!(*mgr.managed_session_id.lock().await, first_id);
+        assert!(!mgr.first_message_sent.load(std::sync::atomic::Ordering::Relaxed));
+
+        // Once a result arrived the turn is kept
+        mgr.first_message_sent.store(true, std::sync::atomic::Ordering::Relaxed);
+        let kept = mgr.managed_session_id.lock().await.clone();
+        mgr.cancel_and_discard().await.unwrap();
+        mgr.finish_run(&kept, false, true).await;
+        assert_eq!(*mgr.managed_session_id.lock().await, kept);
+        assert!(mgr.first_message_sent.load(std::sync::atomic::Ordering::Relaxed));
+
+        // A plain cancel never discards
+        let mut state = StreamState::default();
+        process_stream_line(r#"{"type":"result","subtype":"success","result":"ok"}"#, &mut state);
+        assert!(state.saw_result);
+        mgr.cancel().await.unwrap();
+        mgr.finish_run(&kept, false, false).await;
+        assert_eq!(*mgr.managed_session_id.lock().await, kept);
+    }
+
+    #[tokio::test]
+    async fn test_tool_stats_accumulate_and_reset() {
+        let mgr = ClaudeManager::new(Arc::new(Mutex::new(HashMap::new())));
+        let tool_use = |id: &str, name: &str| {
+            format!(
+                r#"{{"type":"assistant","message":{{"content":[{{"type":"tool_use","id":"{}","name":"{}","input":{{}}}}]}}}}"#,
+                id, name
+            )
+        };
+        let turns = [
+            vec![tool_use("t1", "Read"), tool_use("t2", "Read"), tool_use("t3", "Bash")],
+            vec![tool_use("t4", "Read"), tool_use("t5", "Write")],
+        ];
+        for lines in &turns {
+            let mut state = StreamState::default();
+            for line in lines {
+                process_stream_line(line, &mut state);
+            }
+            mgr.add_tool_counts(&state.tool_counts).await;
+        }
+
+        let stats = mgr.get_tool_stats().await;
+        assert_eq!(stats.get("Read"), Some(&3));
+        assert_eq!(stats.get("Bash"), Some(&1));
+        assert_eq!(stats.get("Write"), Some(&1));
+        assert_eq!(stats.len(), 3);
+
+        mgr.reset_session().await;
+        assert!(mgr.get_tool_stats().await.is_empty());
+    }
+
     // ── NDJSON multi-line parsing simulation ──
 
     #[test]
@@ -764,4 +2361,180 @@ mod tests {
         let event: ClaudeStreamEvent = serde_json::from_str(json_str).unwrap();
         assert!(matches!(event, ClaudeStreamEvent::System { .. }));
     }
+
+    // ── Claude Code settings.json ──
+
+    #[test]
+    fn test_read_claude_settings_file() {
+        let home = std::env::temp_dir().join(format!("cowork-claude-test-{}", uuid::Uuid::new_v4()));
+        let path = claude_settings_path_in(&home);
+        assert!(read_claude_settings_file(&path).unwrap_err().contains("見つかりません"));
+
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, r#"{"hooks":{"PreToolUse":[{"matcher":"*"}]}}"#).unwrap();
+        let content = read_claude_settings_file(&path).unwrap();
+        assert!(content.contains("\n"));
+        let parsed: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed["hooks"]["PreToolUse"][0]["matcher"], "*");
+
+        std::fs::write(&path, "{ broken").unwrap();
+        assert_eq!(read_claude_settings_file(&path).unwrap(), "{ broken");
+
+        let _ = std::fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn test_check_hook_status() {
+        let dir = std::env::temp_dir().join(format!("cowork-claude-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let script = hook_script_path(&dir);
+        std::fs::write(&script, "// hook").unwrap();
+        let settings_with = |command: &str| {
+            serde_json::json!({"hooks": {"PreToolUse": [
+                {"matcher": "Bash", "hooks": [{"type": "command", "command": "other-hook"}]},
+                {"matcher": "", "hooks": [{"type": "command", "command": command}]}
+            ]}})
+        };
+
+        // Missing hook
+        let status = check_hook(&serde_json::json!({"model": "opus"}), &script);
+        assert!(!status.entry_exists && !status.script_exists && !status.path_matches);
+        assert!(status.command.is_none());
+
+        // Stale path left behind by an app move
+        let stale = settings_with("node /old/place/cowork-hook.cjs");
+        let status = check_hook(&stale, &script);
+        assert!(status.entry_exists);
+        assert!(!status.script_exists);
+        assert!(!status.path_matches);
+        assert!(!status.is_ok());
+
+        // Correctly installed
+        let installed = settings_with(&hook_command_for(&script));
+        let status = check_hook(&installed, &script);
+        assert!(status.is_ok());
+
+        // Registered but the script was deleted
+        std::fs::remove_file(&script).unwrap();
+        let status = check_hook(&installed, &script);
+        assert!(status.entry_exists && status.path_matches);
+        assert!(!status.script_exists);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_hook_command_quotes_spaces() {
+        let status = check_hook(
+            &serde_json::json!({"hooks": {"PreToolUse": [
+                {"hooks": [{"command": "node '/Library/Application Support/cowork-hook.cjs'"}]}
+            ]}}),
+            Path::new("/Library/Application Support/cowork-hook.cjs"),
+        );
+        assert!(status.path_matches);
+    }
+
+    // ── CLI argument building ──
+
+    #[test]
+    fn test_build_args_with_append_system_prompt() {
+        let settings = ClaudeSettings {
+            append_system_prompt: Some("常に日本語で回答してください".to_string()),
+            ..Default::default()
+        };
+        let args = build_args(&PromptInput::for_message("hello".to_string()), "s1", false, &settings, None);
+        let idx = args.iter().position(|a| a == "--append-system-prompt").unwrap();
+        assert!(args[idx + 1].starts_with(APP_SYSTEM_PROMPT));
+        assert!(args[idx + 1].ends_with("常に日本語で回答してください"));
+        assert_eq!(args.iter().filter(|a| *a == "--append-system-prompt").count(), 1);
+        assert_eq!(args.last().unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_build_args_without_append_system_prompt() {
+        let args = build_args(&PromptInput::for_message("hello".to_string()), "s1", true, &ClaudeSettings::default(), None);
+        let idx = args.iter().position(|a| a == "--append-system-prompt").unwrap();
+        assert_eq!(args[idx + 1], APP_SYSTEM_PROMPT);
+        assert!(args.windows(2).any(|w| w[0] == "--resume" && w[1] == "s1"));
+        assert!(args.contains(&"--include-partial-messages".to_string()));
+
+        // Blank guidance is treated as unset
+        let blank = ClaudeSettings {
+            append_system_prompt: Some("   ".to_string()),
+            ..Default::default()
+        };
+        let args = build_args(&PromptInput::for_message("hello".to_string()), "s1", false, &blank, None);
+        assert!(args.contains(&APP_SYSTEM_PROMPT.to_string()));
+        assert!(args.windows(2).any(|w| w[0] == "--session-id" && w[1] == "s1"));
+    }
+
+    #[test]
+    fn test_build_args_mcp_config() {
+        let wd = std::env::temp_dir().join(format!("cowork-mcp-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(wd.join(".cowork")).unwrap();
+        let wd_str = wd.to_string_lossy().to_string();
+        let prompt = PromptInput::for_message("hello".to_string());
+
+        assert_eq!(find_mcp_config(&wd_str).unwrap(), None);
+        let args = build_args(&prompt, "s1", false, &ClaudeSettings::default(), None);
+        assert!(!args.contains(&"--mcp-config".to_string()));
+
+        let mcp = wd.join(".cowork").join("mcp.json");
+        std::fs::write(&mcp, r#"{"mcpServers":{"fs":{"command":"npx","args":["server-fs"]}}}"#).unwrap();
+        let found = find_mcp_config(&wd_str).unwrap();
+        assert_eq!(found.as_deref(), Some(mcp.as_path()));
+        let args = build_args(&prompt, "s1", false, &ClaudeSettings::default(), found.as_deref());
+        let idx = args.iter().position(|a| a == "--mcp-config").unwrap();
+        assert_eq!(args[idx + 1], mcp.to_string_lossy());
+        assert_eq!(args.last().unwrap(), "hello");
+
+        std::fs::write(&mcp, "{ not json").unwrap();
+        let err = find_mcp_config(&wd_str).unwrap_err();
+        assert!(err.contains("mcp.json"));
+
+        let _ = std::fs::remove_dir_all(&wd);
+    }
+
+    // ── Tool results ──
+
+    #[test]
+    fn test_tool_result_message_string_content() {
+        let item = serde_json::json!({
+            "type": "tool_result",
+            "tool_use_id": "t1",
+            "content": "file1.txt\nfile2.txt"
+        });
+        let msg = tool_result_message("Bash", &item);
+        assert_eq!(msg.role, "tool");
+        assert_eq!(msg.content, "Bash: file1.txt\nfile2.txt");
+    }
+
+    #[test]
+    fn test_tool_result_message_blocks_error_and_truncation() {
+        let long = "あ".repeat(TOOL_RESULT_MAX_CHARS + 10);
+        let item = serde_json::json!({
+            "type": "tool_result",
+            "tool_use_id": "t1",
+            "is_error": true,
+            "content": [{"type": "text", "text": long}]
+        });
+        let msg = tool_result_message("Read", &item);
+        assert!(msg.content.starts_with("Read（エラー）: "));
+        assert!(msg.content.ends_with("..."));
+    }
+
+    #[test]
+    fn test_tool_role_chat_message_roundtrip() {
+        let msg = ChatMessage {
+            id: "m1".to_string(),
+            role: "tool".to_string(),
+            content: "Bash: ok".to_string(),
+            timestamp: "2026-02-07T00:00:00Z".to_string(),
+        };
+        let json = serde_json::to_string(&vec![msg]).unwrap();
+        assert!(json.contains(r#""role":"tool""#));
+        let parsed: Vec<ChatMessage> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed[0].role, "tool");
+        assert_eq!(parsed[0].content, "Bash: ok");
+    }
 }
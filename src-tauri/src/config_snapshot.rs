@@ -0,0 +1,284 @@
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+
+/// A path in `dir` for a snapshot taken now. Names carry milliseconds and
+/// get a ` (n)` suffix if one is still taken, so snapshots never replace
+/// each other.
+pub fn snapshot_path(dir: &Path) -> PathBuf {
+    let name = format!("claude-config-{}.zip", chrono::Local::now().format("%Y%m%d-%H%M%S-%3f"));
+    crate::files::unique_destination(dir, &name)
+}
+
+/// Zip every file under `src_dir` into `archive`, with paths relative to `src_dir`
+pub fn snapshot_dir(src_dir: &Path, archive: &Path) -> Result<usize, String> {
+    if !src_dir.is_dir() {
+        return Err(format!("フォルダが見つかりません: {}", src_dir.display()));
+    }
+    if let Some(parent) = archive.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+    }
+    // Never overwrite an earlier snapshot
+    let file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(archive)
+        .map_err(|e| format!("スナップショットを作成できませんでした: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut count = 0;
+    let mut stack = vec![src_dir.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("フォルダを読み込めませんでした: {}", e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            // Zip entry names always use '/'
+            let name = entry_name(src_dir, &path);
+            if file_type.is_dir() {
+                zip.add_directory(name, options)
+                    .map_err(|e| format!("スナップショットの書き込みに失敗: {}", e))?;
+                stack.push(path);
+            } else if file_type.is_file() {
+                let mut content = Vec::new();
+                std::fs::File::open(&path)
+                    .and_then(|mut f| f.read_to_end(&mut content))
+                    .map_err(|e| format!("ファイルを読み込めませんでした: {}", e))?;
+                zip.start_file(name, options)
+                    .map_err(|e| format!("スナップショットの書き込みに失敗: {}", e))?;
+                zip.write_all(&content)
+                    .map_err(|e| format!("スナップショットの書き込みに失敗: {}", e))?;
+                count += 1;
+            }
+        }
+    }
+    zip.finish()
+        .map_err(|e| format!("スナップショットの書き込みに失敗: {}", e))?;
+    Ok(count)
+}
+
+fn entry_name(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// A zip entry name as a relative path, or `None` if it could escape the
+/// destination (absolute, `..`, drive prefixes)
+fn safe_entry_path(name: &str) -> Option<PathBuf> {
+    let path = Path::new(name);
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => out.push(part),
+            Component::CurDir => {}
+            _ => return None,
+        }
+    }
+    if out.as_os_str().is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+/// Replace `dest_dir` with the contents of `archive`. Every entry is checked
+/// before anything is written; extraction goes to a sibling folder first and
+/// the current config is only deleted once the new one is in place, so a
+/// failed restore leaves it untouched.
+pub fn restore_dir(archive: &Path, dest_dir: &Path) -> Result<usize, String> {
+    let file = std::fs::File::open(archive)
+        .map_err(|e| format!("スナップショットを開けませんでした: {}", e))?;
+    let mut zip = zip::ZipArchive::new(file)
+        .map_err(|e| format!("スナップショットを読み込めませんでした: {}", e))?;
+
+    let mut entries = Vec::with_capacity(zip.len());
+    for i in 0..zip.len() {
+        let entry = zip
+            .by_index(i)
+            .map_err(|e| format!("スナップショットを読み込めませんでした: {}", e))?;
+        let path = safe_entry_path(entry.name())
+            .ok_or_else(|| format!("不正なパスが含まれています: {}", entry.name()))?;
+        entries.push((path, entry.is_dir()));
+    }
+
+    let staging = dest_dir.with_extension("restoring");
+    if staging.exists() {
+        std::fs::remove_dir_all(&staging)
+            .map_err(|e| format!("一時フォルダを削除できませんでした: {}", e))?;
+    }
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+
+    let mut count = 0;
+    for (i, (path, is_dir)) in entries.iter().enumerate() {
+        let target = staging.join(path);
+        if *is_dir {
+            std::fs::create_dir_all(&target)
+                .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+        }
+        let mut entry = zip
+            .by_index(i)
+            .map_err(|e| format!("スナップショットを読み込めませんでした: {}", e))?;
+        let mut out = std::fs::File::create(&target)
+            .map_err(|e| format!("ファイルを書き込めませんでした: {}", e))?;
+        std::io::copy(&mut entry, &mut out)
+            .map_err(|e| format!("ファイルを書き込めませんでした: {}", e))?;
+        count += 1;
+    }
+
+    swap_in(&staging, dest_dir)?;
+    Ok(count)
+}
+
+/// Move `staging` to `dest_dir`, keeping the old `dest_dir` aside until the
+/// move has succeeded and putting it back if it fails
+fn swap_in(staging: &Path, dest_dir: &Path) -> Result<(), String> {
+    let previous = dest_dir.with_extension("previous");
+    if previous.exists() {
+        std::fs::remove_dir_all(&previous)
+            .map_err(|e| format!("一時フォルダを削除できませんでした: {}", e))?;
+    }
+    let had_previous = dest_dir.exists();
+    if had_previous {
+        std::fs::rename(dest_dir, &previous)
+            .map_err(|e| format!("既存の設定を退避できませんでした: {}", e))?;
+    }
+    if let Err(e) = std::fs::rename(staging, dest_dir) {
+        if had_previous {
+            let _ = std::fs::rename(&previous, dest_dir);
+        }
+        let _ = std::fs::remove_dir_all(staging);
+        return Err(format!("設定を復元できませんでした: {}", e));
+    }
+    if had_previous {
+        if let Err(e) = std::fs::remove_dir_all(&previous) {
+            log::warn!("Failed to remove previous config {}: {}", previous.display(), e);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("cowork-snapshot-test-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_snapshot_and_restore_round_trip() {
+        let root = temp_dir();
+        let claude = root.join("work").join(".claude");
+        std::fs::create_dir_all(claude.join("skills").join("report")).unwrap();
+        std::fs::write(claude.join("skills").join("report").join("SKILL.md"), "---\nname: report\n---\n週報\n").unwrap();
+        std::fs::write(claude.join("settings.json"), "{\"hooks\":{}}").unwrap();
+
+        let archive = snapshot_path(&root.join("data").join("snapshots"));
+        assert_eq!(snapshot_dir(&claude, &archive).unwrap(), 2);
+
+        let restored = root.join("clean").join(".claude");
+        assert_eq!(restore_dir(&archive, &restored).unwrap(), 2);
+        assert_eq!(
+            std::fs::read_to_string(restored.join("skills").join("report").join("SKILL.md")).unwrap(),
+            "---\nname: report\n---\n週報\n"
+        );
+        assert_eq!(std::fs::read_to_string(restored.join("settings.json")).unwrap(), "{\"hooks\":{}}");
+
+        // Restoring over an existing config replaces it
+        std::fs::write(restored.join("extra.txt"), "x").unwrap();
+        restore_dir(&archive, &restored).unwrap();
+        assert!(!restored.join("extra.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_snapshots_in_the_same_second_are_kept() {
+        let root = temp_dir();
+        let claude = root.join(".claude");
+        std::fs::create_dir_all(&claude).unwrap();
+        std::fs::write(claude.join("settings.json"), "{}").unwrap();
+
+        let snapshots = root.join("snapshots");
+        let first = snapshot_path(&snapshots);
+        snapshot_dir(&claude, &first).unwrap();
+        let second = snapshot_path(&snapshots);
+        assert_ne!(first, second);
+        snapshot_dir(&claude, &second).unwrap();
+        assert!(first.is_file() && second.is_file());
+
+        // An existing archive is never overwritten
+        assert!(snapshot_dir(&claude, &first).is_err());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_swap_in_keeps_old_config_until_replaced() {
+        let root = temp_dir();
+        let dest = root.join(".claude");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("old.txt"), "old").unwrap();
+
+        // A missing staging folder fails the move; the old config survives
+        assert!(swap_in(&root.join("missing"), &dest).is_err());
+        assert_eq!(std::fs::read_to_string(dest.join("old.txt")).unwrap(), "old");
+
+        let staging = root.join(".claude.restoring");
+        std::fs::create_dir_all(&staging).unwrap();
+        std::fs::write(staging.join("new.txt"), "new").unwrap();
+        swap_in(&staging, &dest).unwrap();
+        assert!(dest.join("new.txt").exists());
+        assert!(!dest.join("old.txt").exists());
+        assert!(!root.join(".claude.previous").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_restore_rejects_traversal() {
+        let root = temp_dir();
+        std::fs::create_dir_all(&root).unwrap();
+        let archive = root.join("evil.zip");
+        let mut zip = zip::ZipWriter::new(std::fs::File::create(&archive).unwrap());
+        zip.start_file("../outside.txt", SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"x").unwrap();
+        zip.finish().unwrap();
+
+        let dest = root.join("work").join(".claude");
+        std::fs::create_dir_all(&dest).unwrap();
+        std::fs::write(dest.join("keep.txt"), "keep").unwrap();
+
+        let err = restore_dir(&archive, &dest).unwrap_err();
+        assert!(err.contains("不正なパス"));
+        assert!(!root.join("work").join("outside.txt").exists());
+        assert!(dest.join("keep.txt").exists());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_safe_entry_path() {
+        assert_eq!(safe_entry_path("skills/a/SKILL.md"), Some(PathBuf::from("skills/a/SKILL.md")));
+        assert_eq!(safe_entry_path("./a.txt"), Some(PathBuf::from("a.txt")));
+        assert_eq!(safe_entry_path("../a.txt"), None);
+        assert_eq!(safe_entry_path("/etc/passwd"), None);
+        assert_eq!(safe_entry_path(""), None);
+    }
+}
@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::fs;
+use tokio::sync::mpsc;
+
+use crate::gitignore::IgnoreMatcher;
+use crate::util::truncate_chars;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
@@ -13,6 +18,14 @@ pub struct FileEntry {
 
 /// List files/directories at a given path (non-recursive, one level)
 pub async fn list_directory(path: &str) -> Result<Vec<FileEntry>, String> {
+    list_directory_filtered(path, None).await
+}
+
+/// Like `list_directory`, additionally dropping entries matched by `ignore`
+pub async fn list_directory_filtered(
+    path: &str,
+    ignore: Option<&IgnoreMatcher>,
+) -> Result<Vec<FileEntry>, String> {
     let dir_path = Path::new(path);
     if !dir_path.is_dir() {
         return Err(format!("フォルダではありません: {}", path));
@@ -37,6 +50,9 @@ pub async fn list_directory(path: &str) -> Result<Vec<FileEntry>, String> {
 
         let metadata = entry.metadata().await.ok();
         let is_dir = metadata.as_ref().map_or(false, |m| m.is_dir());
+        if ignore.is_some_and(|m| m.is_ignored(&entry.path(), is_dir)) {
+            continue;
+        }
         let size = if is_dir {
             None
         } else {
@@ -83,8 +99,546 @@ pub async fn get_file_tree(path: &str) -> Result<FileEntry, String> {
     })
 }
 
+/// Files larger than this are skipped by content search
+const SEARCH_MAX_FILE_SIZE: u64 = 1024 * 1024;
+/// Matched lines longer than this are cut for display
+const SEARCH_MAX_LINE_CHARS: usize = 200;
+
+/// A single line matching a content search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    pub path: String,
+    pub line_number: usize,
+    pub line: String,
+}
+
+/// Walk `root` and send each line containing `query` (case-insensitive) to `tx`
+/// as soon as it is found. Hidden entries, large files and non-UTF-8 files are
+/// skipped, as are paths matched by `ignore`. Stops early when `cancel` is set or the receiver is dropped.
+/// Returns the number of matches sent.
+pub async fn search_directory(
+    root: &Path,
+    query: &str,
+    ignore: Option<&IgnoreMatcher>,
+    cancel: &AtomicBool,
+    tx: &mpsc::Sender<SearchMatch>,
+) -> Result<usize, String> {
+    if !root.is_dir() {
+        return Err(format!("フォルダではありません: {}", root.display()));
+    }
+    let needle = query.to_lowercase();
+    if needle.is_empty() {
+        return Ok(0);
+    }
+
+    let mut count = 0;
+    let mut stack: Vec<PathBuf> = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let mut read_dir = match fs::read_dir(&dir).await {
+            Ok(rd) => rd,
+            Err(_) => continue,
+        };
+        let mut children = Vec::new();
+        while let Ok(Some(entry)) = read_dir.next_entry().await {
+            children.push(entry);
+        }
+        children.sort_by_key(|e| e.file_name());
+
+        for entry in children {
+            if cancel.load(Ordering::Relaxed) {
+                return Ok(count);
+            }
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let metadata = match entry.metadata().await {
+                Ok(m) => m,
+                Err(_) => continue,
+            };
+            let path = entry.path();
+            if ignore.is_some_and(|m| m.is_ignored(&path, metadata.is_dir())) {
+                continue;
+            }
+            if metadata.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if metadata.len() > SEARCH_MAX_FILE_SIZE {
+                continue;
+            }
+            let content = match fs::read_to_string(&path).await {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            for (i, line) in content.lines().enumerate() {
+                if !line.to_lowercase().contains(&needle) {
+                    continue;
+                }
+                let found = SearchMatch {
+                    path: path.to_string_lossy().to_string(),
+                    line_number: i + 1,
+                    line: truncate_chars(line.trim(), SEARCH_MAX_LINE_CHARS),
+                };
+                if tx.send(found).await.is_err() {
+                    return Ok(count);
+                }
+                count += 1;
+            }
+        }
+    }
+
+    Ok(count)
+}
+
+/// Line/word/byte counts of a file (like `wc`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileStats {
+    pub lines: u64,
+    pub words: u64,
+    pub bytes: u64,
+    /// When true only `bytes` is meaningful
+    pub is_binary: bool,
+}
+
+/// Count lines, words and bytes by streaming the file in chunks.
+/// Files containing a NUL byte are reported as binary.
+pub async fn file_stats(path: &str) -> Result<FileStats, String> {
+    use tokio::io::AsyncReadExt;
+
+    let mut file = fs::File::open(path)
+        .await
+        .map_err(|e| format!("ファイルを開けませんでした: {}", e))?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut stats = FileStats {
+        lines: 0,
+        words: 0,
+        bytes: 0,
+        is_binary: false,
+    };
+    let mut in_word = false;
+
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        stats.bytes += n as u64;
+        if stats.is_binary {
+            continue;
+        }
+        for &b in &buf[..n] {
+            if b == 0 {
+                stats.is_binary = true;
+                break;
+            }
+            if b == b'\n' {
+                stats.lines += 1;
+            }
+            if b.is_ascii_whitespace() {
+                in_word = false;
+            } else if !in_word {
+                in_word = true;
+                stats.words += 1;
+            }
+        }
+    }
+
+    if stats.is_binary {
+        stats.lines = 0;
+        stats.words = 0;
+    }
+    Ok(stats)
+}
+
+/// Hex digest of a file, read in chunks. `algorithm` is "sha256" or "md5".
+pub async fn hash_file(path: &str, algorithm: &str) -> Result<String, String> {
+    let file = match fs::File::open(path).await {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(format!("ファイルが見つかりません: {}", path));
+        }
+        Err(e) => return Err(format!("ファイルを開けませんでした: {}", e)),
+    };
+    let digest = match algorithm.to_ascii_lowercase().replace('-', "").as_str() {
+        "sha256" => digest_file::<sha2::Sha256>(file).await?,
+        "md5" => digest_file::<md5::Md5>(file).await?,
+        other => return Err(format!("対応していないハッシュ方式です: {}", other)),
+    };
+    Ok(digest.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+async fn digest_file<D: sha2::Digest>(mut file: fs::File) -> Result<Vec<u8>, String> {
+    use tokio::io::AsyncReadExt;
+
+    let mut hasher = D::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+/// Largest file `diff_files` will compare
+const DIFF_MAX_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Unified diff of two text files with `context` lines around each change.
+/// Empty when the files have the same text.
+pub async fn diff_files(a: &str, b: &str, context: usize) -> Result<String, String> {
+    let old = read_text_for_diff(a).await?;
+    let new = read_text_for_diff(b).await?;
+    crate::util::unified_diff(&old, &new, a, b, context)
+}
+
+async fn read_text_for_diff(path: &str) -> Result<String, String> {
+    let meta = fs::metadata(path)
+        .await
+        .map_err(|_| format!("ファイルが見つかりません: {}", path))?;
+    if meta.len() > DIFF_MAX_FILE_SIZE {
+        return Err(format!("ファイルが大きすぎるため比較できません: {}", path));
+    }
+    let bytes = fs::read(path)
+        .await
+        .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+    if bytes.contains(&0) {
+        return Err(format!("バイナリファイルは比較できません: {}", path));
+    }
+    String::from_utf8(bytes).map_err(|_| format!("テキストファイルではありません: {}", path))
+}
+
+/// Chunk size used when scanning a file backwards
+const TAIL_CHUNK_SIZE: u64 = 8 * 1024;
+/// Most bytes a file preview may load into memory
+const MAX_PREVIEW_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Return the last `lines` lines of a file without reading all of it.
+/// A trailing newline at the end of the file does not count as an extra line.
+pub async fn read_file_tail(path: &str, lines: usize) -> Result<String, String> {
+    read_file_tail_limited(path, lines, MAX_PREVIEW_BYTES).await
+}
+
+/// `read_file_tail` that refuses to hold more than `max_bytes` of the file,
+/// e.g. a huge log without line breaks
+async fn read_file_tail_limited(path: &str, lines: usize, max_bytes: u64) -> Result<String, String> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    let mut file = fs::File::open(path)
+        .await
+        .map_err(|e| format!("ファイルを開けませんでした: {}", e))?;
+    let len = file
+        .metadata()
+        .await
+        .map_err(|e| format!("ファイル情報を読み込めませんでした: {}", e))?
+        .len();
+    if lines == 0 || len == 0 {
+        return Ok(String::new());
+    }
+
+    // Bytes collected from the end, in file order
+    let mut tail: Vec<u8> = Vec::new();
+    let mut pos = len;
+    let mut newlines = 0;
+    let mut skip_trailing = true;
+
+    while pos > 0 {
+        if len - pos >= max_bytes {
+            return Err(format!(
+                "末尾{}行が大きすぎるため表示できません（上限 {}）。行数を減らしてください",
+                lines,
+                format_size(max_bytes)
+            ));
+        }
+        let read_len = TAIL_CHUNK_SIZE.min(pos);
+        pos -= read_len;
+        let mut chunk = vec![0u8; read_len as usize];
+        file.seek(std::io::SeekFrom::Start(pos))
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+        file.read_exact(&mut chunk)
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+
+        let mut cut = None;
+        for (i, &b) in chunk.iter().enumerate().rev() {
+            if b != b'\n' {
+                skip_trailing = false;
+                continue;
+            }
+            if skip_trailing {
+                skip_trailing = false;
+                continue;
+            }
+            newlines += 1;
+            if newlines == lines {
+                cut = Some(i);
+                break;
+            }
+        }
+
+        if let Some(i) = cut {
+            chunk.drain(..=i);
+        }
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+        if cut.is_some() {
+            break;
+        }
+    }
+
+    Ok(String::from_utf8_lossy(&tail).to_string())
+}
+
+/// How often a followed file is checked for new content
+const FOLLOW_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(300);
+
+/// A line appended to a followed file
+#[derive(Debug, Clone, Serialize)]
+pub struct FileLine {
+    pub path: String,
+    pub line: String,
+}
+
+/// Reads the complete lines appended to a file since the last poll
+struct TailReader {
+    path: PathBuf,
+    pos: u64,
+    /// Bytes of a line whose newline hasn't been written yet
+    partial: Vec<u8>,
+}
+
+impl TailReader {
+    fn open(path: &Path, from_end: bool) -> Result<Self, String> {
+        let len = std::fs::metadata(path)
+            .map_err(|e| format!("ファイルを開けませんでした: {}", e))?
+            .len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            pos: if from_end { len } else { 0 },
+            partial: Vec::new(),
+        })
+    }
+
+    fn poll(&mut self) -> std::io::Result<Vec<String>> {
+        use std::io::{Read, Seek};
+
+        let mut file = std::fs::File::open(&self.path)?;
+        let len = file.metadata()?.len();
+        if len < self.pos {
+            // Truncated or replaced by a rotated log: start over
+            self.pos = 0;
+            self.partial.clear();
+        }
+        if len == self.pos {
+            return Ok(Vec::new());
+        }
+
+        file.seek(std::io::SeekFrom::Start(self.pos))?;
+        let mut buf = Vec::new();
+        file.take(len - self.pos).read_to_end(&mut buf)?;
+        self.pos += buf.len() as u64;
+        self.partial.extend_from_slice(&buf);
+
+        let mut lines = Vec::new();
+        while let Some(i) = self.partial.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = self.partial.drain(..=i).collect();
+            let line = String::from_utf8_lossy(&line[..i]);
+            lines.push(line.trim_end_matches('\r').to_string());
+        }
+        Ok(lines)
+    }
+}
+
+/// Send each line appended to `path` to `tx` (like `tail -f`), starting at
+/// the end of the file when `from_end` is set. A truncated or rotated file
+/// is read again from the start. Stops when `stop` is set or the receiver is dropped.
+pub async fn follow_file(
+    path: &Path,
+    from_end: bool,
+    stop: &AtomicBool,
+    tx: &mpsc::Sender<String>,
+) -> Result<(), String> {
+    let mut reader = TailReader::open(path, from_end)?;
+    while !stop.load(Ordering::Relaxed) {
+        match reader.poll() {
+            Ok(lines) => {
+                for line in lines {
+                    if tx.send(line).await.is_err() {
+                        return Ok(());
+                    }
+                }
+            }
+            // The file may be briefly missing while a log is rotated
+            Err(e) => log::debug!("Follow {} failed: {}", path.display(), e),
+        }
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+    }
+    Ok(())
+}
+
+/// Path, size and mtime of every file under `dir`, sorted. Empty if `dir`
+/// doesn't exist, so creating it counts as a change too.
+fn dir_fingerprint(dir: &Path) -> Vec<(PathBuf, u64, Option<std::time::SystemTime>)> {
+    let mut out = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            if meta.is_dir() {
+                stack.push(entry.path());
+            } else {
+                out.push((entry.path(), meta.len(), meta.modified().ok()));
+            }
+        }
+    }
+    out.sort();
+    out
+}
+
+/// Poll `dir` until `stop` is set, sending `()` once a burst of changes has
+/// settled (nothing changed for one poll interval).
+pub async fn watch_dir(dir: &Path, stop: &AtomicBool, tx: &mpsc::Sender<()>) {
+    let mut last = dir_fingerprint(dir);
+    let mut dirty = false;
+    while !stop.load(Ordering::Relaxed) {
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        let current = dir_fingerprint(dir);
+        if current != last {
+            last = current;
+            dirty = true;
+        } else if dirty {
+            dirty = false;
+            if tx.send(()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// Marker files and the project tags they indicate
+const PROJECT_MARKERS: &[(&str, &str)] = &[
+    ("Cargo.toml", "rust"),
+    ("package.json", "node"),
+    ("pyproject.toml", "python"),
+    ("requirements.txt", "python"),
+    (".git", "git"),
+];
+
+/// Detect what kind of project `path` is from its marker files,
+/// e.g. `["rust", "git"]`. Tags are unique and in `PROJECT_MARKERS` order.
+pub fn detect_project_type(path: &str) -> Vec<String> {
+    let dir = Path::new(path);
+    let mut tags: Vec<String> = Vec::new();
+    for (marker, tag) in PROJECT_MARKERS {
+        if dir.join(marker).exists() && !tags.iter().any(|t| t == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    tags
+}
+
+/// Pick a path in `dir` for `filename` that doesn't exist yet, appending
+/// ` (1)`, ` (2)`, ... before the extension as needed
+pub fn unique_destination(dir: &Path, filename: &str) -> PathBuf {
+    let candidate = dir.join(filename);
+    if !candidate.exists() {
+        return candidate;
+    }
+
+    let name = Path::new(filename);
+    let stem = name
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| filename.to_string());
+    let ext = name
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut n = 1;
+    loop {
+        let candidate = dir.join(format!("{} ({}){}", stem, n, ext));
+        if !candidate.exists() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
+
+/// Get (total, available) bytes of the filesystem containing `path`
+pub fn get_disk_space(path: &str) -> Result<(u64, u64), String> {
+    let target = Path::new(path);
+    if !target.exists() {
+        return Err(format!("パスが見つかりません: {}", path));
+    }
+    disk_space_impl(target)
+}
+
+#[cfg(unix)]
+#[allow(clippy::unnecessary_cast)]
+fn disk_space_impl(path: &Path) -> Result<(u64, u64), String> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| format!("不正なパスです: {}", path.display()))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    let rc = unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) };
+    if rc != 0 {
+        return Err(format!(
+            "ディスク容量を取得できませんでした: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    let block = stat.f_frsize as u64;
+    Ok((stat.f_blocks as u64 * block, stat.f_bavail as u64 * block))
+}
+
+#[cfg(windows)]
+fn disk_space_impl(path: &Path) -> Result<(u64, u64), String> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_to_caller: *mut u64,
+            total: *mut u64,
+            total_free: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(std::iter::once(0)).collect();
+    let (mut available, mut total, mut free) = (0u64, 0u64, 0u64);
+    let ok = unsafe { GetDiskFreeSpaceExW(wide.as_ptr(), &mut available, &mut total, &mut free) };
+    if ok == 0 {
+        return Err(format!(
+            "ディスク容量を取得できませんでした: {}",
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok((total, available))
+}
+
+#[cfg(not(any(unix, windows)))]
+fn disk_space_impl(_path: &Path) -> Result<(u64, u64), String> {
+    Err("このOSではディスク容量を取得できません".to_string())
+}
+
 /// Format file size for display
-#[allow(dead_code)]
 pub fn format_size(bytes: u64) -> String {
     if bytes < 1024 {
         format!("{} B", bytes)
@@ -142,4 +696,404 @@ mod tests {
         assert!(tree.is_dir);
         assert!(tree.children.is_some());
     }
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("cowork-files-test-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[tokio::test]
+    async fn test_search_directory_streams_matches() {
+        let root = temp_dir();
+        fs::create_dir_all(root.join("sub")).await.unwrap();
+        fs::create_dir_all(root.join(".hidden")).await.unwrap();
+        fs::write(root.join("a.txt"), "hello world\nnothing here\nHELLO again\n").await.unwrap();
+        fs::write(root.join("sub").join("b.md"), "say hello\n").await.unwrap();
+        fs::write(root.join(".hidden").join("c.txt"), "hello hidden\n").await.unwrap();
+
+        // A capacity-1 channel forces the walker to hand over each match
+        // before it can find the next one.
+        let (tx, mut rx) = mpsc::channel(1);
+        let cancel = AtomicBool::new(false);
+        let walker_root = root.clone();
+        let walker = tokio::spawn(async move {
+            search_directory(&walker_root, "hello", None, &cancel, &tx).await
+        });
+
+        let first = rx.recv().await.unwrap();
+        assert!(!walker.is_finished());
+        assert_eq!(first.line_number, 1);
+
+        let mut received = vec![first];
+        while let Some(m) = rx.recv().await {
+            received.push(m);
+        }
+        let total = walker.await.unwrap().unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(received.len(), total);
+        assert!(received.iter().all(|m| !m.path.contains(".hidden")));
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_search_directory_cancelled() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        fs::write(root.join("a.txt"), "hello\n").await.unwrap();
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let cancel = AtomicBool::new(true);
+        let total = search_directory(&root, "hello", None, &cancel, &tx).await.unwrap();
+        drop(tx);
+        assert_eq!(total, 0);
+        assert!(rx.recv().await.is_none());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_list_directory_respects_gitignore() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").await.unwrap();
+        fs::write(root.join("app.log"), "log").await.unwrap();
+        fs::write(root.join("main.rs"), "fn main() {}").await.unwrap();
+        let path = root.to_string_lossy().to_string();
+
+        // Opt-in: the plain listing still shows ignored files
+        let all = list_directory(&path).await.unwrap();
+        assert_eq!(all.len(), 2);
+
+        let matcher = IgnoreMatcher::from_dir(&root);
+        let filtered = list_directory_filtered(&path, Some(&matcher)).await.unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].name, "main.rs");
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_search_directory_respects_gitignore() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        fs::write(root.join(".gitignore"), "*.log\n").await.unwrap();
+        fs::write(root.join("app.log"), "needle\n").await.unwrap();
+        fs::write(root.join("notes.txt"), "needle\n").await.unwrap();
+
+        let matcher = IgnoreMatcher::from_dir(&root);
+        let (tx, mut rx) = mpsc::channel(8);
+        let cancel = AtomicBool::new(false);
+        let total = search_directory(&root, "needle", Some(&matcher), &cancel, &tx)
+            .await
+            .unwrap();
+        drop(tx);
+        assert_eq!(total, 1);
+        assert!(rx.recv().await.unwrap().path.ends_with("notes.txt"));
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[test]
+    fn test_get_disk_space_temp_dir() {
+        let tmp = std::env::temp_dir();
+        let (total, available) = get_disk_space(tmp.to_str().unwrap()).unwrap();
+        assert!(total > 0);
+        assert!(available > 0);
+        assert!(available <= total);
+    }
+
+    #[test]
+    fn test_get_disk_space_invalid_path() {
+        let result = get_disk_space("/nonexistent_dir_xyz/abc");
+        assert!(result.unwrap_err().contains("パスが見つかりません"));
+    }
+
+    #[tokio::test]
+    async fn test_read_file_tail() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        let path = root.join("build.log");
+        // Enough lines to span several read chunks
+        let content: String = (1..=5000).map(|i| format!("line {}\n", i)).collect();
+        fs::write(&path, content).await.unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let tail = read_file_tail(&path, 3).await.unwrap();
+        assert_eq!(tail, "line 4998\nline 4999\nline 5000\n");
+
+        let tail = read_file_tail(&path, 1).await.unwrap();
+        assert_eq!(tail, "line 5000\n");
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_file_tail_size_guard() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        // A single 64 KiB line: the tail can't be found without reading it all
+        let path = root.join("one_line.log");
+        fs::write(&path, vec![b'x'; 64 * 1024]).await.unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        let err = read_file_tail_limited(&path, 1, 16 * 1024).await.unwrap_err();
+        assert!(err.contains("大きすぎる"));
+        assert!(err.contains("16.0 KB"));
+
+        // Within the limit it still works
+        let tail = read_file_tail_limited(&path, 1, 1024 * 1024).await.unwrap();
+        assert_eq!(tail.len(), 64 * 1024);
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_read_file_tail_edge_cases() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+
+        // More lines requested than the file has
+        let short = root.join("short.txt");
+        fs::write(&short, "a\nb\n").await.unwrap();
+        let short = short.to_string_lossy().to_string();
+        assert_eq!(read_file_tail(&short, 10).await.unwrap(), "a\nb\n");
+
+        // No trailing newline
+        let no_nl = root.join("no_nl.txt");
+        fs::write(&no_nl, "a\nb\nc").await.unwrap();
+        let no_nl = no_nl.to_string_lossy().to_string();
+        assert_eq!(read_file_tail(&no_nl, 2).await.unwrap(), "b\nc");
+
+        // Empty file and zero lines
+        let empty = root.join("empty.txt");
+        fs::write(&empty, "").await.unwrap();
+        assert_eq!(read_file_tail(&empty.to_string_lossy(), 5).await.unwrap(), "");
+        assert_eq!(read_file_tail(&short, 0).await.unwrap(), "");
+
+        assert!(read_file_tail("/nonexistent_dir_xyz/a.log", 5).await.is_err());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_stats_text() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        let path = root.join("notes.txt");
+        fs::write(&path, "hello world\n  two  words\nこんにちは 世界\n").await.unwrap();
+
+        let stats = file_stats(&path.to_string_lossy()).await.unwrap();
+        assert!(!stats.is_binary);
+        assert_eq!(stats.lines, 3);
+        assert_eq!(stats.words, 6);
+        assert_eq!(stats.bytes, fs::metadata(&path).await.unwrap().len());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_hash_file() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        let path = root.join("hello.txt");
+        fs::write(&path, "hello world").await.unwrap();
+        let path = path.to_string_lossy().to_string();
+
+        assert_eq!(
+            hash_file(&path, "sha256").await.unwrap(),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(hash_file(&path, "SHA-256").await.unwrap().len(), 64);
+        assert_eq!(hash_file(&path, "md5").await.unwrap(), "5eb63bbbe01eeed093cb22bb8f5acdc3");
+        assert!(hash_file(&path, "crc32").await.is_err());
+
+        let missing = root.join("missing.txt").to_string_lossy().to_string();
+        assert!(hash_file(&missing, "sha256").await.unwrap_err().contains("見つかりません"));
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_diff_files() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        let backup = root.join("report.md.bak");
+        let current = root.join("report.md");
+        fs::write(&backup, "# 週報\n作業A\n作業B\n").await.unwrap();
+        fs::write(&current, "# 週報\n作業A\n作業C\n").await.unwrap();
+        let (a, b) = (backup.to_string_lossy().to_string(), current.to_string_lossy().to_string());
+
+        let diff = diff_files(&a, &b, 3).await.unwrap();
+        assert!(diff.starts_with(&format!("--- {}\n+++ {}\n", a, b)));
+        assert!(diff.contains("\n-作業B\n"));
+        assert!(diff.contains("\n+作業C\n"));
+        assert!(diff.contains("\n 作業A\n"));
+        assert_eq!(diff_files(&a, &a, 3).await.unwrap(), "");
+
+        let binary = root.join("image.bin");
+        fs::write(&binary, [0x89u8, b'P', 0, 1]).await.unwrap();
+        let err = diff_files(&a, &binary.to_string_lossy(), 3).await.unwrap_err();
+        assert!(err.contains("バイナリ"));
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_file_stats_binary() {
+        let root = temp_dir();
+        fs::create_dir_all(&root).await.unwrap();
+        let path = root.join("image.bin");
+        let mut data = b"PNG header\n".to_vec();
+        data.extend_from_slice(&[0u8, 1, 2, 3]);
+        data.extend_from_slice(&[b'a'; 100_000]);
+        fs::write(&path, &data).await.unwrap();
+
+        let stats = file_stats(&path.to_string_lossy()).await.unwrap();
+        assert!(stats.is_binary);
+        assert_eq!(stats.bytes, data.len() as u64);
+        assert_eq!(stats.lines, 0);
+        assert_eq!(stats.words, 0);
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[test]
+    fn test_unique_destination_free_name() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        assert_eq!(unique_destination(&dir, "report.pdf"), dir.join("report.pdf"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unique_destination_appends_counter() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("report.pdf"), "a").unwrap();
+        assert_eq!(unique_destination(&dir, "report.pdf"), dir.join("report (1).pdf"));
+
+        std::fs::write(dir.join("report (1).pdf"), "b").unwrap();
+        std::fs::write(dir.join("report (2).pdf"), "c").unwrap();
+        assert_eq!(unique_destination(&dir, "report.pdf"), dir.join("report (3).pdf"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unique_destination_without_extension() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("README"), "a").unwrap();
+        std::fs::write(dir.join(".env"), "b").unwrap();
+        std::fs::write(dir.join("archive.tar.gz"), "c").unwrap();
+        assert_eq!(unique_destination(&dir, "README"), dir.join("README (1)"));
+        assert_eq!(unique_destination(&dir, ".env"), dir.join(".env (1)"));
+        assert_eq!(unique_destination(&dir, "archive.tar.gz"), dir.join("archive.tar (1).gz"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    async fn recv_line(rx: &mut mpsc::Receiver<String>) -> String {
+        tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("line within timeout")
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_follow_file_emits_appended_lines() {
+        use std::io::Write;
+        use std::sync::Arc;
+
+        let dir = temp_dir();
+        fs::create_dir_all(&dir).await.unwrap();
+        let path = dir.join("app.log");
+        std::fs::write(&path, "first\n").unwrap();
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let stop = Arc::new(AtomicBool::new(false));
+        let follower_stop = Arc::clone(&stop);
+        let follower_path = path.clone();
+        let follower = tokio::spawn(async move {
+            follow_file(&follower_path, false, &follower_stop, &tx).await
+        });
+        assert_eq!(recv_line(&mut rx).await, "first");
+
+        let mut file = std::fs::OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(b"second\r\nthi").unwrap();
+        file.flush().unwrap();
+        assert_eq!(recv_line(&mut rx).await, "second");
+        file.write_all(b"rd\n").unwrap();
+        file.flush().unwrap();
+        assert_eq!(recv_line(&mut rx).await, "third");
+
+        // Rotation: the file is replaced by a shorter one
+        std::fs::write(&path, "new\n").unwrap();
+        assert_eq!(recv_line(&mut rx).await, "new");
+
+        stop.store(true, Ordering::Relaxed);
+        follower.await.unwrap().unwrap();
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_watch_dir_reports_modified_skill() {
+        use std::sync::Arc;
+
+        let dir = temp_dir();
+        let skill_dir = dir.join("report");
+        std::fs::create_dir_all(&skill_dir).unwrap();
+        std::fs::write(skill_dir.join("SKILL.md"), "---\nname: report\n---\n本文\n").unwrap();
+
+        let (tx, mut rx) = mpsc::channel(8);
+        let stop = Arc::new(AtomicBool::new(false));
+        let watcher_stop = Arc::clone(&stop);
+        let watch_path = dir.clone();
+        let watcher = tokio::spawn(async move { watch_dir(&watch_path, &watcher_stop, &tx).await });
+
+        tokio::time::sleep(FOLLOW_POLL_INTERVAL).await;
+        std::fs::write(skill_dir.join("SKILL.md"), "---\nname: report\n---\n更新された本文\n").unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), rx.recv())
+            .await
+            .expect("change within timeout")
+            .unwrap();
+        // A single edit is reported once
+        assert!(tokio::time::timeout(FOLLOW_POLL_INTERVAL * 3, rx.recv()).await.is_err());
+
+        stop.store(true, Ordering::Relaxed);
+        watcher.await.unwrap();
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn test_detect_project_type() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join("Cargo.toml"), "[package]\n").unwrap();
+        std::fs::write(dir.join("requirements.txt"), "").unwrap();
+        std::fs::write(dir.join("pyproject.toml"), "").unwrap();
+
+        let tags = detect_project_type(&dir.to_string_lossy());
+        assert!(tags.contains(&"rust".to_string()));
+        assert!(!tags.contains(&"node".to_string()));
+        assert_eq!(tags, vec!["rust", "python", "git"]);
+
+        assert!(detect_project_type(&dir.join("missing").to_string_lossy()).is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_tail_reader_from_end() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("app.log");
+        std::fs::write(&path, "old line\n").unwrap();
+
+        let mut reader = TailReader::open(&path, true).unwrap();
+        assert!(reader.poll().unwrap().is_empty());
+        std::fs::write(&path, "old line\nnew line\n").unwrap();
+        assert_eq!(reader.poll().unwrap(), vec!["new line"]);
+        assert!(TailReader::open(&dir.join("missing.log"), true).is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
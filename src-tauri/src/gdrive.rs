@@ -1,14 +1,33 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use tokio::fs;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
 
 use crate::oauth_server;
 
 const AUTH_URL: &str = "https://accounts.google.com/o/oauth2/v2/auth";
 const TOKEN_URL: &str = "https://oauth2.googleapis.com/token";
+const REVOKE_URL: &str = "https://oauth2.googleapis.com/revoke";
 const DRIVE_API: &str = "https://www.googleapis.com/drive/v3";
+const UPLOAD_API: &str = "https://www.googleapis.com/upload/drive/v3";
+const FOLDER_MIME: &str = "application/vnd.google-apps.folder";
+const FILE_FIELDS: &str = "id,name,mimeType,size,modifiedTime";
+/// Maximum number of files uploaded in parallel by `upload_directory`
+const UPLOAD_CONCURRENCY: usize = 4;
+/// Drive rejects `uploadType=multipart` bodies above 5 MB; larger files use
+/// a resumable session
+const MULTIPART_UPLOAD_LIMIT: u64 = 5 * 1024 * 1024;
+/// Bytes sent per request of a resumable upload (a multiple of 256 KiB)
+const UPLOAD_CHUNK_SIZE: usize = 8 * 1024 * 1024;
 const SCOPES: &str = "https://www.googleapis.com/auth/drive";
+/// Error returned by a download stopped with `cancel_download`
+pub const DOWNLOAD_CANCELLED: &str = "ダウンロードがキャンセルされました";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GDriveConfig {
@@ -23,6 +42,28 @@ pub struct GDriveTokens {
     pub expires_at: Option<i64>,
 }
 
+/// The first bytes of a Drive file, for a quick look without downloading it
+#[derive(Debug, Clone, Serialize)]
+pub struct FilePreview {
+    /// Lossy UTF-8 text; None for binary content
+    pub text: Option<String>,
+    /// Content contains NUL bytes
+    pub is_binary: bool,
+    /// Number of bytes previewed
+    pub bytes: usize,
+}
+
+impl FilePreview {
+    pub fn from_bytes(head: &[u8]) -> Self {
+        let is_binary = head.contains(&0);
+        Self {
+            text: (!is_binary).then(|| String::from_utf8_lossy(head).to_string()),
+            is_binary,
+            bytes: head.len(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DriveFile {
     pub id: String,
@@ -33,22 +74,35 @@ pub struct DriveFile {
     pub modified_time: Option<String>,
 }
 
+/// Callback invoked with the new authentication state whenever tokens change
+pub type AuthListener = Box<dyn Fn(bool) + Send + Sync>;
+
 pub struct GDriveClient {
-    http: Client,
+    http: std::sync::RwLock<Client>,
     data_dir: PathBuf,
     resource_dir: Option<PathBuf>,
     config: tokio::sync::Mutex<Option<GDriveConfig>>,
     tokens: tokio::sync::Mutex<Option<GDriveTokens>>,
+    revoke_endpoint: String,
+    auth_listener: std::sync::Mutex<Option<AuthListener>>,
+    /// Cancel handle of the OAuth flow currently waiting for a callback
+    auth_flow: tokio::sync::Mutex<Option<oauth_server::OAuthCancelHandle>>,
+    /// Cancel flags of in-progress downloads, by file id
+    downloads: std::sync::Mutex<HashMap<String, Arc<AtomicBool>>>,
 }
 
 impl GDriveClient {
     pub fn new(data_dir: PathBuf, resource_dir: Option<PathBuf>) -> Self {
         Self {
-            http: Client::new(),
+            http: std::sync::RwLock::new(Client::new()),
             data_dir,
             resource_dir,
             config: tokio::sync::Mutex::new(None),
             tokens: tokio::sync::Mutex::new(None),
+            revoke_endpoint: REVOKE_URL.to_string(),
+            auth_listener: std::sync::Mutex::new(None),
+            auth_flow: tokio::sync::Mutex::new(None),
+            downloads: std::sync::Mutex::new(HashMap::new()),
         }
     }
 
@@ -97,6 +151,26 @@ impl GDriveClient {
         None
     }
 
+    /// Replace the HTTP client (e.g. after proxy settings change)
+    pub fn set_http_client(&self, client: Client) {
+        *self.http.write().unwrap() = client;
+    }
+
+    fn http(&self) -> Client {
+        self.http.read().unwrap().clone()
+    }
+
+    /// Register a callback notified when tokens are saved, refreshed, or cleared.
+    pub fn set_auth_listener(&self, listener: AuthListener) {
+        *self.auth_listener.lock().unwrap() = Some(listener);
+    }
+
+    fn notify_auth_state(&self, authenticated: bool) {
+        if let Some(listener) = self.auth_listener.lock().unwrap().as_ref() {
+            listener(authenticated);
+        }
+    }
+
     async fn save_tokens(&self, tokens: &GDriveTokens) -> Result<(), String> {
         fs::create_dir_all(&self.data_dir)
             .await
@@ -108,6 +182,32 @@ impl GDriveClient {
             .await
             .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
         *self.tokens.lock().await = Some(tokens.clone());
+        self.notify_auth_state(true);
+        Ok(())
+    }
+
+    /// Write user-provided OAuth credentials to the data dir and apply them
+    /// without requiring a restart.
+    pub async fn set_config(&self, client_id: &str, client_secret: &str) -> Result<(), String> {
+        let client_id = client_id.trim();
+        let client_secret = client_secret.trim();
+        if client_id.is_empty() || client_secret.is_empty() {
+            return Err("クライアントIDとクライアントシークレットを入力してください".to_string());
+        }
+        let config = GDriveConfig {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        };
+        fs::create_dir_all(&self.data_dir)
+            .await
+            .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+        let path = self.data_dir.join("gdrive_config.json");
+        let content = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("データの変換に失敗しました: {}", e))?;
+        fs::write(&path, &content)
+            .await
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        *self.config.lock().await = Some(config);
         Ok(())
     }
 
@@ -130,7 +230,9 @@ impl GDriveClient {
             .as_ref()
             .ok_or("Google DriveのOAuth設定が見つかりません。開発者に連絡してください。")?;
 
-        let (port, rx) = oauth_server::wait_for_oauth_callback().await?;
+        let (port, rx, handle) = oauth_server::wait_for_oauth_callback().await?;
+        // Starting a new flow replaces (and shuts down) any previous one
+        *self.auth_flow.lock().await = Some(handle);
 
         let url = format!(
             "{}?client_id={}&redirect_uri={}&response_type=code&scope={}&access_type=offline&prompt=consent",
@@ -143,6 +245,27 @@ impl GDriveClient {
         Ok((url, port, rx))
     }
 
+    /// Abort the in-flight OAuth flow. Its receiver resolves with a
+    /// cancellation error. Returns false if no flow was in progress.
+    pub async fn cancel_auth_flow(&self) -> bool {
+        match self.auth_flow.lock().await.take() {
+            Some(handle) => {
+                handle.cancel().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Release the callback server once the flow on `port` has finished.
+    /// A newer flow started in the meantime is left untouched.
+    pub async fn finish_auth_flow(&self, port: u16) {
+        let mut flow = self.auth_flow.lock().await;
+        if flow.as_ref().is_some_and(|h| h.port() == port) {
+            flow.take();
+        }
+    }
+
     /// Exchange an authorization code for tokens.
     pub async fn exchange_code(&self, code: &str, port: u16) -> Result<(), String> {
         let (client_id, client_secret) = {
@@ -154,7 +277,7 @@ impl GDriveClient {
         };
 
         let resp = self
-            .http
+            .http()
             .post(TOKEN_URL)
             .form(&[
                 ("code", code),
@@ -224,7 +347,7 @@ impl GDriveClient {
         };
 
         let resp = self
-            .http
+            .http()
             .post(TOKEN_URL)
             .form(&[
                 ("refresh_token", refresh_tok.as_str()),
@@ -291,7 +414,7 @@ impl GDriveClient {
         );
 
         let resp = self
-            .http
+            .http()
             .get(&url)
             .bearer_auth(&token)
             .send()
@@ -310,69 +433,413 @@ impl GDriveClient {
         let files = body
             .get("files")
             .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|f| {
-                        let id = f.get("id")?.as_str()?.to_string();
-                        let name = f.get("name")?.as_str()?.to_string();
-                        let mime = f
-                            .get("mimeType")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("")
-                            .to_string();
-                        let is_folder = mime == "application/vnd.google-apps.folder";
-                        let size = f
-                            .get("size")
-                            .and_then(|v| v.as_str())
-                            .and_then(|s| s.parse().ok());
-                        let modified = f
-                            .get("modifiedTime")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-
-                        Some(DriveFile {
-                            id,
-                            name,
-                            mime_type: mime,
-                            is_folder,
-                            size,
-                            modified_time: modified,
-                        })
-                    })
-                    .collect()
-            })
+            .map(|arr| arr.iter().filter_map(parse_drive_file).collect())
             .unwrap_or_default();
 
         Ok(files)
     }
 
-    /// Download a file to a local path.
-    pub async fn download_file(&self, file_id: &str, dest: &str) -> Result<String, String> {
+    /// Download a file to a local path. Data goes to `<target>.part` first,
+    /// which is kept if the transfer fails; with `resume` an existing
+    /// `.part` is continued with a `Range` request instead of starting over.
+    pub async fn download_file(
+        &self,
+        file_id: &str,
+        dest: &str,
+        overwrite: bool,
+        resume: bool,
+    ) -> Result<String, String> {
         let token = self.get_access_token().await?;
+        let dest_path = Path::new(dest);
+        let named = if dest_path.is_dir() {
+            // Saving into a folder: use the file's name on Drive
+            Some(dest_path.join(sanitize_file_name(&self.get_file_name(file_id).await?)))
+        } else {
+            None
+        };
+        let target = download_target(dest_path, named, overwrite);
+
         let url = format!("{}/files/{}?alt=media", DRIVE_API, file_id);
+        let part = part_path(&target);
+        let offset = if resume { partial_len(&part).await } else { 0 };
+
+        let mut request = self.http().get(&url).bearer_auth(&token);
+        if let Some(range) = resume_range_header(offset) {
+            request = request.header("Range", range);
+        }
+        let mut resp = request
+            .send()
+            .await
+            .map_err(|e| format!("ダウンロードに失敗しました: {}", e))?;
+        let partial_mismatch = resp.status() == reqwest::StatusCode::PARTIAL_CONTENT
+            && content_range_start(
+                resp.headers()
+                    .get(reqwest::header::CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok()),
+            ) != Some(offset);
+        if resp.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE || partial_mismatch {
+            // The partial file doesn't fit the current file; start over
+            resp = self
+                .http()
+                .get(&url)
+                .bearer_auth(&token)
+                .send()
+                .await
+                .map_err(|e| format!("ダウンロードに失敗しました: {}", e))?;
+        }
+        if !resp.status().is_success() {
+            return Err(format!("ダウンロードに失敗しました: {}", resp.status()));
+        }
+        // A server that ignores Range answers 200 with the whole file
+        let append = offset > 0 && resp.status() == reqwest::StatusCode::PARTIAL_CONTENT;
+
+        let cancel = self.register_download(file_id);
+        let (tx, mut rx) = mpsc::channel::<Result<Vec<u8>, String>>(8);
+        let pump_cancel = Arc::clone(&cancel);
+        let pump = tokio::spawn(async move {
+            while !pump_cancel.load(Ordering::Relaxed) {
+                let next = match resp.chunk().await {
+                    Ok(Some(chunk)) => Ok(chunk.to_vec()),
+                    Ok(None) => break,
+                    Err(e) => Err(format!("ファイルの読み込みに失敗しました: {}", e)),
+                };
+                let failed = next.is_err();
+                if tx.send(next).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        let result = write_chunks(&mut rx, &part, append, &cancel).await;
+        drop(rx);
+        let _ = pump.await;
+        self.unregister_download(file_id, &cancel);
+        result?;
+
+        if target.exists() {
+            // rename() doesn't replace an existing file on Windows
+            let _ = fs::remove_file(&target).await;
+        }
+        fs::rename(&part, &target)
+            .await
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        Ok(target.to_string_lossy().to_string())
+    }
+
+    fn register_download(&self, file_id: &str) -> Arc<AtomicBool> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let previous = self
+            .downloads
+            .lock()
+            .unwrap()
+            .insert(file_id.to_string(), Arc::clone(&cancel));
+        if let Some(previous) = previous {
+            previous.store(true, Ordering::Relaxed);
+        }
+        cancel
+    }
+
+    fn unregister_download(&self, file_id: &str, cancel: &Arc<AtomicBool>) {
+        let mut downloads = self.downloads.lock().unwrap();
+        // A newer download of the same file may have replaced this one
+        if downloads.get(file_id).is_some_and(|c| Arc::ptr_eq(c, cancel)) {
+            downloads.remove(file_id);
+        }
+    }
+
+    /// Stop an in-progress download; its partial file is removed. Returns
+    /// false if no download of `file_id` is running.
+    pub fn cancel_download(&self, file_id: &str) -> bool {
+        match self.downloads.lock().unwrap().remove(file_id) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Create a folder on Drive (under My Drive's root when `parent_id` is None).
+    pub async fn create_folder(
+        &self,
+        name: &str,
+        parent_id: Option<&str>,
+    ) -> Result<DriveFile, String> {
+        let token = self.get_access_token().await?;
+        let url = format!("{}/files?fields={}", DRIVE_API, FILE_FIELDS);
+        let metadata = serde_json::json!({
+            "name": name,
+            "mimeType": FOLDER_MIME,
+            "parents": [parent_id.unwrap_or("root")],
+        });
+
+        let resp = self
+            .http()
+            .post(&url)
+            .bearer_auth(&token)
+            .json(&metadata)
+            .send()
+            .await
+            .map_err(|e| format!("フォルダの作成に失敗しました: {}", e))?;
+
+        drive_file_from_response(resp).await
+    }
+
+    /// Upload a local file into a Drive folder. Files over 5 MB are streamed
+    /// from disk in chunks through a resumable session.
+    pub async fn upload_file(
+        &self,
+        local_path: &str,
+        parent_id: Option<&str>,
+    ) -> Result<DriveFile, String> {
+        let path = Path::new(local_path);
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("ファイル名を取得できませんでした: {}", local_path))?;
+        let metadata = serde_json::json!({
+            "name": name,
+            "parents": [parent_id.unwrap_or("root")],
+        });
+        let size = fs::metadata(path)
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?
+            .len();
+        if size > MULTIPART_UPLOAD_LIMIT {
+            return self.upload_resumable(path, &metadata, size).await;
+        }
+
+        let content = fs::read(path)
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+        let token = self.get_access_token().await?;
+        let url = format!("{}/files?uploadType=multipart&fields={}", UPLOAD_API, FILE_FIELDS);
+        let boundary = format!("cowork-{}", uuid::Uuid::new_v4().simple());
 
         let resp = self
-            .http
+            .http()
+            .post(&url)
+            .bearer_auth(&token)
+            .header(
+                "Content-Type",
+                format!("multipart/related; boundary={}", boundary),
+            )
+            .body(multipart_related_body(&boundary, &metadata, &content))
+            .send()
+            .await
+            .map_err(|e| format!("アップロードに失敗しました: {}", e))?;
+
+        drive_file_from_response(resp).await
+    }
+
+    /// Upload `path` through a resumable session, one `UPLOAD_CHUNK_SIZE`
+    /// chunk in memory at a time
+    async fn upload_resumable(
+        &self,
+        path: &Path,
+        metadata: &serde_json::Value,
+        size: u64,
+    ) -> Result<DriveFile, String> {
+        let token = self.get_access_token().await?;
+        let url = format!("{}/files?uploadType=resumable&fields={}", UPLOAD_API, FILE_FIELDS);
+        let resp = self
+            .http()
+            .post(&url)
+            .bearer_auth(&token)
+            .header("X-Upload-Content-Length", size.to_string())
+            .json(metadata)
+            .send()
+            .await
+            .map_err(|e| format!("アップロードに失敗しました: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("アップロードを開始できませんでした: HTTP {}", resp.status()));
+        }
+        let session = resp
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .map(|s| s.to_string())
+            .ok_or("アップロードを開始できませんでした: セッションURLがありません")?;
+
+        let mut file = fs::File::open(path)
+            .await
+            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+        let mut offset = 0u64;
+        loop {
+            let chunk = read_chunk(&mut file, offset, UPLOAD_CHUNK_SIZE).await?;
+            let resp = self
+                .http()
+                .put(&session)
+                .header(
+                    reqwest::header::CONTENT_RANGE,
+                    upload_content_range(offset, chunk.len() as u64, size),
+                )
+                .body(chunk)
+                .send()
+                .await
+                .map_err(|e| format!("アップロードに失敗しました: {}", e))?;
+
+            // 308 Resume Incomplete: Drive wants the rest
+            if resp.status().as_u16() == 308 {
+                let range = resp
+                    .headers()
+                    .get(reqwest::header::RANGE)
+                    .and_then(|v| v.to_str().ok());
+                offset = uploaded_until(range);
+                continue;
+            }
+            return drive_file_from_response(resp).await;
+        }
+    }
+
+    /// Recreate a local directory tree under a Drive folder. Hidden files and
+    /// folders are skipped. Returns every folder and file created.
+    pub async fn upload_directory(
+        self: &Arc<Self>,
+        local_dir: &str,
+        parent_id: Option<&str>,
+    ) -> Result<Vec<DriveFile>, String> {
+        let root = Path::new(local_dir);
+        if !root.is_dir() {
+            return Err(format!("フォルダが見つかりません: {}", local_dir));
+        }
+        let plan = plan_upload(root)?;
+
+        let folders = Arc::clone(self);
+        let files = Arc::clone(self);
+        upload_tree(
+            &plan,
+            parent_id.map(|s| s.to_string()),
+            move |name, parent| {
+                let client = Arc::clone(&folders);
+                async move { client.create_folder(&name, parent.as_deref()).await }
+            },
+            move |path, parent| {
+                let client = Arc::clone(&files);
+                async move {
+                    client
+                        .upload_file(&path.to_string_lossy(), Some(&parent))
+                        .await
+                }
+            },
+        )
+        .await
+    }
+
+    /// Fetch at most `max_bytes` from the start of a file with a ranged GET.
+    pub async fn peek_file(&self, file_id: &str, max_bytes: usize) -> Result<Vec<u8>, String> {
+        let range = range_header(max_bytes).ok_or("プレビューするバイト数を指定してください")?;
+        let token = self.get_access_token().await?;
+        let url = format!("{}/files/{}?alt=media", DRIVE_API, file_id);
+
+        let mut resp = self
+            .http()
             .get(&url)
             .bearer_auth(&token)
+            .header("Range", range)
             .send()
             .await
             .map_err(|e| format!("ダウンロードに失敗しました: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("ダウンロードに失敗しました: {}", resp.status()));
+        }
+
+        // A server that ignores Range sends the whole file; stop reading
+        // once enough has arrived
+        let mut head = Vec::new();
+        while head.len() < max_bytes {
+            match resp
+                .chunk()
+                .await
+                .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?
+            {
+                Some(chunk) => head.extend_from_slice(&chunk),
+                None => break,
+            }
+        }
+        Ok(truncate_head(head, max_bytes))
+    }
 
-        let bytes = resp
-            .bytes()
+    /// Get a file's name on Drive.
+    async fn get_file_name(&self, file_id: &str) -> Result<String, String> {
+        let token = self.get_access_token().await?;
+        let url = format!("{}/files/{}?fields=name", DRIVE_API, file_id);
+
+        let resp = self
+            .http()
+            .get(&url)
+            .bearer_auth(&token)
+            .send()
             .await
-            .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+            .map_err(|e| format!("Google Drive APIエラー: {}", e))?;
 
-        fs::write(dest, &bytes)
+        let body: serde_json::Value = resp
+            .json()
             .await
-            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+            .map_err(|e| format!("応答の解析に失敗しました: {}", e))?;
 
-        Ok(dest.to_string())
+        body.get("name")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .ok_or_else(|| "ファイル名を取得できませんでした".to_string())
     }
 
-    /// Clear tokens (logout).
+    /// Get a browser URL for a file (its webViewLink).
+    pub async fn get_file_link(&self, file_id: &str) -> Result<String, String> {
+        let token = self.get_access_token().await?;
+        let url = format!("{}/files/{}?fields=webViewLink", DRIVE_API, file_id);
+
+        let resp = self
+            .http()
+            .get(&url)
+            .bearer_auth(&token)
+            .send()
+            .await
+            .map_err(|e| format!("Google Drive APIエラー: {}", e))?;
+
+        let body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("応答の解析に失敗しました: {}", e))?;
+
+        if let Some(err) = body.get("error") {
+            return Err(format!("Google Driveエラー: {}", err));
+        }
+
+        Ok(file_link_from_response(file_id, &body))
+    }
+
+    /// Revoke the OAuth grant on Google's side.
+    async fn revoke_token(&self, token: &str) -> Result<(), String> {
+        let resp = self
+            .http()
+            .post(revoke_url(&self.revoke_endpoint, token))
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .timeout(std::time::Duration::from_secs(10))
+            .send()
+            .await
+            .map_err(|e| format!("トークンの無効化に失敗しました: {}", e))?;
+        if !resp.status().is_success() {
+            return Err(format!("トークンの無効化に失敗しました: {}", resp.status()));
+        }
+        Ok(())
+    }
+
+    /// Revoke the grant and clear tokens (logout).
+    /// A failed revoke is logged but local tokens are cleared regardless.
     pub async fn logout(&self) -> Result<(), String> {
+        // Revoking the refresh token also invalidates its access tokens
+        let token = self
+            .tokens
+            .lock()
+            .await
+            .as_ref()
+            .map(|t| t.refresh_token.clone().unwrap_or_else(|| t.access_token.clone()));
+        if let Some(token) = token {
+            if let Err(e) = self.revoke_token(&token).await {
+                log::warn!("Google Drive token revoke failed: {}", e);
+            }
+        }
+
         let path = self.data_dir.join("gdrive_tokens.json");
         if path.exists() {
             fs::remove_file(&path)
@@ -380,10 +847,350 @@ impl GDriveClient {
                 .map_err(|e| format!("ログアウトに失敗しました: {}", e))?;
         }
         *self.tokens.lock().await = None;
+        self.notify_auth_state(false);
         Ok(())
     }
 }
 
+/// Build a `DriveFile` from one entry of a Drive API file listing
+fn parse_drive_file(f: &serde_json::Value) -> Option<DriveFile> {
+    let id = f.get("id")?.as_str()?.to_string();
+    let name = f.get("name")?.as_str()?.to_string();
+    let mime = f
+        .get("mimeType")
+        .and_then(|v| v.as_str())
+        .unwrap_or("")
+        .to_string();
+    let is_folder = mime == FOLDER_MIME;
+    let size = f
+        .get("size")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse().ok());
+    let modified = f
+        .get("modifiedTime")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    Some(DriveFile {
+        id,
+        name,
+        mime_type: mime,
+        is_folder,
+        size,
+        modified_time: modified,
+    })
+}
+
+/// Parse the single file returned by a create/upload call
+async fn drive_file_from_response(resp: reqwest::Response) -> Result<DriveFile, String> {
+    let body: serde_json::Value = resp
+        .json()
+        .await
+        .map_err(|e| format!("応答の解析に失敗しました: {}", e))?;
+
+    if let Some(err) = body.get("error") {
+        return Err(format!("Google Driveエラー: {}", err));
+    }
+
+    parse_drive_file(&body).ok_or_else(|| "応答の解析に失敗しました".to_string())
+}
+
+/// `Range` header value for the first `max_bytes` bytes (the end is inclusive)
+fn range_header(max_bytes: usize) -> Option<String> {
+    (max_bytes > 0).then(|| format!("bytes=0-{}", max_bytes - 1))
+}
+
+fn truncate_head(mut bytes: Vec<u8>, max_bytes: usize) -> Vec<u8> {
+    bytes.truncate(max_bytes);
+    bytes
+}
+
+/// Where an unfinished download of `target` is written
+fn part_path(target: &Path) -> PathBuf {
+    let mut name = target.as_os_str().to_os_string();
+    name.push(".part");
+    PathBuf::from(name)
+}
+
+/// Bytes already downloaded to a partial file (0 if there is none)
+async fn partial_len(part: &Path) -> u64 {
+    fs::metadata(part).await.map(|m| m.len()).unwrap_or(0)
+}
+
+/// Where a download is saved. `named` is the Drive file's name inside `dest`
+/// when `dest` is a folder. An existing file is only replaced with
+/// `overwrite`; otherwise a free ` (n)` name is used. Resuming follows the
+/// same rules, since the partial file sits next to the chosen name.
+fn download_target(dest: &Path, named: Option<PathBuf>, overwrite: bool) -> PathBuf {
+    let (dir, wanted) = match named {
+        Some(named) => (dest.to_path_buf(), named),
+        None => (
+            dest.parent().unwrap_or(Path::new(".")).to_path_buf(),
+            dest.to_path_buf(),
+        ),
+    };
+    if overwrite || !wanted.exists() {
+        return wanted;
+    }
+    let name = wanted
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    crate::files::unique_destination(&dir, &name)
+}
+
+/// Start offset of a 206 response's `Content-Range` ("bytes 100-199/200")
+fn content_range_start(header: Option<&str>) -> Option<u64> {
+    header?
+        .strip_prefix("bytes ")?
+        .split('-')
+        .next()?
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// `Range` header continuing a download after `offset` bytes
+fn resume_range_header(offset: u64) -> Option<String> {
+    (offset > 0).then(|| format!("bytes={}-", offset))
+}
+
+/// Write the chunks received on `rx` to `target` (appending if `append`)
+/// until the sender is done. A cancelled download's file is removed; after
+/// other errors it is kept so the download can be resumed.
+async fn write_chunks(
+    rx: &mut mpsc::Receiver<Result<Vec<u8>, String>>,
+    target: &Path,
+    append: bool,
+    cancel: &AtomicBool,
+) -> Result<u64, String> {
+    let result = async {
+        let mut file = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(target)
+            .await
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        let mut written = 0u64;
+        loop {
+            let next = rx.recv().await;
+            if cancel.load(Ordering::Relaxed) {
+                return Err(DOWNLOAD_CANCELLED.to_string());
+            }
+            match next {
+                Some(Ok(chunk)) => {
+                    file.write_all(&chunk)
+                        .await
+                        .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+                    written += chunk.len() as u64;
+                }
+                Some(Err(e)) => return Err(e),
+                None => break,
+            }
+        }
+        file.flush()
+            .await
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        Ok(written)
+    }
+    .await;
+
+    if matches!(&result, Err(e) if e == DOWNLOAD_CANCELLED) {
+        let _ = fs::remove_file(target).await;
+    }
+    result
+}
+
+/// Read up to `max` bytes of `file` starting at `offset`
+async fn read_chunk(file: &mut fs::File, offset: u64, max: usize) -> Result<Vec<u8>, String> {
+    use tokio::io::{AsyncReadExt, AsyncSeekExt};
+
+    file.seek(std::io::SeekFrom::Start(offset))
+        .await
+        .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+    let mut chunk = Vec::with_capacity(max);
+    (&mut *file)
+        .take(max as u64)
+        .read_to_end(&mut chunk)
+        .await
+        .map_err(|e| format!("ファイルの読み込みに失敗しました: {}", e))?;
+    Ok(chunk)
+}
+
+/// `Content-Range` for `len` bytes at `offset` of a `total`-byte upload
+fn upload_content_range(offset: u64, len: u64, total: u64) -> String {
+    if len == 0 {
+        format!("bytes */{}", total)
+    } else {
+        format!("bytes {}-{}/{}", offset, offset + len - 1, total)
+    }
+}
+
+/// Offset to continue from after a 308, given its `Range` header
+/// ("bytes=0-N" means N+1 bytes were stored; no header means none were)
+fn uploaded_until(range: Option<&str>) -> u64 {
+    range
+        .and_then(|r| r.strip_prefix("bytes=0-"))
+        .and_then(|end| end.trim().parse::<u64>().ok())
+        .map(|end| end + 1)
+        .unwrap_or(0)
+}
+
+/// Body for Drive's `uploadType=multipart` (JSON metadata + raw content)
+fn multipart_related_body(boundary: &str, metadata: &serde_json::Value, content: &[u8]) -> Vec<u8> {
+    let mut body = format!(
+        "--{b}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{m}\r\n--{b}\r\nContent-Type: application/octet-stream\r\n\r\n",
+        b = boundary,
+        m = metadata,
+    )
+    .into_bytes();
+    body.extend_from_slice(content);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+/// One folder or file of a directory upload
+#[derive(Debug, Clone)]
+struct UploadEntry {
+    path: PathBuf,
+    name: String,
+    is_dir: bool,
+    /// Index of the containing folder in the plan; None for the top folder
+    parent: Option<usize>,
+}
+
+fn is_hidden(name: &str) -> bool {
+    name.starts_with('.')
+}
+
+/// Walk `root` into a flat plan where every folder precedes its contents.
+/// Hidden entries and symlinks are skipped.
+fn plan_upload(root: &Path) -> Result<Vec<UploadEntry>, String> {
+    let name = root
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .ok_or_else(|| format!("フォルダ名を取得できませんでした: {}", root.display()))?;
+    let mut plan = vec![UploadEntry {
+        path: root.to_path_buf(),
+        name,
+        is_dir: true,
+        parent: None,
+    }];
+
+    let mut next = 0;
+    while next < plan.len() {
+        let index = next;
+        next += 1;
+        if !plan[index].is_dir {
+            continue;
+        }
+
+        let read_dir = std::fs::read_dir(&plan[index].path)
+            .map_err(|e| format!("フォルダを読み込めませんでした: {}", e))?;
+        let mut children: Vec<UploadEntry> = read_dir
+            .filter_map(|e| e.ok())
+            .filter_map(|e| {
+                let name = e.file_name().to_string_lossy().to_string();
+                let file_type = e.file_type().ok()?;
+                if is_hidden(&name) || file_type.is_symlink() {
+                    return None;
+                }
+                Some(UploadEntry {
+                    path: e.path(),
+                    name,
+                    is_dir: file_type.is_dir(),
+                    parent: Some(index),
+                })
+            })
+            .collect();
+        children.sort_by(|a, b| a.name.cmp(&b.name));
+        plan.extend(children);
+    }
+
+    Ok(plan)
+}
+
+/// Execute an upload plan: folders one by one (their ids are needed for the
+/// children), then files with at most `UPLOAD_CONCURRENCY` in flight.
+async fn upload_tree<C, CF, U, UF>(
+    plan: &[UploadEntry],
+    root_parent: Option<String>,
+    create_folder: C,
+    upload_file: U,
+) -> Result<Vec<DriveFile>, String>
+where
+    C: Fn(String, Option<String>) -> CF,
+    CF: Future<Output = Result<DriveFile, String>>,
+    U: Fn(PathBuf, String) -> UF,
+    UF: Future<Output = Result<DriveFile, String>> + Send + 'static,
+{
+    let mut folder_ids: Vec<Option<String>> = vec![None; plan.len()];
+    let mut created = Vec::new();
+
+    for (index, entry) in plan.iter().enumerate().filter(|(_, e)| e.is_dir) {
+        let parent = match entry.parent {
+            Some(p) => folder_ids[p].clone(),
+            None => root_parent.clone(),
+        };
+        let folder = create_folder(entry.name.clone(), parent).await?;
+        folder_ids[index] = Some(folder.id.clone());
+        created.push(folder);
+    }
+
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(UPLOAD_CONCURRENCY));
+    let mut uploads = tokio::task::JoinSet::new();
+    for entry in plan.iter().filter(|e| !e.is_dir) {
+        let Some(parent) = entry.parent.and_then(|p| folder_ids[p].clone()) else {
+            continue;
+        };
+        let permit = Arc::clone(&semaphore)
+            .acquire_owned()
+            .await
+            .map_err(|e| format!("アップロードに失敗しました: {}", e))?;
+        let upload = upload_file(entry.path.clone(), parent);
+        uploads.spawn(async move {
+            let _permit = permit;
+            upload.await
+        });
+    }
+
+    while let Some(result) = uploads.join_next().await {
+        let file = result.map_err(|e| format!("アップロードに失敗しました: {}", e))??;
+        created.push(file);
+    }
+
+    Ok(created)
+}
+
+/// Extract webViewLink, falling back to the standard viewer URL for files
+/// that don't report one (e.g. some binary uploads).
+fn file_link_from_response(file_id: &str, body: &serde_json::Value) -> String {
+    body.get("webViewLink")
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .unwrap_or_else(|| format!("https://drive.google.com/file/d/{}/view", file_id))
+}
+
+/// Drive allows `/` in names; keep downloads inside the chosen folder
+fn sanitize_file_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| if matches!(c, '/' | '\\') { '_' } else { c })
+        .collect();
+    match cleaned.trim() {
+        "" | "." | ".." => "download".to_string(),
+        n => n.to_string(),
+    }
+}
+
+fn revoke_url(endpoint: &str, token: &str) -> String {
+    format!("{}?token={}", endpoint, urlencoding(token))
+}
+
 fn urlencoding(s: &str) -> String {
     s.chars()
         .map(|c| match c {
@@ -401,6 +1208,259 @@ mod tests {
         std::env::temp_dir().join(format!("cowork-gdrive-test-{}", uuid::Uuid::new_v4()))
     }
 
+    #[tokio::test]
+    async fn test_cancelled_download_removes_partial_file() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("large.bin");
+        let (tx, mut rx) = mpsc::channel(8);
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let writer_cancel = Arc::clone(&cancel);
+        let writer_target = target.clone();
+        let writer = tokio::spawn(async move { write_chunks(&mut rx, &writer_target, false, &writer_cancel).await });
+
+        tx.send(Ok(vec![1u8; 1024])).await.unwrap();
+        tx.send(Ok(vec![2u8; 1024])).await.unwrap();
+        cancel.store(true, Ordering::Relaxed);
+        let _ = tx.send(Ok(vec![3u8; 1024])).await;
+        drop(tx);
+
+        assert_eq!(writer.await.unwrap().unwrap_err(), DOWNLOAD_CANCELLED);
+        assert!(!target.exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_write_chunks_completes() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("small.txt");
+        let (tx, mut rx) = mpsc::channel(8);
+        tx.send(Ok(b"hello ".to_vec())).await.unwrap();
+        tx.send(Ok(b"drive".to_vec())).await.unwrap();
+        drop(tx);
+
+        let written = write_chunks(&mut rx, &target, false, &AtomicBool::new(false)).await.unwrap();
+        assert_eq!(written, 11);
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "hello drive");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_resume_continues_partial_file() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let target = dir.join("video.mp4");
+        let part = part_path(&target);
+        assert_eq!(part, dir.join("video.mp4.part"));
+
+        assert_eq!(partial_len(&part).await, 0);
+        assert_eq!(resume_range_header(0), None);
+
+        std::fs::write(&part, vec![7u8; 1500]).unwrap();
+        let offset = partial_len(&part).await;
+        assert_eq!(offset, 1500);
+        assert_eq!(resume_range_header(offset).as_deref(), Some("bytes=1500-"));
+
+        let (tx, mut rx) = mpsc::channel(8);
+        tx.send(Ok(vec![8u8; 500])).await.unwrap();
+        drop(tx);
+        write_chunks(&mut rx, &part, true, &AtomicBool::new(false)).await.unwrap();
+        let content = std::fs::read(&part).unwrap();
+        assert_eq!(content.len(), 2000);
+        assert_eq!(content[1499], 7);
+        assert_eq!(content[1500], 8);
+
+        // A failed transfer keeps what was written
+        let (tx, mut rx) = mpsc::channel(8);
+        tx.send(Ok(vec![9u8; 100])).await.unwrap();
+        tx.send(Err("接続が切れました".to_string())).await.unwrap();
+        drop(tx);
+        assert!(write_chunks(&mut rx, &part, true, &AtomicBool::new(false)).await.is_err());
+        assert_eq!(partial_len(&part).await, 2100);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_cancel_download_tracks_active_ids() {
+        let client = GDriveClient::new(temp_dir(), None);
+        let cancel = client.register_download("file1");
+        assert!(!client.cancel_download("other"));
+        assert!(client.cancel_download("file1"));
+        assert!(cancel.load(Ordering::Relaxed));
+        // Already removed
+        assert!(!client.cancel_download("file1"));
+
+        let cancel = client.register_download("file2");
+        client.unregister_download("file2", &cancel);
+        assert!(!client.cancel_download("file2"));
+    }
+
+    #[tokio::test]
+    async fn test_read_chunk_walks_file() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("big.bin");
+        std::fs::write(&path, b"abcdefghij").unwrap();
+
+        let mut file = fs::File::open(&path).await.unwrap();
+        assert_eq!(read_chunk(&mut file, 0, 4).await.unwrap(), b"abcd");
+        assert_eq!(read_chunk(&mut file, 4, 4).await.unwrap(), b"efgh");
+        assert_eq!(read_chunk(&mut file, 8, 4).await.unwrap(), b"ij");
+        // Re-reading after a partial 308 seeks back
+        assert_eq!(read_chunk(&mut file, 6, 4).await.unwrap(), b"ghij");
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_resumable_upload_ranges() {
+        assert_eq!(upload_content_range(0, 8, 20), "bytes 0-7/20");
+        assert_eq!(upload_content_range(16, 4, 20), "bytes 16-19/20");
+        assert_eq!(upload_content_range(20, 0, 20), "bytes */20");
+
+        assert_eq!(uploaded_until(Some("bytes=0-7")), 8);
+        assert_eq!(uploaded_until(None), 0);
+        assert_eq!(uploaded_until(Some("garbage")), 0);
+        assert_eq!(UPLOAD_CHUNK_SIZE % (256 * 1024), 0);
+    }
+
+    #[test]
+    fn test_download_target_never_replaces_without_overwrite() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(&dir).unwrap();
+        let existing = dir.join("report.pdf");
+        std::fs::write(&existing, "mine").unwrap();
+        // A stale partial download of the same name must not win
+        std::fs::write(part_path(&existing), "part").unwrap();
+
+        assert_eq!(download_target(&existing, None, false), dir.join("report (1).pdf"));
+        assert_eq!(download_target(&dir, Some(existing.clone()), false), dir.join("report (1).pdf"));
+        assert_eq!(download_target(&existing, None, true), existing);
+        assert_eq!(download_target(&dir.join("new.pdf"), None, false), dir.join("new.pdf"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_content_range_start() {
+        assert_eq!(content_range_start(Some("bytes 100-199/200")), Some(100));
+        assert_eq!(content_range_start(Some("bytes 0-9/10")), Some(0));
+        assert_eq!(content_range_start(Some("bytes */200")), None);
+        assert_eq!(content_range_start(None), None);
+    }
+
+    #[test]
+    fn test_range_header() {
+        assert_eq!(range_header(1024).as_deref(), Some("bytes=0-1023"));
+        assert_eq!(range_header(1).as_deref(), Some("bytes=0-0"));
+        assert!(range_header(0).is_none());
+    }
+
+    #[test]
+    fn test_truncate_head_when_range_ignored() {
+        // Whole body returned despite the Range header
+        let body = b"0123456789".to_vec();
+        assert_eq!(truncate_head(body.clone(), 4), b"0123");
+        // Shorter than requested: kept as is
+        assert_eq!(truncate_head(body.clone(), 100), body);
+    }
+
+    #[test]
+    fn test_file_preview_detects_binary() {
+        let text = FilePreview::from_bytes("こんにちは\nworld".as_bytes());
+        assert!(!text.is_binary);
+        assert_eq!(text.text.as_deref(), Some("こんにちは\nworld"));
+
+        let binary = FilePreview::from_bytes(&[0x89, b'P', b'N', b'G', 0, 0]);
+        assert!(binary.is_binary);
+        assert!(binary.text.is_none());
+        assert_eq!(binary.bytes, 6);
+    }
+
+    fn stub_file(id: String, name: &str, is_folder: bool) -> DriveFile {
+        DriveFile {
+            id,
+            name: name.to_string(),
+            mime_type: if is_folder { FOLDER_MIME.to_string() } else { String::new() },
+            is_folder,
+            size: None,
+            modified_time: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_upload_tree_maps_parents() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::create_dir_all(dir.join(".git")).unwrap();
+        std::fs::write(dir.join("a.txt"), "a").unwrap();
+        std::fs::write(dir.join(".env"), "secret").unwrap();
+        std::fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+        std::fs::write(dir.join(".git").join("HEAD"), "ref").unwrap();
+
+        let plan = plan_upload(&dir).unwrap();
+        let root_name = plan[0].name.clone();
+        let calls = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let folder_calls = Arc::clone(&calls);
+        let file_calls = Arc::clone(&calls);
+        let created = upload_tree(
+            &plan,
+            Some("dest".to_string()),
+            move |name, parent| {
+                folder_calls.lock().unwrap().push((name.clone(), parent));
+                let id = format!("folder:{}", name);
+                async move { Ok(stub_file(id, &name, true)) }
+            },
+            move |path, parent| {
+                let name = path.file_name().unwrap().to_string_lossy().to_string();
+                file_calls.lock().unwrap().push((name.clone(), Some(parent)));
+                async move { Ok(stub_file(format!("file:{}", name), &name, false)) }
+            },
+        )
+        .await
+        .unwrap();
+
+        let mut calls = calls.lock().unwrap().clone();
+        calls.sort();
+        let root_id = format!("folder:{}", root_name);
+        let mut expected = vec![
+            (root_name.clone(), Some("dest".to_string())),
+            ("sub".to_string(), Some(root_id.clone())),
+            ("a.txt".to_string(), Some(root_id)),
+            ("b.txt".to_string(), Some("folder:sub".to_string())),
+        ];
+        expected.sort();
+        assert_eq!(calls, expected);
+        assert_eq!(created.len(), 4);
+        assert_eq!(created.iter().filter(|f| f.is_folder).count(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_upload_tree_stops_on_failure() {
+        let dir = temp_dir();
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+
+        let plan = plan_upload(&dir).unwrap();
+        let err = upload_tree(
+            &plan,
+            None,
+            |name, _| async move { Err::<DriveFile, _>(format!("作成失敗: {}", name)) },
+            |_, _| async { Err::<DriveFile, _>("呼ばれないはず".to_string()) },
+        )
+        .await
+        .unwrap_err();
+        assert!(err.starts_with("作成失敗"));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn test_gdrive_config_serialization() {
         let config = GDriveConfig {
@@ -595,7 +1655,9 @@ mod tests {
     #[tokio::test]
     async fn test_gdrive_client_logout() {
         let dir = temp_dir();
-        let client = GDriveClient::new(dir.clone(), None);
+        let mut client = GDriveClient::new(dir.clone(), None);
+        // Unreachable endpoint: the revoke fails but logout must still succeed
+        client.revoke_endpoint = "http://127.0.0.1:9/revoke".to_string();
 
         let tokens = GDriveTokens {
             access_token: "ya29.logout-test".to_string(),
@@ -614,6 +1676,35 @@ mod tests {
         let _ = fs::remove_dir_all(&dir).await;
     }
 
+    #[test]
+    fn test_revoke_url() {
+        let url = revoke_url(REVOKE_URL, "1//refresh+token");
+        assert_eq!(
+            url,
+            "https://oauth2.googleapis.com/revoke?token=1%2F%2Frefresh%2Btoken"
+        );
+    }
+
+    #[test]
+    fn test_file_link_from_response() {
+        let body = serde_json::json!({
+            "webViewLink": "https://docs.google.com/document/d/abc/edit?usp=drivesdk"
+        });
+        assert_eq!(
+            file_link_from_response("abc", &body),
+            "https://docs.google.com/document/d/abc/edit?usp=drivesdk"
+        );
+    }
+
+    #[test]
+    fn test_file_link_from_response_fallback() {
+        let body = serde_json::json!({});
+        assert_eq!(
+            file_link_from_response("xyz", &body),
+            "https://drive.google.com/file/d/xyz/view"
+        );
+    }
+
     #[tokio::test]
     async fn test_gdrive_client_logout_no_tokens() {
         let client = GDriveClient::new(temp_dir(), None);
@@ -668,4 +1759,80 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().contains("OAuth設定が見つかりません"));
     }
+
+    #[tokio::test]
+    async fn test_gdrive_client_set_config() {
+        let dir = temp_dir();
+        let client = GDriveClient::new(dir.clone(), None);
+        assert!(!client.is_configured().await);
+
+        client.set_config("my-client-id", "my-secret").await.unwrap();
+        assert!(client.is_configured().await);
+        assert!(dir.join("gdrive_config.json").exists());
+
+        // A fresh client picks the written config up on load
+        let reloaded = GDriveClient::new(dir.clone(), None);
+        reloaded.load().await.unwrap();
+        assert!(reloaded.is_configured().await);
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_gdrive_client_set_config_rejects_empty() {
+        let dir = temp_dir();
+        let client = GDriveClient::new(dir.clone(), None);
+        assert!(client.set_config("  ", "secret").await.is_err());
+        assert!(client.set_config("id", "").await.is_err());
+        assert!(!client.is_configured().await);
+        assert!(!dir.join("gdrive_config.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_gdrive_client_auth_listener_notified() {
+        let dir = temp_dir();
+        let mut client = GDriveClient::new(dir.clone(), None);
+        client.revoke_endpoint = "http://127.0.0.1:9/revoke".to_string();
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_ref = events.clone();
+        client.set_auth_listener(Box::new(move |authenticated| {
+            events_ref.lock().unwrap().push(authenticated);
+        }));
+
+        let tokens = GDriveTokens {
+            access_token: "ya29.listener".to_string(),
+            refresh_token: None,
+            expires_at: None,
+        };
+        client.save_tokens(&tokens).await.unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![true]);
+
+        client.logout().await.unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![true, false]);
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_gdrive_client_cancel_auth_flow() {
+        let dir = temp_dir();
+        let client = GDriveClient::new(dir.clone(), None);
+        assert!(!client.cancel_auth_flow().await);
+
+        client.set_config("my-client-id", "my-secret").await.unwrap();
+        let (_url, _port, rx) = client.start_auth_flow().await.unwrap();
+        assert!(client.cancel_auth_flow().await);
+
+        let result = rx.await.unwrap();
+        assert_eq!(result.unwrap_err(), oauth_server::CANCELLED_MESSAGE);
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("議事録.docx"), "議事録.docx");
+        assert_eq!(sanitize_file_name("a/b\\c.txt"), "a_b_c.txt");
+        assert_eq!(sanitize_file_name(".."), "download");
+    }
 }
@@ -0,0 +1,232 @@
+use std::path::{Path, PathBuf};
+
+/// A single parsed .gitignore line
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+    regex: regex_lite::Regex,
+    negate: bool,
+    dir_only: bool,
+    /// Patterns containing a slash match the full relative path,
+    /// others match the entry name at any depth
+    anchored: bool,
+}
+
+/// Matches paths against the rules of the nearest `.gitignore`.
+/// Independent of the hidden-file filter; callers apply both as needed.
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreMatcher {
+    base: PathBuf,
+    rules: Vec<IgnoreRule>,
+}
+
+impl IgnoreMatcher {
+    /// Build a matcher from the `.gitignore` in `dir` or its closest ancestor.
+    /// Returns an empty matcher (ignores nothing) when none is found.
+    pub fn from_dir(dir: &Path) -> Self {
+        let mut current = Some(dir);
+        while let Some(d) = current {
+            let candidate = d.join(".gitignore");
+            if let Ok(content) = std::fs::read_to_string(&candidate) {
+                return Self::parse(d.to_path_buf(), &content);
+            }
+            // Don't look past the repository root
+            if d.join(".git").exists() {
+                break;
+            }
+            current = d.parent();
+        }
+        Self::default()
+    }
+
+    /// Parse `.gitignore` content whose patterns are relative to `base`
+    pub fn parse(base: PathBuf, content: &str) -> Self {
+        let rules = content.lines().filter_map(parse_rule).collect();
+        Self { base, rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether `path` (absolute, or relative to the .gitignore's folder) is ignored.
+    /// A path inside an ignored directory is ignored as well.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        if self.rules.is_empty() {
+            return false;
+        }
+        let rel = if path.is_absolute() {
+            match path.strip_prefix(&self.base) {
+                Ok(r) => r,
+                Err(_) => return false,
+            }
+        } else {
+            path
+        };
+        let parts: Vec<String> = rel
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_string())
+            .collect();
+        if parts.is_empty() {
+            return false;
+        }
+
+        // Git never re-includes a file whose parent directory is excluded
+        for end in 1..parts.len() {
+            if self.matches(&parts[..end], true) {
+                return true;
+            }
+        }
+        self.matches(&parts, is_dir)
+    }
+
+    fn matches(&self, parts: &[String], is_dir: bool) -> bool {
+        let full = parts.join("/");
+        let name = parts.last().map(|s| s.as_str()).unwrap_or_default();
+        let mut ignored = false;
+        for rule in &self.rules {
+            if rule.dir_only && !is_dir {
+                continue;
+            }
+            let target = if rule.anchored { full.as_str() } else { name };
+            if rule.regex.is_match(target) {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
+
+fn parse_rule(line: &str) -> Option<IgnoreRule> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+    let (negate, pattern) = match line.strip_prefix('!') {
+        Some(rest) => (true, rest),
+        None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, pattern) = match pattern.strip_suffix('/') {
+        Some(rest) => (true, rest),
+        None => (false, pattern),
+    };
+    let anchored = pattern.contains('/');
+    let pattern = pattern.strip_prefix('/').unwrap_or(pattern);
+    if pattern.is_empty() {
+        return None;
+    }
+    let regex = regex_lite::Regex::new(&glob_to_regex(pattern)).ok()?;
+    Some(IgnoreRule {
+        regex,
+        negate,
+        dir_only,
+        anchored,
+    })
+}
+
+/// Translate a gitignore glob into an anchored regex
+fn glob_to_regex(pattern: &str) -> String {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut re = String::from("^");
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                if chars.get(i + 2) == Some(&'/') {
+                    // "**/" matches zero or more directories
+                    re.push_str("(?:.*/)?");
+                    i += 3;
+                } else {
+                    re.push_str(".*");
+                    i += 2;
+                }
+                continue;
+            }
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            '[' => {
+                if let Some(len) = chars[i..].iter().position(|&c| c == ']') {
+                    let class: String = chars[i + 1..i + len].iter().collect();
+                    let class = class.strip_prefix('!').map(|c| format!("^{}", c)).unwrap_or(class);
+                    re.push('[');
+                    re.push_str(&class.replace('\\', "\\\\"));
+                    re.push(']');
+                    i += len + 1;
+                    continue;
+                }
+                re.push_str("\\[");
+            }
+            c => re.push_str(&regex_lite::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    re.push('$');
+    re
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matcher(content: &str) -> IgnoreMatcher {
+        IgnoreMatcher::parse(PathBuf::from("/repo"), content)
+    }
+
+    #[test]
+    fn test_extension_pattern() {
+        let m = matcher("*.log\n");
+        assert!(m.is_ignored(Path::new("/repo/debug.log"), false));
+        assert!(m.is_ignored(Path::new("/repo/sub/deep/app.log"), false));
+        assert!(!m.is_ignored(Path::new("/repo/main.rs"), false));
+    }
+
+    #[test]
+    fn test_comments_and_negation() {
+        let m = matcher("# build output\n*.log\n!keep.log\n");
+        assert!(m.is_ignored(Path::new("/repo/a.log"), false));
+        assert!(!m.is_ignored(Path::new("/repo/keep.log"), false));
+    }
+
+    #[test]
+    fn test_dir_only_and_children() {
+        let m = matcher("target/\n");
+        assert!(m.is_ignored(Path::new("/repo/target"), true));
+        assert!(!m.is_ignored(Path::new("/repo/target"), false));
+        assert!(m.is_ignored(Path::new("/repo/target/debug/app"), false));
+    }
+
+    #[test]
+    fn test_anchored_pattern() {
+        let m = matcher("/dist\ndocs/*.pdf\n");
+        assert!(m.is_ignored(Path::new("/repo/dist"), true));
+        assert!(!m.is_ignored(Path::new("/repo/sub/dist"), true));
+        assert!(m.is_ignored(Path::new("/repo/docs/a.pdf"), false));
+        assert!(!m.is_ignored(Path::new("/repo/other/docs/a.pdf"), false));
+    }
+
+    #[test]
+    fn test_double_star() {
+        let m = matcher("**/cache/**\n");
+        assert!(m.is_ignored(Path::new("/repo/cache/x"), false));
+        assert!(m.is_ignored(Path::new("/repo/a/b/cache/x"), false));
+    }
+
+    #[test]
+    fn test_path_outside_base_not_ignored() {
+        let m = matcher("*.log\n");
+        assert!(!m.is_ignored(Path::new("/elsewhere/a.log"), false));
+    }
+
+    #[test]
+    fn test_from_dir_finds_parent_gitignore() {
+        let root = std::env::temp_dir().join(format!("cowork-gitignore-test-{}", uuid::Uuid::new_v4()));
+        let sub = root.join("sub");
+        std::fs::create_dir_all(&sub).unwrap();
+        std::fs::write(root.join(".gitignore"), "*.log\n").unwrap();
+
+        let m = IgnoreMatcher::from_dir(&sub);
+        assert!(!m.is_empty());
+        assert!(m.is_ignored(&sub.join("out.log"), false));
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -1,22 +1,38 @@
 mod approval_server;
+mod chat_history;
 mod claude;
+mod config_snapshot;
 mod files;
 mod gdrive;
+mod gitignore;
+mod http_client;
 mod oauth_server;
+mod paths;
 mod skills;
 mod slack;
+mod tasks;
 mod todos;
 mod translator;
-
-use claude::{ChatMessage, ClaudeManager};
-use files::FileEntry;
-use gdrive::{DriveFile, GDriveClient};
-use skills::{CoworkSkill, SkillStore};
-use slack::{SlackClient, SlackListItem, SlackSettings};
+mod util;
+
+use chat_history::ChatHistory;
+use claude::{ChatMessage, ClaudeManager, ClaudeSettings, HookStatus};
+use files::{FileEntry, FileStats, SearchMatch};
+use gdrive::{DriveFile, FilePreview, GDriveClient};
+use gitignore::IgnoreMatcher;
+use http_client::NetworkSettings;
+use serde::{Deserialize, Serialize};
+use skills::{CoworkSkill, MigrationReport, SkillSource, SkillStore};
+use slack::{
+    SlackBatchResult, SlackClient, SlackListItem, SlackMessage, SlackSettings, SlackWorkspace,
+};
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tasks::{TaskInfo, TaskRegistry};
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_log::{RotationStrategy, Target, TargetKind};
 use todos::{TodoItem, TodoManager};
 use tokio::sync::{Mutex, oneshot};
 
@@ -25,6 +41,13 @@ type SkillState = Arc<SkillStore>;
 type GDriveState = Arc<GDriveClient>;
 type SlackState = Arc<SlackClient>;
 type TodoState = Arc<TodoManager>;
+type SearchCancelState = Arc<AtomicBool>;
+/// Files being followed by `tail_follow`, keyed by path, with their stop flags
+type TailFollowState = Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>;
+/// Stop flag of the working dir's `.claude/skills/` watcher
+type SkillsWatchState = Arc<Mutex<Option<Arc<AtomicBool>>>>;
+type TaskState = Arc<TaskRegistry>;
+type ChatHistoryState = Arc<ChatHistory>;
 type ApprovalPendingState = Arc<Mutex<HashMap<String, oneshot::Sender<bool>>>>;
 
 // ── Claude commands ──
@@ -41,7 +64,7 @@ async fn send_message(
         content: message.clone(),
         timestamp: chrono::Utc::now().to_rfc3339(),
     };
-    let _ = app.emit("claude:message", &user_msg);
+    chat_history::emit_message(&app, &user_msg);
     state.send_message(&app, message).await
 }
 
@@ -51,53 +74,201 @@ async fn set_working_directory(
     state: State<'_, ClaudeState>,
     skill_state: State<'_, SkillState>,
     path: String,
-) -> Result<(), String> {
-    state.set_working_dir(path.clone()).await;
-    skill_state.set_working_dir(path.clone()).await;
+) -> Result<String, String> {
+    // Validate before touching any manager so a bad path changes nothing
+    let path = paths::normalize_working_dir(&path)?;
+
+    // Flush the previous workspace's chat before switching histories
+    if let Some(history) = app.try_state::<ChatHistoryState>() {
+        if let Err(e) = history.save_now().await {
+            log::warn!("Failed to save chat history: {}", e);
+        }
+    }
+
+    apply_working_dir(&state, &skill_state, &path, |event| {
+        let _ = app.emit("workspace:changed", event);
+    })
+    .await;
+    restart_skills_watch(&app, &path).await;
+
+    if let (Some(history), Ok(data_dir)) =
+        (app.try_state::<ChatHistoryState>(), app.path().app_data_dir())
+    {
+        let messages = load_chat_history(&data_dir, &path).await.unwrap_or_default();
+        history.reset(chat_history_path(&data_dir, &path), messages);
+    }
 
     if !path.is_empty() {
         if let Err(e) = save_last_working_dir(&app, &path).await {
             log::warn!("Failed to save working dir: {}", e);
         }
 
+        if let Ok(false) = paths::check_working_dir_writable(&path) {
+            let msg = ChatMessage {
+                id: uuid::Uuid::new_v4().to_string(),
+                role: "system".to_string(),
+                content: "この作業フォルダには書き込みできません。ファイルの作成や編集は失敗します".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            chat_history::emit_message(&app, &msg);
+        }
+
+        let rules = state.get_repo_rules().await;
+        if rules.is_present() && !rules.accepted {
+            let msg = ChatMessage {
+                id: uuid::Uuid::new_v4().to_string(),
+                role: "system".to_string(),
+                content: "このフォルダの .cowork/approvals.json は未承認のため無視しています。設定画面で内容を確認して承認してください".to_string(),
+                timestamp: chrono::Utc::now().to_rfc3339(),
+            };
+            chat_history::emit_message(&app, &msg);
+        }
+
         // Migrate legacy JSON skills
-        match skill_state.migrate_legacy_skills().await {
-            Ok(count) if count > 0 => {
-                log::info!("Migrated {} legacy JSON skills", count);
-                let msg = ChatMessage {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    role: "system".to_string(),
-                    content: format!("{}件のレガシースキルを移行しました", count),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                };
-                let _ = app.emit("claude:message", &msg);
+        match skill_state.migrate_legacy_skills(false).await {
+            Ok(report) => {
+                for e in &report.errors {
+                    log::warn!("Legacy skill migration error: {}", e);
+                }
+                if report.migrated > 0 {
+                    log::info!("Migrated {} legacy JSON skills", report.migrated);
+                    let msg = ChatMessage {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        role: "system".to_string(),
+                        content: format!("{}件のレガシースキルを移行しました", report.migrated),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    };
+                    chat_history::emit_message(&app, &msg);
+                }
             }
             Err(e) => {
                 log::warn!("Legacy skill migration failed: {}", e);
             }
-            _ => {}
         }
 
         // Migrate old .claude/commands/ to .claude/skills/
-        match skill_state.migrate_commands_to_skills().await {
-            Ok(count) if count > 0 => {
-                log::info!("Migrated {} commands to skills format", count);
-                let msg = ChatMessage {
-                    id: uuid::Uuid::new_v4().to_string(),
-                    role: "system".to_string(),
-                    content: format!("{}件のコマンドをスキル形式に移行しました", count),
-                    timestamp: chrono::Utc::now().to_rfc3339(),
-                };
-                let _ = app.emit("claude:message", &msg);
+        match skill_state.migrate_commands_to_skills(false).await {
+            Ok(report) => {
+                for e in &report.errors {
+                    log::warn!("Command migration error: {}", e);
+                }
+                if report.migrated > 0 {
+                    log::info!("Migrated {} commands to skills format", report.migrated);
+                    let msg = ChatMessage {
+                        id: uuid::Uuid::new_v4().to_string(),
+                        role: "system".to_string(),
+                        content: format!("{}件のコマンドをスキル形式に移行しました", report.migrated),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    };
+                    chat_history::emit_message(&app, &msg);
+                }
             }
             Err(e) => {
                 log::warn!("Command to skill migration failed: {}", e);
             }
-            _ => {}
         }
+
+        // Migrations may have added skills; panels reload once more
+        let event = WorkspaceChanged {
+            path: path.clone(),
+            migrated: true,
+        };
+        let _ = app.emit("workspace:changed", &event);
     }
 
-    Ok(())
+    Ok(path)
+}
+
+/// `workspace:changed` payload
+#[derive(Debug, Clone, PartialEq, Serialize)]
+struct WorkspaceChanged {
+    /// Normalized working directory ("" when cleared)
+    path: String,
+    /// `true` for the follow-up sent after legacy migrations finish
+    migrated: bool,
+}
+
+/// Point the Claude and skill managers at an already-normalized `path`, then
+/// report the switch through `notify`
+async fn apply_working_dir(
+    claude: &ClaudeManager,
+    skills: &SkillStore,
+    path: &str,
+    notify: impl FnOnce(&WorkspaceChanged),
+) {
+    claude.set_working_dir(path.to_string()).await;
+    skills.set_working_dir(path.to_string()).await;
+    notify(&WorkspaceChanged {
+        path: path.to_string(),
+        migrated: false,
+    });
+}
+
+/// Stop the previous skills watcher and watch `working_dir`'s
+/// `.claude/skills/` and the user's `~/.claude/skills/`, emitting
+/// `skills:changed` after edits settle
+async fn restart_skills_watch(app: &AppHandle, working_dir: &str) {
+    let Some(watch) = app.try_state::<SkillsWatchState>() else {
+        return;
+    };
+    let user_dir = app
+        .try_state::<SkillState>()
+        .and_then(|s| s.user_dir().map(Path::to_path_buf));
+    let dirs = skills_watch_dirs(working_dir, user_dir.as_deref());
+
+    let stop = Arc::new(AtomicBool::new(false));
+    let new_stop = if dirs.is_empty() { None } else { Some(Arc::clone(&stop)) };
+    if let Some(previous) = std::mem::replace(&mut *watch.lock().await, new_stop) {
+        previous.store(true, Ordering::Relaxed);
+    }
+    if dirs.is_empty() {
+        return;
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<()>(4);
+    for dir in dirs {
+        let stop = Arc::clone(&stop);
+        let tx = tx.clone();
+        tokio::spawn(async move {
+            files::watch_dir(&dir, &stop, &tx).await;
+        });
+    }
+    drop(tx);
+    let app = app.clone();
+    // Ends once every watcher has stopped and dropped its sender
+    tokio::spawn(async move {
+        while rx.recv().await.is_some() {
+            let _ = app.emit("skills:changed", ());
+        }
+    });
+}
+
+/// Skill folders to watch: the project's (if a working dir is set), then the user's
+fn skills_watch_dirs(working_dir: &str, user_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if !working_dir.is_empty() {
+        dirs.push(Path::new(working_dir).join(".claude").join("skills"));
+    }
+    dirs.extend(user_dir.map(Path::to_path_buf));
+    dirs
+}
+
+/// Zip the working dir's `.claude/` into the data dir; returns the archive path
+#[tauri::command]
+async fn snapshot_claude_config(state: State<'_, ClaudeState>) -> Result<String, String> {
+    state.snapshot_claude_config().await
+}
+
+/// Replace the working dir's `.claude/` with a snapshot; returns the file count
+#[tauri::command]
+async fn restore_claude_config(state: State<'_, ClaudeState>, archive: String) -> Result<usize, String> {
+    state.restore_claude_config(&archive).await
+}
+
+/// Whether files can be created in `path`
+#[tauri::command]
+async fn check_working_dir_writable(path: String) -> Result<bool, String> {
+    paths::check_working_dir_writable(&path)
 }
 
 #[tauri::command]
@@ -105,6 +276,19 @@ async fn cancel_message(state: State<'_, ClaudeState>) -> Result<(), String> {
     state.cancel().await
 }
 
+/// Cancel and, if no result arrived yet, drop the turn from the session
+#[tauri::command]
+async fn cancel_and_discard(state: State<'_, ClaudeState>) -> Result<(), String> {
+    state.cancel_and_discard().await
+}
+
+/// Absolute path a (possibly relative) path refers to in the working directory
+#[tauri::command]
+async fn resolve_working_path(state: State<'_, ClaudeState>, path: String) -> Result<String, String> {
+    let working_dir = state.get_working_dir().await;
+    paths::resolve_against(&working_dir, &path).map(|p| p.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 async fn get_working_directory(state: State<'_, ClaudeState>) -> Result<String, String> {
     Ok(state.get_working_dir().await)
@@ -116,24 +300,31 @@ async fn respond_to_approval(
     approval_id: String,
     approved: bool,
 ) -> Result<(), String> {
-    let tx = {
-        let mut pending = state.lock().await;
-        pending.remove(&approval_id)
-    };
-
-    if let Some(tx) = tx {
-        let _ = tx.send(approved);
+    if approval_server::resolve_pending(&state, &approval_id, approved).await {
         Ok(())
     } else {
         Err(format!("承認リクエストが見つかりません: {}", approval_id))
     }
 }
 
+#[tauri::command]
+async fn list_pending_approvals(state: State<'_, ApprovalPendingState>) -> Result<Vec<String>, String> {
+    Ok(approval_server::pending_ids(&state).await)
+}
+
 // ── File browser commands ──
 
 #[tauri::command]
-async fn list_files(path: String) -> Result<Vec<FileEntry>, String> {
-    files::list_directory(&path).await
+async fn list_files(
+    path: String,
+    respect_gitignore: Option<bool>,
+) -> Result<Vec<FileEntry>, String> {
+    if respect_gitignore.unwrap_or(false) {
+        let matcher = IgnoreMatcher::from_dir(Path::new(&path));
+        files::list_directory_filtered(&path, Some(&matcher)).await
+    } else {
+        files::list_directory(&path).await
+    }
 }
 
 #[tauri::command]
@@ -141,6 +332,150 @@ async fn get_file_tree(path: String) -> Result<FileEntry, String> {
     files::get_file_tree(&path).await
 }
 
+#[derive(Debug, Clone, Serialize)]
+struct SearchDone {
+    total: usize,
+    cancelled: bool,
+}
+
+/// Search file contents under `root`, emitting `search:result` per match and
+/// `search:done` with the total once finished or cancelled.
+#[tauri::command]
+async fn search_in_directory_streaming(
+    app: AppHandle,
+    cancel: State<'_, SearchCancelState>,
+    root: String,
+    query: String,
+) -> Result<usize, String> {
+    let cancel = cancel.inner().clone();
+    cancel.store(false, Ordering::Relaxed);
+
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<SearchMatch>(64);
+    let emitter = app.clone();
+    let forward = tokio::spawn(async move {
+        while let Some(m) = rx.recv().await {
+            let _ = emitter.emit("search:result", &m);
+        }
+    });
+
+    let root = Path::new(&root);
+    let matcher = IgnoreMatcher::from_dir(root);
+    let result = files::search_directory(root, &query, Some(&matcher), &cancel, &tx).await;
+    drop(tx);
+    let _ = forward.await;
+
+    let total = *result.as_ref().unwrap_or(&0);
+    let done = SearchDone {
+        total,
+        cancelled: cancel.load(Ordering::Relaxed),
+    };
+    let _ = app.emit("search:done", &done);
+    result
+}
+
+#[tauri::command]
+async fn cancel_search(cancel: State<'_, SearchCancelState>) -> Result<(), String> {
+    cancel.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Project tags (rust, node, python, git) detected from marker files
+#[tauri::command]
+async fn detect_project_type(path: String) -> Result<Vec<String>, String> {
+    Ok(files::detect_project_type(&path))
+}
+
+#[tauri::command]
+async fn read_file_tail(path: String, lines: usize) -> Result<String, String> {
+    files::read_file_tail(&path, lines).await
+}
+
+/// Emit `file:line` for each line appended to `path` until `tail_stop`.
+/// Following a path again replaces the previous follow.
+#[tauri::command]
+async fn tail_follow(
+    app: AppHandle,
+    follows: State<'_, TailFollowState>,
+    path: String,
+    from_end: bool,
+) -> Result<(), String> {
+    if !Path::new(&path).is_file() {
+        return Err(format!("ファイルが見つかりません: {}", path));
+    }
+
+    let stop = Arc::new(AtomicBool::new(false));
+    if let Some(previous) = follows.lock().await.insert(path.clone(), Arc::clone(&stop)) {
+        previous.store(true, Ordering::Relaxed);
+    }
+
+    let follows = follows.inner().clone();
+    tokio::spawn(async move {
+        let (tx, mut rx) = tokio::sync::mpsc::channel::<String>(64);
+        let emitter = app.clone();
+        let line_path = path.clone();
+        let forward = tokio::spawn(async move {
+            while let Some(line) = rx.recv().await {
+                let payload = files::FileLine {
+                    path: line_path.clone(),
+                    line,
+                };
+                let _ = emitter.emit("file:line", &payload);
+            }
+        });
+
+        if let Err(e) = files::follow_file(Path::new(&path), from_end, &stop, &tx).await {
+            log::warn!("tail_follow {} failed: {}", path, e);
+        }
+        drop(tx);
+        let _ = forward.await;
+
+        // Only unregister if this follow wasn't replaced in the meantime
+        let mut follows = follows.lock().await;
+        if follows.get(&path).is_some_and(|s| Arc::ptr_eq(s, &stop)) {
+            follows.remove(&path);
+        }
+    });
+    Ok(())
+}
+
+/// Stop following `path`. Returns false if it wasn't being followed.
+#[tauri::command]
+async fn tail_stop(follows: State<'_, TailFollowState>, path: String) -> Result<bool, String> {
+    match follows.lock().await.remove(&path) {
+        Some(stop) => {
+            stop.store(true, Ordering::Relaxed);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[tauri::command]
+async fn file_stats(path: String) -> Result<FileStats, String> {
+    files::file_stats(&path).await
+}
+
+/// Default context lines for `diff_files`
+const DIFF_DEFAULT_CONTEXT: usize = 3;
+
+/// Unified diff of two text files, e.g. a backup against the current file
+#[tauri::command]
+async fn diff_files(a: String, b: String, context: Option<usize>) -> Result<String, String> {
+    files::diff_files(&a, &b, context.unwrap_or(DIFF_DEFAULT_CONTEXT)).await
+}
+
+/// Hex digest of a file ("sha256" or "md5")
+#[tauri::command]
+async fn hash_file(path: String, algorithm: String) -> Result<String, String> {
+    files::hash_file(&path, &algorithm).await
+}
+
+/// Returns (total, available) bytes for the filesystem containing `path`
+#[tauri::command]
+async fn get_disk_space(path: String) -> Result<(u64, u64), String> {
+    files::get_disk_space(&path)
+}
+
 // ── Skill commands ──
 
 #[tauri::command]
@@ -148,6 +483,32 @@ async fn list_skills(state: State<'_, SkillState>) -> Result<Vec<CoworkSkill>, S
     state.list().await
 }
 
+#[tauri::command]
+async fn list_skills_with_status(
+    state: State<'_, SkillState>,
+) -> Result<Vec<(CoworkSkill, Vec<String>)>, String> {
+    state.list_with_status().await
+}
+
+/// Re-run both skill migrations on demand and report what happened.
+/// Existing skills with the same name are skipped unless `overwrite` is set.
+#[tauri::command]
+async fn migrate_skills_now(
+    state: State<'_, SkillState>,
+    overwrite: Option<bool>,
+) -> Result<MigrationReport, String> {
+    let overwrite = overwrite.unwrap_or(false);
+    let mut report = state.migrate_legacy_skills(overwrite).await?;
+    report.merge(state.migrate_commands_to_skills(overwrite).await?);
+    Ok(report)
+}
+
+/// Delete `.migrated` backups whose skills exist; returns how many were removed
+#[tauri::command]
+async fn cleanup_migration_backups(state: State<'_, SkillState>) -> Result<usize, String> {
+    state.cleanup_migration_backups().await
+}
+
 #[tauri::command]
 async fn save_skill(
     state: State<'_, SkillState>,
@@ -156,9 +517,53 @@ async fn save_skill(
     state.save(&skill).await
 }
 
+/// The skill's SKILL.md exactly as stored
+#[tauri::command]
+async fn get_skill_raw(state: State<'_, SkillState>, name: String) -> Result<String, String> {
+    state.get_raw(&name).await
+}
+
+/// Overwrite the skill's SKILL.md with `content` after checking it parses
+#[tauri::command]
+async fn save_skill_raw(state: State<'_, SkillState>, name: String, content: String) -> Result<(), String> {
+    state.save_raw(&name, &content).await
+}
+
+/// Pin skills to the top of the list in the given order
+#[tauri::command]
+async fn reorder_skills(state: State<'_, SkillState>, ordered_names: Vec<String>) -> Result<(), String> {
+    state.reorder(&ordered_names).await
+}
+
+/// Delete a skill; `source` defaults to the project's skills
+#[tauri::command]
+async fn delete_skill(
+    state: State<'_, SkillState>,
+    name: String,
+    source: Option<SkillSource>,
+) -> Result<(), String> {
+    state.delete(&name, source.unwrap_or_default()).await
+}
+
+/// Check positional argument values against the skill's declared `arguments`
+#[tauri::command]
+async fn validate_skill_args(
+    state: State<'_, SkillState>,
+    name: String,
+    args: Vec<String>,
+) -> Result<(), String> {
+    let skill = state.get(&name).await?;
+    skills::validate_skill_args(&skill, &args)
+}
+
+/// A skill's body with `$ARGUMENTS` replaced by `context`
 #[tauri::command]
-async fn delete_skill(state: State<'_, SkillState>, name: String) -> Result<(), String> {
-    state.delete(&name).await
+async fn resolve_skill(
+    state: State<'_, SkillState>,
+    name: String,
+    context: String,
+) -> Result<String, String> {
+    state.resolve_skill(&name, &context).await
 }
 
 #[tauri::command]
@@ -181,7 +586,7 @@ async fn execute_skill(
         content: message.clone(),
         timestamp: chrono::Utc::now().to_rfc3339(),
     };
-    let _ = app.emit("claude:message", &user_msg);
+    chat_history::emit_message(&app, &user_msg);
 
     claude_state.send_message(&app, message).await
 }
@@ -193,13 +598,20 @@ async fn todo_list(state: State<'_, TodoState>) -> Result<Vec<TodoItem>, String>
     Ok(state.list().await)
 }
 
+/// Add a todo. With `dedupe`, returns None instead of adding a duplicate of
+/// an incomplete item.
 #[tauri::command]
 async fn todo_add(
     state: State<'_, TodoState>,
     text: String,
     due_date: Option<String>,
-) -> Result<TodoItem, String> {
-    state.add(text, due_date).await
+    dedupe: Option<bool>,
+) -> Result<Option<TodoItem>, String> {
+    if dedupe.unwrap_or(false) {
+        state.add_unique(text, due_date).await
+    } else {
+        state.add(text, due_date).await.map(Some)
+    }
 }
 
 #[tauri::command]
@@ -215,6 +627,18 @@ async fn todo_remove(state: State<'_, TodoState>, id: String) -> Result<bool, St
     state.remove(&id).await
 }
 
+/// Add the checkbox items of a Markdown document as todos; returns how many were added
+#[tauri::command]
+async fn import_todos_markdown(state: State<'_, TodoState>, markdown: String) -> Result<usize, String> {
+    state.import_markdown(&markdown).await
+}
+
+/// Serialize the todo list ("markdown" | "csv" | "json") for the frontend to save
+#[tauri::command]
+async fn export_todos(state: State<'_, TodoState>, format: String) -> Result<String, String> {
+    state.export(&format).await
+}
+
 // ── Google Drive commands ──
 
 #[tauri::command]
@@ -233,12 +657,13 @@ async fn gdrive_is_authenticated(state: State<'_, GDriveState>) -> Result<bool,
 async fn gdrive_start_auth(
     app: AppHandle,
     state: State<'_, GDriveState>,
+    tasks: State<'_, TaskState>,
 ) -> Result<String, String> {
     let (url, port, rx) = state.start_auth_flow().await?;
 
     let gdrive = state.inner().clone();
     let app_clone = app.clone();
-    tokio::spawn(async move {
+    let flow = async move {
         let result = tokio::time::timeout(std::time::Duration::from_secs(300), rx).await;
         match result {
             Ok(Ok(Ok(code))) => match gdrive.exchange_code(&code, port).await {
@@ -259,11 +684,35 @@ async fn gdrive_start_auth(
                 let _ = app_clone.emit("gdrive:auth_error", "認証がタイムアウトしました（5分）");
             }
         }
+        gdrive.finish_auth_flow(port).await;
+    };
+    // Cancelling goes through the flow so the UI still gets `gdrive:auth_error`
+    let gdrive = state.inner().clone();
+    tasks.spawn_with_cancel("Google Drive認証", flow, move || {
+        tokio::spawn(async move {
+            gdrive.cancel_auth_flow().await;
+        });
     });
 
     Ok(url)
 }
 
+/// Abort a pending Google Drive OAuth flow. The waiting task emits
+/// `gdrive:auth_error` with the cancellation message right away.
+#[tauri::command]
+async fn gdrive_cancel_auth(state: State<'_, GDriveState>) -> Result<bool, String> {
+    Ok(state.cancel_auth_flow().await)
+}
+
+#[tauri::command]
+async fn set_gdrive_oauth_config(
+    state: State<'_, GDriveState>,
+    client_id: String,
+    client_secret: String,
+) -> Result<(), String> {
+    state.set_config(&client_id, &client_secret).await
+}
+
 #[tauri::command]
 async fn gdrive_logout(state: State<'_, GDriveState>) -> Result<(), String> {
     state.logout().await
@@ -282,8 +731,53 @@ async fn gdrive_download_file(
     state: State<'_, GDriveState>,
     file_id: String,
     dest: String,
+    overwrite: Option<bool>,
+    resume: Option<bool>,
+) -> Result<String, String> {
+    state
+        .download_file(&file_id, &dest, overwrite.unwrap_or(false), resume.unwrap_or(false))
+        .await
+}
+
+/// Abort an in-progress `gdrive_download_file`; false if none was running
+#[tauri::command]
+async fn gdrive_cancel_download(state: State<'_, GDriveState>, file_id: String) -> Result<bool, String> {
+    Ok(state.cancel_download(&file_id))
+}
+
+/// Default number of bytes shown by `gdrive_peek_file`
+const DRIVE_PEEK_DEFAULT_BYTES: usize = 16 * 1024;
+
+/// Preview the start of a Drive file without downloading all of it.
+#[tauri::command]
+async fn gdrive_peek_file(
+    state: State<'_, GDriveState>,
+    file_id: String,
+    max_bytes: Option<usize>,
+) -> Result<FilePreview, String> {
+    let head = state
+        .peek_file(&file_id, max_bytes.unwrap_or(DRIVE_PEEK_DEFAULT_BYTES))
+        .await?;
+    Ok(FilePreview::from_bytes(&head))
+}
+
+/// Upload a local folder (recursively) into a Drive folder.
+#[tauri::command]
+async fn gdrive_upload_directory(
+    state: State<'_, GDriveState>,
+    local_dir: String,
+    parent_id: Option<String>,
+) -> Result<Vec<DriveFile>, String> {
+    state.upload_directory(&local_dir, parent_id.as_deref()).await
+}
+
+/// Get a browser URL for a Drive file; the frontend opens it with the shell plugin.
+#[tauri::command]
+async fn gdrive_get_file_link(
+    state: State<'_, GDriveState>,
+    file_id: String,
 ) -> Result<String, String> {
-    state.download_file(&file_id, &dest).await
+    state.get_file_link(&file_id).await
 }
 
 // ── Slack commands ──
@@ -321,12 +815,13 @@ async fn slack_save_settings(
 async fn slack_start_auth(
     app: AppHandle,
     state: State<'_, SlackState>,
+    tasks: State<'_, TaskState>,
 ) -> Result<String, String> {
     let (url, port, rx) = state.start_auth_flow().await?;
 
     let slack = state.inner().clone();
     let app_clone = app.clone();
-    tokio::spawn(async move {
+    let flow = async move {
         let result = tokio::time::timeout(std::time::Duration::from_secs(300), rx).await;
         match result {
             Ok(Ok(Ok(code))) => match slack.exchange_code(&code, port).await {
@@ -347,14 +842,61 @@ async fn slack_start_auth(
                 let _ = app_clone.emit("slack:auth_error", "認証がタイムアウトしました（5分）");
             }
         }
+        slack.finish_auth_flow(port).await;
+    };
+    let slack = state.inner().clone();
+    tasks.spawn_with_cancel("Slack認証", flow, move || {
+        tokio::spawn(async move {
+            slack.cancel_auth_flow().await;
+        });
     });
 
     Ok(url)
 }
 
+/// Abort a pending Slack OAuth flow. The waiting task emits
+/// `slack:auth_error` with the cancellation message right away.
 #[tauri::command]
-async fn slack_logout(state: State<'_, SlackState>) -> Result<(), String> {
-    state.logout().await
+async fn slack_cancel_auth(state: State<'_, SlackState>) -> Result<bool, String> {
+    Ok(state.cancel_auth_flow().await)
+}
+
+#[tauri::command]
+async fn set_slack_oauth_config(
+    state: State<'_, SlackState>,
+    client_id: String,
+    client_secret: String,
+) -> Result<(), String> {
+    state.set_oauth_config(&client_id, &client_secret).await
+}
+
+/// Log out of `team_id`, or of the active workspace when omitted
+#[tauri::command]
+async fn slack_logout(state: State<'_, SlackState>, team_id: Option<String>) -> Result<(), String> {
+    match team_id {
+        Some(team_id) => state.logout_workspace(&team_id).await,
+        None => state.logout().await,
+    }
+}
+
+/// A single List item with every field, including custom columns
+#[tauri::command]
+async fn slack_get_item(
+    state: State<'_, SlackState>,
+    list_id: String,
+    item_id: String,
+) -> Result<serde_json::Value, String> {
+    state.get_item(&list_id, &item_id).await
+}
+
+#[tauri::command]
+async fn slack_list_workspaces(state: State<'_, SlackState>) -> Result<Vec<SlackWorkspace>, String> {
+    Ok(state.list_workspaces().await)
+}
+
+#[tauri::command]
+async fn slack_set_active_workspace(state: State<'_, SlackState>, team_id: String) -> Result<(), String> {
+    state.set_active_workspace(&team_id).await
 }
 
 #[tauri::command]
@@ -374,6 +916,55 @@ async fn slack_create_item(
     state.create_item(&list_id, &title).await
 }
 
+#[tauri::command]
+async fn slack_create_items(
+    state: State<'_, SlackState>,
+    list_id: String,
+    titles: Vec<String>,
+) -> Result<SlackBatchResult, String> {
+    state.create_items(&list_id, titles).await
+}
+
+/// Push every local todo into a Slack List as a new item
+#[tauri::command]
+async fn export_todos_to_slack(
+    todo_state: State<'_, TodoState>,
+    slack_state: State<'_, SlackState>,
+    list_id: String,
+) -> Result<SlackBatchResult, String> {
+    let titles = todo_state.list().await.into_iter().map(|t| t.text).collect();
+    slack_state.create_items(&list_id, titles).await
+}
+
+/// (text, due_date) entries for the list items that are still open
+fn open_slack_items_as_todos(items: Vec<SlackListItem>) -> Vec<(String, Option<String>)> {
+    items
+        .into_iter()
+        .filter(|item| !item.completed)
+        .map(|item| (item.title, item.due_date))
+        .collect()
+}
+
+/// Add the incomplete items of a Slack List as local todos; returns how many were added
+#[tauri::command]
+async fn import_slack_list_as_todos(
+    slack_state: State<'_, SlackState>,
+    todo_state: State<'_, TodoState>,
+    list_id: String,
+) -> Result<usize, String> {
+    let items = slack_state.list_items(&list_id).await?;
+    todo_state.import_items(open_slack_items_as_todos(items)).await
+}
+
+#[tauri::command]
+async fn slack_get_messages(
+    state: State<'_, SlackState>,
+    channel: String,
+    limit: Option<usize>,
+) -> Result<Vec<SlackMessage>, String> {
+    state.conversations_history(&channel, limit.unwrap_or(50)).await
+}
+
 // ── Working directory persistence ──
 
 #[tauri::command]
@@ -401,10 +992,34 @@ async fn save_last_working_dir(app: &AppHandle, dir: &str) -> Result<(), String>
 
 // ── Chat history commands ──
 
-#[tauri::command]
-async fn chat_load_messages(app: AppHandle) -> Result<Vec<ChatMessage>, String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let path = data_dir.join("chat_messages.json");
+/// History file used before histories were kept per working directory
+const LEGACY_CHAT_FILE: &str = "chat_messages.json";
+
+/// FNV-1a, so file names stay the same across Rust versions
+fn stable_hash(s: &str) -> u64 {
+    s.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, b| {
+        (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Chat history file for a working directory (the legacy global file when none is set)
+fn chat_history_path(data_dir: &Path, working_dir: &str) -> PathBuf {
+    if working_dir.is_empty() {
+        data_dir.join(LEGACY_CHAT_FILE)
+    } else {
+        data_dir.join(format!("chat_messages_{:016x}.json", stable_hash(working_dir)))
+    }
+}
+
+async fn load_chat_history(data_dir: &Path, working_dir: &str) -> Result<Vec<ChatMessage>, String> {
+    let path = chat_history_path(data_dir, working_dir);
+    let legacy = data_dir.join(LEGACY_CHAT_FILE);
+    // First load for a workspace adopts the old global history
+    if !path.exists() && path != legacy && legacy.exists() {
+        if let Err(e) = tokio::fs::rename(&legacy, &path).await {
+            log::warn!("Failed to migrate chat history: {}", e);
+        }
+    }
     if !path.exists() {
         return Ok(Vec::new());
     }
@@ -415,24 +1030,23 @@ async fn chat_load_messages(app: AppHandle) -> Result<Vec<ChatMessage>, String>
         .map_err(|e| format!("チャット履歴の解析に失敗: {}", e))
 }
 
-#[tauri::command]
-async fn chat_save_messages(app: AppHandle, messages: Vec<ChatMessage>) -> Result<(), String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    tokio::fs::create_dir_all(&data_dir)
+async fn save_chat_history(
+    data_dir: &Path,
+    working_dir: &str,
+    messages: &[ChatMessage],
+) -> Result<(), String> {
+    tokio::fs::create_dir_all(data_dir)
         .await
         .map_err(|e| format!("ディレクトリ作成に失敗: {}", e))?;
-    let path = data_dir.join("chat_messages.json");
-    let content = serde_json::to_string(&messages)
+    let content = serde_json::to_string(messages)
         .map_err(|e| format!("チャット履歴のシリアライズに失敗: {}", e))?;
-    tokio::fs::write(&path, content)
+    tokio::fs::write(chat_history_path(data_dir, working_dir), content)
         .await
         .map_err(|e| format!("チャット履歴の保存に失敗: {}", e))
 }
 
-#[tauri::command]
-async fn chat_clear_messages(app: AppHandle) -> Result<(), String> {
-    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
-    let path = data_dir.join("chat_messages.json");
+async fn clear_chat_history(data_dir: &Path, working_dir: &str) -> Result<(), String> {
+    let path = chat_history_path(data_dir, working_dir);
     if path.exists() {
         tokio::fs::remove_file(&path)
             .await
@@ -441,12 +1055,339 @@ async fn chat_clear_messages(app: AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Load the chat history of the current working directory
+#[tauri::command]
+async fn chat_load_messages(
+    app: AppHandle,
+    state: State<'_, ClaudeState>,
+    history: State<'_, ChatHistoryState>,
+) -> Result<Vec<ChatMessage>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let working_dir = state.get_working_dir().await;
+    let messages = load_chat_history(&data_dir, &working_dir).await?;
+    history.reset(chat_history_path(&data_dir, &working_dir), messages.clone());
+    Ok(messages)
+}
+
+#[tauri::command]
+async fn chat_save_messages(
+    app: AppHandle,
+    state: State<'_, ClaudeState>,
+    history: State<'_, ChatHistoryState>,
+    messages: Vec<ChatMessage>,
+    working_dir: Option<String>,
+) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let current = state.get_working_dir().await;
+    let working_dir = working_dir.unwrap_or_else(|| current.clone());
+    save_chat_history(&data_dir, &working_dir, &messages).await?;
+    // A save that arrives after a folder switch belongs to the old folder and
+    // must not become the new folder's live history
+    if working_dir == current {
+        history.reset(chat_history_path(&data_dir, &working_dir), messages);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+async fn chat_clear_messages(
+    app: AppHandle,
+    state: State<'_, ClaudeState>,
+    history: State<'_, ChatHistoryState>,
+) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let working_dir = state.get_working_dir().await;
+    clear_chat_history(&data_dir, &working_dir).await?;
+    history.reset(chat_history_path(&data_dir, &working_dir), Vec::new());
+    Ok(())
+}
+
+#[tauri::command]
+async fn get_claude_settings(state: State<'_, ClaudeState>) -> Result<ClaudeSettings, String> {
+    Ok(state.get_settings().await)
+}
+
+#[tauri::command]
+async fn save_claude_settings(
+    state: State<'_, ClaudeState>,
+    settings: ClaudeSettings,
+) -> Result<(), String> {
+    state.save_settings(settings).await
+}
+
+#[tauri::command]
+async fn get_session_cost(state: State<'_, ClaudeState>) -> Result<f64, String> {
+    Ok(state.get_session_cost().await)
+}
+
+/// Tool use counts for the current session (e.g. {"Read": 12, "Bash": 3})
+#[tauri::command]
+async fn get_tool_stats(state: State<'_, ClaudeState>) -> Result<HashMap<String, u32>, String> {
+    Ok(state.get_tool_stats().await)
+}
+
+#[tauri::command]
+async fn get_repo_approval_rules(
+    state: State<'_, ClaudeState>,
+) -> Result<approval_server::RepoApprovalRules, String> {
+    Ok(state.get_repo_rules().await)
+}
+
+#[tauri::command]
+async fn accept_repo_approval_rules(
+    state: State<'_, ClaudeState>,
+) -> Result<approval_server::RepoApprovalRules, String> {
+    state.accept_repo_rules().await
+}
+
 #[tauri::command]
 async fn reset_session(state: State<'_, ClaudeState>) -> Result<(), String> {
     state.reset_session().await;
     Ok(())
 }
 
+// ── Settings backup commands ──
+
+/// Data-dir files included in a settings backup
+const BACKUP_FILES: &[&str] = &[
+    "slack_settings.json",
+    "network_settings.json",
+    "todos.json",
+    "last_working_dir.txt",
+    "claude_settings.json",
+    "skill_order.json",
+];
+/// Token and OAuth credential files, only included when explicitly requested
+const BACKUP_SECRET_FILES: &[&str] = &[
+    "slack_tokens.json",
+    "gdrive_tokens.json",
+    "gdrive_config.json",
+    "slack_oauth_config.json",
+];
+
+/// A portable snapshot of the app's settings files (file name -> content)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SettingsBundle {
+    version: u32,
+    exported_at: String,
+    files: HashMap<String, String>,
+}
+
+async fn build_settings_bundle(
+    data_dir: &Path,
+    include_secrets: bool,
+) -> Result<SettingsBundle, String> {
+    let mut names: Vec<&str> = BACKUP_FILES.to_vec();
+    if include_secrets {
+        names.extend_from_slice(BACKUP_SECRET_FILES);
+    }
+
+    let mut files = HashMap::new();
+    for name in names {
+        let path = data_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        let content = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| format!("{}の読み込みに失敗: {}", name, e))?;
+        files.insert(name.to_string(), content);
+    }
+
+    Ok(SettingsBundle {
+        version: 1,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        files,
+    })
+}
+
+/// Write the bundled files back into the data dir. Unknown file names are ignored.
+async fn restore_settings_bundle(data_dir: &Path, bundle: &SettingsBundle) -> Result<usize, String> {
+    tokio::fs::create_dir_all(data_dir)
+        .await
+        .map_err(|e| format!("ディレクトリ作成に失敗: {}", e))?;
+
+    let mut restored = 0;
+    for (name, content) in &bundle.files {
+        let known = BACKUP_FILES.contains(&name.as_str())
+            || BACKUP_SECRET_FILES.contains(&name.as_str());
+        if !known {
+            log::warn!("Skipping unknown file in settings bundle: {}", name);
+            continue;
+        }
+        tokio::fs::write(data_dir.join(name), content)
+            .await
+            .map_err(|e| format!("{}の書き込みに失敗: {}", name, e))?;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+#[tauri::command]
+async fn export_settings(
+    app: AppHandle,
+    dest: String,
+    include_secrets: Option<bool>,
+) -> Result<(), String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let bundle = build_settings_bundle(&data_dir, include_secrets.unwrap_or(false)).await?;
+    let content = serde_json::to_string_pretty(&bundle)
+        .map_err(|e| format!("設定のシリアライズに失敗: {}", e))?;
+    tokio::fs::write(&dest, content)
+        .await
+        .map_err(|e| format!("設定のエクスポートに失敗: {}", e))
+}
+
+#[tauri::command]
+async fn import_settings(
+    app: AppHandle,
+    claude_state: State<'_, ClaudeState>,
+    todo_state: State<'_, TodoState>,
+    gdrive_state: State<'_, GDriveState>,
+    slack_state: State<'_, SlackState>,
+    src: String,
+) -> Result<usize, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let content = tokio::fs::read_to_string(&src)
+        .await
+        .map_err(|e| format!("設定ファイルの読み込みに失敗: {}", e))?;
+    let bundle: SettingsBundle = serde_json::from_str(&content)
+        .map_err(|e| format!("設定ファイルの解析に失敗: {}", e))?;
+    let restored = restore_settings_bundle(&data_dir, &bundle).await?;
+
+    // Reload in-memory state from the restored files (the skill order is
+    // read from disk on every listing)
+    claude_state.reload_settings().await;
+    todo_state.load().await?;
+    gdrive_state.load().await?;
+    slack_state.load().await?;
+    if let Ok(client) = http_client::build_http_client(&NetworkSettings::load(&data_dir)) {
+        gdrive_state.set_http_client(client.clone());
+        slack_state.set_http_client(client);
+    }
+
+    Ok(restored)
+}
+
+// ── Network settings ──
+
+#[tauri::command]
+async fn get_network_settings(app: AppHandle) -> Result<NetworkSettings, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    Ok(NetworkSettings::load(&data_dir))
+}
+
+/// Save proxy settings and apply them to the Drive and Slack clients immediately
+#[tauri::command]
+async fn save_network_settings(
+    app: AppHandle,
+    gdrive_state: State<'_, GDriveState>,
+    slack_state: State<'_, SlackState>,
+    settings: NetworkSettings,
+) -> Result<(), String> {
+    // Validate by building the client before anything is written
+    let client = http_client::build_http_client(&settings)?;
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    settings.save(&data_dir)?;
+    gdrive_state.set_http_client(client.clone());
+    slack_state.set_http_client(client);
+    Ok(())
+}
+
+// ── Diagnostics ──
+
+/// Where Cowork keeps its files, for support and debugging
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AppPaths {
+    data_dir: String,
+    resource_dir: Option<String>,
+    hook_path: String,
+    claude_settings_path: Option<String>,
+}
+
+fn build_app_paths(data_dir: &Path, resource_dir: Option<&Path>) -> AppPaths {
+    let display = |p: &Path| p.to_string_lossy().to_string();
+    AppPaths {
+        data_dir: display(data_dir),
+        resource_dir: resource_dir.map(display),
+        hook_path: display(&claude::hook_script_path(data_dir)),
+        claude_settings_path: claude::claude_settings_path().map(|p| display(&p)),
+    }
+}
+
+/// Read-only view of ~/.claude/settings.json, for diagnosing hook installation
+#[tauri::command]
+async fn read_claude_settings() -> Result<String, String> {
+    let path = claude::claude_settings_path().ok_or("ホームディレクトリが見つかりません")?;
+    claude::read_claude_settings_file(&path)
+}
+
+/// Check that the approval hook is registered and points at this install
+#[tauri::command]
+async fn verify_hook(app: AppHandle) -> Result<HookStatus, String> {
+    ClaudeManager::verify_hook(&app)
+}
+
+/// Reinstall the approval hook script and settings.json entry
+#[tauri::command]
+async fn repair_hook(app: AppHandle) -> Result<HookStatus, String> {
+    ClaudeManager::ensure_hook_installed(&app)?;
+    ClaudeManager::verify_hook(&app)
+}
+
+#[tauri::command]
+async fn get_claude_settings_path() -> Result<String, String> {
+    claude::claude_settings_path()
+        .map(|p| p.to_string_lossy().to_string())
+        .ok_or_else(|| "ホームディレクトリが見つかりません".to_string())
+}
+
+#[tauri::command]
+async fn get_paths(app: AppHandle) -> Result<AppPaths, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let resource_dir = app.path().resource_dir().ok();
+    Ok(build_app_paths(&data_dir, resource_dir.as_deref()))
+}
+
+// ── Logs ──
+
+/// Log file name without the `.log` extension the log plugin appends
+const LOG_FILE_STEM: &str = "cowork";
+/// The log is rotated (keeping one old file) once it reaches this size
+const LOG_MAX_FILE_SIZE: u128 = 5 * 1024 * 1024;
+
+fn log_dir(data_dir: &Path) -> PathBuf {
+    data_dir.join("logs")
+}
+
+/// Last `lines` lines of the current log file (empty if nothing was logged yet)
+async fn read_log_tail(log_dir: &Path, lines: usize) -> Result<String, String> {
+    let path = log_dir.join(format!("{}.log", LOG_FILE_STEM));
+    if !path.exists() {
+        return Ok(String::new());
+    }
+    files::read_file_tail(&path.to_string_lossy(), lines).await
+}
+
+/// Recent log lines for an in-app "copy diagnostics" button
+#[tauri::command]
+async fn get_recent_logs(app: AppHandle, lines: usize) -> Result<String, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    read_log_tail(&log_dir(&data_dir), lines).await
+}
+
+// ── Background tasks ──
+
+#[tauri::command]
+async fn list_tasks(tasks: State<'_, TaskState>) -> Result<Vec<TaskInfo>, String> {
+    Ok(tasks.list())
+}
+
+#[tauri::command]
+async fn cancel_task(tasks: State<'_, TaskState>, id: String) -> Result<bool, String> {
+    Ok(tasks.cancel(&id))
+}
+
 // ── App setup ──
 
 fn get_app_data_dir(app: &tauri::App) -> PathBuf {
@@ -469,28 +1410,62 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .manage(claude_manager)
         .manage(approval_pending)
+        .manage(SearchCancelState::default())
+        .manage(TailFollowState::default())
+        .manage(SkillsWatchState::default())
+        .manage(TaskState::default())
+        .manage(ChatHistoryState::new(ChatHistory::new(chat_history::AUTOSAVE_IDLE)))
         .invoke_handler(tauri::generate_handler![
             send_message,
             cancel_message,
+            cancel_and_discard,
             set_working_directory,
             get_working_directory,
+            check_working_dir_writable,
+            resolve_working_path,
             list_files,
             get_file_tree,
+            search_in_directory_streaming,
+            cancel_search,
+            get_disk_space,
+            read_file_tail,
+            tail_follow,
+            tail_stop,
+            file_stats,
+            hash_file,
+            diff_files,
+            detect_project_type,
             list_skills,
+            list_skills_with_status,
+            migrate_skills_now,
+            cleanup_migration_backups,
             save_skill,
+            get_skill_raw,
+            save_skill_raw,
             delete_skill,
+            reorder_skills,
             execute_skill,
+            resolve_skill,
+            validate_skill_args,
             todo_list,
             todo_add,
             todo_toggle,
             todo_remove,
+            export_todos,
+            import_todos_markdown,
             // Google Drive
             gdrive_is_configured,
             gdrive_is_authenticated,
             gdrive_start_auth,
+            gdrive_cancel_auth,
+            set_gdrive_oauth_config,
             gdrive_logout,
             gdrive_list_files,
             gdrive_download_file,
+            gdrive_cancel_download,
+            gdrive_upload_directory,
+            gdrive_peek_file,
+            gdrive_get_file_link,
             // Slack
             slack_is_configured,
             slack_is_authenticated,
@@ -498,27 +1473,65 @@ pub fn run() {
             slack_get_settings,
             slack_save_settings,
             slack_start_auth,
+            slack_cancel_auth,
+            set_slack_oauth_config,
             slack_logout,
+            slack_list_workspaces,
+            slack_set_active_workspace,
             slack_list_items,
+            slack_get_item,
             slack_create_item,
+            slack_create_items,
+            export_todos_to_slack,
+            import_slack_list_as_todos,
+            slack_get_messages,
             // Other
             respond_to_approval,
+            list_pending_approvals,
             get_last_working_dir,
             chat_load_messages,
             chat_save_messages,
             chat_clear_messages,
             reset_session,
+            get_session_cost,
+            get_tool_stats,
+            get_repo_approval_rules,
+            accept_repo_approval_rules,
+            get_claude_settings,
+            save_claude_settings,
+            export_settings,
+            import_settings,
+            snapshot_claude_config,
+            restore_claude_config,
+            get_network_settings,
+            save_network_settings,
+            get_paths,
+            read_claude_settings,
+            verify_hook,
+            repair_hook,
+            get_claude_settings_path,
+            get_recent_logs,
+            list_tasks,
+            cancel_task,
         ])
         .setup(|app| {
+            let data_dir = get_app_data_dir(app);
+
+            // File log in every build so users can attach it to bug reports;
+            // console output only while developing
+            let mut log_builder = tauri_plugin_log::Builder::default()
+                .clear_targets()
+                .target(Target::new(TargetKind::Folder {
+                    path: log_dir(&data_dir),
+                    file_name: Some(LOG_FILE_STEM.to_string()),
+                }))
+                .max_file_size(LOG_MAX_FILE_SIZE)
+                .rotation_strategy(RotationStrategy::KeepOne)
+                .level(log::LevelFilter::Info);
             if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
+                log_builder = log_builder.target(Target::new(TargetKind::Stdout));
             }
-
-            let data_dir = get_app_data_dir(app);
+            app.handle().plugin(log_builder.build())?;
             let resource_dir = get_resource_dir(app);
 
             // Install hook for Claude Code approval flow
@@ -526,14 +1539,29 @@ pub fn run() {
                 log::warn!("Hook installation failed: {}", e);
             }
 
+            // Check that the Claude Code CLI is installed so the UI can show onboarding
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = ClaudeManager::check_cli_available().await {
+                    log::warn!("Claude CLI unavailable: {}", e);
+                    let _ = app_handle.emit("claude:unavailable", &e);
+                }
+            });
+
             // Restore saved session ID so conversations persist across restarts
             let claude: Arc<ClaudeManager> = app.state::<ClaudeState>().inner().clone();
             let data_dir_for_session = data_dir.clone();
             tauri::async_runtime::block_on(claude.set_data_dir(data_dir_for_session));
 
             // Initialize skill store
-            let skill_store = Arc::new(SkillStore::new(data_dir.clone()));
+            let mut skill_store = SkillStore::new(data_dir.clone());
+            if let Some(home) = claude::home_dir() {
+                skill_store = skill_store.with_user_dir(home.join(".claude").join("skills"));
+            }
+            let skill_store = Arc::new(skill_store);
             app.manage(skill_store);
+            // User-level skills are watched even before a folder is chosen
+            tauri::async_runtime::block_on(restart_skills_watch(app.handle(), ""));
 
             // Initialize todo manager
             let todo_manager = Arc::new(TodoManager::new(data_dir.clone()));
@@ -543,9 +1571,21 @@ pub fn run() {
             });
             app.manage(todo_manager);
 
+            // Shared HTTP client honoring the proxy settings
+            let http = http_client::build_http_client(&NetworkSettings::load(&data_dir))
+                .unwrap_or_else(|e| {
+                    log::warn!("Invalid network settings, using defaults: {}", e);
+                    reqwest::Client::new()
+                });
+
             // Initialize Google Drive client
             let gdrive_client =
                 Arc::new(GDriveClient::new(data_dir.clone(), resource_dir.clone()));
+            gdrive_client.set_http_client(http.clone());
+            let app_handle = app.handle().clone();
+            gdrive_client.set_auth_listener(Box::new(move |authenticated| {
+                let _ = app_handle.emit("gdrive:auth_state_changed", authenticated);
+            }));
             let gdrive_ref = gdrive_client.clone();
             tauri::async_runtime::spawn(async move {
                 let _ = gdrive_ref.load().await;
@@ -554,6 +1594,11 @@ pub fn run() {
 
             // Initialize Slack client
             let slack_client = Arc::new(SlackClient::new(data_dir, resource_dir));
+            slack_client.set_http_client(http);
+            let app_handle = app.handle().clone();
+            slack_client.set_auth_listener(Box::new(move |authenticated| {
+                let _ = app_handle.emit("slack:auth_state_changed", authenticated);
+            }));
             let slack_ref = slack_client.clone();
             tauri::async_runtime::spawn(async move {
                 let _ = slack_ref.load().await;
@@ -565,3 +1610,254 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        std::env::temp_dir().join(format!("cowork-lib-test-{}", uuid::Uuid::new_v4()))
+    }
+
+    #[test]
+    fn test_skills_watch_dirs_include_user_dir() {
+        let user = PathBuf::from("/home/me/.claude/skills");
+        assert_eq!(
+            skills_watch_dirs("/work", Some(&user)),
+            vec![Path::new("/work").join(".claude").join("skills"), user.clone()]
+        );
+        assert_eq!(skills_watch_dirs("", Some(&user)), vec![user]);
+        assert!(skills_watch_dirs("", None).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_apply_working_dir_notifies_with_normalized_path() {
+        let root = temp_dir();
+        std::fs::create_dir_all(root.join("work")).unwrap();
+        let claude = ClaudeManager::new(Arc::new(Mutex::new(HashMap::new())));
+        let skills = SkillStore::new(root.join("data"));
+
+        // A non-canonical spelling of the same folder
+        let raw = root.join("work").join(".").to_string_lossy().to_string();
+        let path = paths::normalize_working_dir(&raw).unwrap();
+        let mut events = Vec::new();
+        apply_working_dir(&claude, &skills, &path, |e| events.push(e.clone())).await;
+
+        assert_eq!(
+            events,
+            vec![WorkspaceChanged {
+                path: path.clone(),
+                migrated: false,
+            }]
+        );
+        assert!(!path.ends_with('.'));
+        assert_eq!(claude.get_working_dir().await, path);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_settings_bundle_roundtrip_excludes_tokens() {
+        let src = temp_dir();
+        tokio::fs::create_dir_all(&src).await.unwrap();
+        tokio::fs::write(src.join("slack_settings.json"), r#"{"default_list_id":"L1"}"#)
+            .await
+            .unwrap();
+        tokio::fs::write(src.join("todos.json"), r#"{"items":[]}"#).await.unwrap();
+        tokio::fs::write(src.join("last_working_dir.txt"), "/tmp/work").await.unwrap();
+        tokio::fs::write(src.join("claude_settings.json"), r#"{"run_timeout_mins":0}"#)
+            .await
+            .unwrap();
+        tokio::fs::write(src.join("skill_order.json"), r#"["report"]"#).await.unwrap();
+        tokio::fs::write(src.join("slack_tokens.json"), r#"{"bot_token":"xoxb-secret"}"#)
+            .await
+            .unwrap();
+
+        let bundle = build_settings_bundle(&src, false).await.unwrap();
+        assert_eq!(bundle.files.len(), 5);
+        assert!(bundle.files.contains_key("claude_settings.json"));
+        assert!(bundle.files.contains_key("skill_order.json"));
+        assert!(!bundle.files.contains_key("slack_tokens.json"));
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert!(!json.contains("xoxb-secret"));
+
+        let dest = temp_dir();
+        let parsed: SettingsBundle = serde_json::from_str(&json).unwrap();
+        let restored = restore_settings_bundle(&dest, &parsed).await.unwrap();
+        assert_eq!(restored, 5);
+        assert_eq!(
+            tokio::fs::read_to_string(dest.join("last_working_dir.txt")).await.unwrap(),
+            "/tmp/work"
+        );
+        assert!(!dest.join("slack_tokens.json").exists());
+
+        let _ = tokio::fs::remove_dir_all(&src).await;
+        let _ = tokio::fs::remove_dir_all(&dest).await;
+    }
+
+    #[tokio::test]
+    async fn test_settings_bundle_includes_tokens_on_opt_in() {
+        let src = temp_dir();
+        tokio::fs::create_dir_all(&src).await.unwrap();
+        tokio::fs::write(src.join("gdrive_tokens.json"), r#"{"access_token":"ya29"}"#)
+            .await
+            .unwrap();
+
+        tokio::fs::write(src.join("gdrive_config.json"), r#"{"client_id":"id","client_secret":"s"}"#)
+            .await
+            .unwrap();
+        tokio::fs::write(src.join("slack_oauth_config.json"), r#"{"client_id":"id","client_secret":"s"}"#)
+            .await
+            .unwrap();
+
+        let bundle = build_settings_bundle(&src, false).await.unwrap();
+        assert!(bundle.files.is_empty());
+
+        let bundle = build_settings_bundle(&src, true).await.unwrap();
+        assert!(bundle.files.contains_key("gdrive_tokens.json"));
+        assert!(bundle.files.contains_key("gdrive_config.json"));
+        assert!(bundle.files.contains_key("slack_oauth_config.json"));
+
+        let _ = tokio::fs::remove_dir_all(&src).await;
+    }
+
+    #[tokio::test]
+    async fn test_restore_settings_bundle_ignores_unknown_files() {
+        let dest = temp_dir();
+        let mut files = HashMap::new();
+        files.insert("../evil.txt".to_string(), "x".to_string());
+        let bundle = SettingsBundle {
+            version: 1,
+            exported_at: String::new(),
+            files,
+        };
+        let restored = restore_settings_bundle(&dest, &bundle).await.unwrap();
+        assert_eq!(restored, 0);
+
+        let _ = tokio::fs::remove_dir_all(&dest).await;
+    }
+
+    fn chat_message(content: &str) -> ChatMessage {
+        ChatMessage {
+            id: uuid::Uuid::new_v4().to_string(),
+            role: "user".to_string(),
+            content: content.to_string(),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_history_per_working_dir() {
+        let data_dir = temp_dir();
+        save_chat_history(&data_dir, "/work/a", &[chat_message("Aの会話")]).await.unwrap();
+        save_chat_history(&data_dir, "/work/b", &[chat_message("Bの会話"), chat_message("続き")])
+            .await
+            .unwrap();
+
+        let a = load_chat_history(&data_dir, "/work/a").await.unwrap();
+        let b = load_chat_history(&data_dir, "/work/b").await.unwrap();
+        assert_eq!(a.len(), 1);
+        assert_eq!(a[0].content, "Aの会話");
+        assert_eq!(b.len(), 2);
+
+        clear_chat_history(&data_dir, "/work/a").await.unwrap();
+        assert!(load_chat_history(&data_dir, "/work/a").await.unwrap().is_empty());
+        assert_eq!(load_chat_history(&data_dir, "/work/b").await.unwrap().len(), 2);
+
+        let _ = tokio::fs::remove_dir_all(&data_dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_legacy_chat_history_moves_to_first_workspace() {
+        let data_dir = temp_dir();
+        save_chat_history(&data_dir, "", &[chat_message("以前の会話")]).await.unwrap();
+        assert!(data_dir.join(LEGACY_CHAT_FILE).exists());
+
+        let adopted = load_chat_history(&data_dir, "/work/a").await.unwrap();
+        assert_eq!(adopted[0].content, "以前の会話");
+        assert!(!data_dir.join(LEGACY_CHAT_FILE).exists());
+        assert!(load_chat_history(&data_dir, "/work/b").await.unwrap().is_empty());
+
+        let _ = tokio::fs::remove_dir_all(&data_dir).await;
+    }
+
+    #[test]
+    fn test_chat_history_path_is_stable() {
+        let data_dir = Path::new("/data");
+        assert_eq!(chat_history_path(data_dir, ""), data_dir.join(LEGACY_CHAT_FILE));
+        assert_eq!(
+            chat_history_path(data_dir, "/work/a"),
+            chat_history_path(data_dir, "/work/a")
+        );
+        assert_ne!(
+            chat_history_path(data_dir, "/work/a"),
+            chat_history_path(data_dir, "/work/b")
+        );
+        // FNV-1a test vector
+        assert_eq!(stable_hash("a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[tokio::test]
+    async fn test_read_log_tail() {
+        let dir = temp_dir();
+        assert_eq!(read_log_tail(&dir, 10).await.unwrap(), "");
+
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        let content: String = (1..=100)
+            .map(|i| format!("[2026-01-01][INFO][cowork_lib] event {}\n", i))
+            .collect();
+        tokio::fs::write(dir.join("cowork.log"), content).await.unwrap();
+
+        let tail = read_log_tail(&dir, 2).await.unwrap();
+        assert_eq!(
+            tail,
+            "[2026-01-01][INFO][cowork_lib] event 99\n[2026-01-01][INFO][cowork_lib] event 100\n"
+        );
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn test_app_paths_serializes_all_keys() {
+        let data_dir = temp_dir();
+        let paths = build_app_paths(&data_dir, None);
+        assert!(paths.hook_path.ends_with("cowork-hook.cjs"));
+
+        let value = serde_json::to_value(&paths).unwrap();
+        let obj = value.as_object().unwrap();
+        for key in ["data_dir", "resource_dir", "hook_path", "claude_settings_path"] {
+            assert!(obj.contains_key(key), "missing key: {}", key);
+        }
+        assert!(obj["resource_dir"].is_null());
+    }
+
+    #[tokio::test]
+    async fn test_import_slack_items_only_open_and_new() {
+        let item = |title: &str, completed: bool, due: Option<&str>| SlackListItem {
+            id: format!("id-{}", title),
+            title: title.to_string(),
+            completed,
+            assignee: None,
+            due_date: due.map(|d| d.to_string()),
+            fields: serde_json::Value::Null,
+        };
+        let items = vec![
+            item("見積書を送る", false, Some("2026-05-01")),
+            item("完了済み", true, None),
+            item("既存のタスク", false, None),
+        ];
+
+        let dir = temp_dir();
+        let todos = TodoManager::new(dir.clone());
+        todos.add("既存のタスク".to_string(), None).await.unwrap();
+
+        let added = todos.import_items(open_slack_items_as_todos(items)).await.unwrap();
+        assert_eq!(added, 1);
+        let list = todos.list().await;
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].text, "見積書を送る");
+        assert_eq!(list[1].due_date.as_deref(), Some("2026-05-01"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -13,6 +13,9 @@ struct CallbackParams {
     error: Option<String>,
 }
 
+/// Error sent to the receiver when the user cancels an auth flow
+pub const CANCELLED_MESSAGE: &str = "認証がキャンセルされました";
+
 struct CallbackState {
     tx: Mutex<Option<oneshot::Sender<Result<String, String>>>>,
 }
@@ -59,10 +62,38 @@ async fn handle_callback(
     }
 }
 
+/// Handle for aborting an in-flight OAuth flow.
+/// Dropping it shuts the callback server down.
+pub struct OAuthCancelHandle {
+    port: u16,
+    state: Arc<CallbackState>,
+    _shutdown: oneshot::Sender<()>,
+}
+
+impl OAuthCancelHandle {
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Resolve the pending receiver with a cancellation error and stop the server
+    pub async fn cancel(self) {
+        if let Some(tx) = self.state.tx.lock().await.take() {
+            let _ = tx.send(Err(CANCELLED_MESSAGE.to_string()));
+        }
+    }
+}
+
 /// Start a temporary local HTTP server to receive an OAuth callback.
-/// Returns (port, receiver that yields the auth code or an error).
-pub async fn wait_for_oauth_callback(
-) -> Result<(u16, oneshot::Receiver<Result<String, String>>), String> {
+/// Returns (port, receiver that yields the auth code or an error, cancel handle).
+/// The server runs until the cancel handle is dropped.
+pub async fn wait_for_oauth_callback() -> Result<
+    (
+        u16,
+        oneshot::Receiver<Result<String, String>>,
+        OAuthCancelHandle,
+    ),
+    String,
+> {
     let (tx, rx) = oneshot::channel();
     let state = Arc::new(CallbackState {
         tx: Mutex::new(Some(tx)),
@@ -70,7 +101,7 @@ pub async fn wait_for_oauth_callback(
 
     let app = Router::new()
         .route("/callback", get(handle_callback))
-        .with_state(state);
+        .with_state(Arc::clone(&state));
 
     let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
         .await
@@ -83,13 +114,26 @@ pub async fn wait_for_oauth_callback(
 
     log::info!("OAuth callback server started on port {}", port);
 
+    let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
     tokio::spawn(async move {
-        if let Err(e) = axum::serve(listener, app).await {
+        let shutdown = async {
+            let _ = shutdown_rx.await;
+        };
+        if let Err(e) = axum::serve(listener, app)
+            .with_graceful_shutdown(shutdown)
+            .await
+        {
             log::error!("OAuth callback server error: {}", e);
         }
+        log::info!("OAuth callback server on port {} stopped", port);
     });
 
-    Ok((port, rx))
+    let handle = OAuthCancelHandle {
+        port,
+        state,
+        _shutdown: shutdown_tx,
+    };
+    Ok((port, rx, handle))
 }
 
 #[cfg(test)]
@@ -98,13 +142,13 @@ mod tests {
 
     #[tokio::test]
     async fn test_server_starts_and_returns_port() {
-        let (port, _rx) = wait_for_oauth_callback().await.unwrap();
+        let (port, _rx, _handle) = wait_for_oauth_callback().await.unwrap();
         assert!(port > 0);
     }
 
     #[tokio::test]
     async fn test_callback_with_code() {
-        let (port, rx) = wait_for_oauth_callback().await.unwrap();
+        let (port, rx, _handle) = wait_for_oauth_callback().await.unwrap();
 
         let client = reqwest::Client::new();
         let resp = client
@@ -122,7 +166,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_callback_with_error() {
-        let (port, rx) = wait_for_oauth_callback().await.unwrap();
+        let (port, rx, _handle) = wait_for_oauth_callback().await.unwrap();
 
         let client = reqwest::Client::new();
         let resp = client
@@ -141,7 +185,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_callback_with_no_params() {
-        let (port, rx) = wait_for_oauth_callback().await.unwrap();
+        let (port, rx, _handle) = wait_for_oauth_callback().await.unwrap();
 
         let client = reqwest::Client::new();
         let resp = client
@@ -159,7 +203,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_second_callback_ignored() {
-        let (port, rx) = wait_for_oauth_callback().await.unwrap();
+        let (port, rx, _handle) = wait_for_oauth_callback().await.unwrap();
 
         let client = reqwest::Client::new();
         // First callback should succeed
@@ -180,4 +224,21 @@ mod tests {
         let result = rx.await.unwrap();
         assert_eq!(result.unwrap(), "first_code");
     }
+
+    #[tokio::test]
+    async fn test_cancel_resolves_receiver_and_stops_server() {
+        let (port, rx, handle) = wait_for_oauth_callback().await.unwrap();
+        handle.cancel().await;
+
+        let result = rx.await.unwrap();
+        assert_eq!(result.unwrap_err(), CANCELLED_MESSAGE);
+
+        // Give the server a moment to finish its graceful shutdown
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        let resp = reqwest::Client::new()
+            .get(format!("http://127.0.0.1:{}/callback?code=late", port))
+            .send()
+            .await;
+        assert!(resp.is_err());
+    }
 }
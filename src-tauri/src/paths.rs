@@ -0,0 +1,298 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::util::no_working_dir_error;
+
+/// Normalize a user-chosen working directory: expand a leading `~`, make it
+/// absolute, resolve symlinks and check it is an existing folder.
+/// An empty path (no working directory) is returned unchanged.
+pub fn normalize_working_dir(path: &str) -> Result<String, String> {
+    let trimmed = path.trim();
+    if trimmed.is_empty() {
+        return Ok(String::new());
+    }
+
+    let expanded = expand_tilde(trimmed)?;
+    let absolute = if expanded.is_absolute() {
+        expanded
+    } else {
+        std::env::current_dir()
+            .map_err(|e| format!("現在のフォルダを取得できませんでした: {}", e))?
+            .join(expanded)
+    };
+
+    let canonical = std::fs::canonicalize(&absolute)
+        .map_err(|_| format!("フォルダが見つかりません: {}", path))?;
+    if !canonical.is_dir() {
+        return Err(format!("フォルダではありません: {}", path));
+    }
+    Ok(strip_verbatim_prefix(&canonical))
+}
+
+/// Whether files can be created in `path`, checked by creating and deleting
+/// a temporary file. Errors only if `path` isn't an existing folder.
+pub fn check_working_dir_writable(path: &str) -> Result<bool, String> {
+    let dir = Path::new(path);
+    if !dir.is_dir() {
+        return Err(format!("フォルダが見つかりません: {}", path));
+    }
+    let probe = dir.join(format!(".cowork-write-test-{}", uuid::Uuid::new_v4()));
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(file) => {
+            drop(file);
+            let _ = std::fs::remove_file(&probe);
+            Ok(true)
+        }
+        Err(e) => {
+            log::info!("Working dir {} is not writable: {}", path, e);
+            Ok(false)
+        }
+    }
+}
+
+/// Expand `~` / `~/...` to the user's home directory
+fn expand_tilde(path: &str) -> Result<PathBuf, String> {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => rest,
+        _ => return Ok(PathBuf::from(path)),
+    };
+    let home = crate::claude::home_dir()
+        .ok_or("ホームフォルダが見つかりません")?;
+    Ok(home.join(rest.trim_start_matches(['/', '\\'])))
+}
+
+/// `canonicalize` returns `\\?\C:\...` on Windows; keep the familiar form
+fn strip_verbatim_prefix(path: &Path) -> String {
+    let s = path.to_string_lossy().to_string();
+    match s.strip_prefix(r"\\?\") {
+        Some(rest) if !rest.starts_with("UNC") => rest.to_string(),
+        _ => s,
+    }
+}
+
+/// Where `path` points given the working directory: absolute (and `~`)
+/// paths are taken as-is, relative ones are joined onto `working_dir`.
+/// `.`/`..` are resolved lexically; the target need not exist.
+pub fn resolve_against(working_dir: &str, path: &str) -> Result<PathBuf, String> {
+    let expanded = expand_tilde(path.trim())?;
+    let joined = if expanded.is_absolute() {
+        expanded
+    } else if working_dir.is_empty() {
+        return Err(no_working_dir_error());
+    } else {
+        Path::new(working_dir).join(expanded)
+    };
+    normalize(&joined)
+}
+
+/// Resolve `user_path` (relative to `base`, or absolute) and make sure the
+/// result stays inside `base`. `..` is resolved lexically so the target does
+/// not need to exist; the deepest existing ancestor is canonicalized so
+/// symlinks cannot be used to escape.
+pub fn resolve_within(base: &Path, user_path: &str) -> Result<PathBuf, String> {
+    let base = canonicalize_existing(&normalize(base)?);
+    let candidate = Path::new(user_path.trim());
+    let joined = if candidate.is_absolute() {
+        candidate.to_path_buf()
+    } else {
+        base.join(candidate)
+    };
+    let resolved = canonicalize_existing(&normalize(&joined)?);
+
+    if resolved.starts_with(&base) {
+        Ok(resolved)
+    } else {
+        Err(format!(
+            "作業フォルダの外にあるパスにはアクセスできません: {}",
+            user_path
+        ))
+    }
+}
+
+/// Lexically remove `.` and `..` components
+fn normalize(path: &Path) -> Result<PathBuf, String> {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                let at_root = !matches!(
+                    out.components().next_back(),
+                    Some(Component::Normal(_))
+                );
+                if at_root {
+                    return Err(format!("不正なパスです: {}", path.display()));
+                }
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    Ok(out)
+}
+
+/// Canonicalize the deepest existing ancestor and re-append the rest
+fn canonicalize_existing(path: &Path) -> PathBuf {
+    let mut existing = path.to_path_buf();
+    let mut rest = Vec::new();
+    while !existing.exists() {
+        match (existing.file_name(), existing.parent()) {
+            (Some(name), Some(parent)) => {
+                rest.push(name.to_os_string());
+                existing = parent.to_path_buf();
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+    let mut resolved = std::fs::canonicalize(&existing).unwrap_or(existing);
+    for name in rest.into_iter().rev() {
+        resolved.push(name);
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_base() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("cowork-paths-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(dir.join("sub")).unwrap();
+        std::fs::canonicalize(dir).unwrap()
+    }
+
+    #[test]
+    fn test_check_working_dir_writable() {
+        let base = temp_base();
+        let base_str = base.to_string_lossy().to_string();
+        assert!(check_working_dir_writable(&base_str).unwrap());
+        // The probe file is cleaned up
+        assert_eq!(std::fs::read_dir(&base).unwrap().count(), 1);
+        assert!(check_working_dir_writable(&base.join("missing").to_string_lossy()).is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_working_dir_read_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // root ignores directory permissions
+        if unsafe { libc::geteuid() } == 0 {
+            return;
+        }
+        let base = temp_base();
+        let locked = base.join("sub");
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+        assert!(!check_working_dir_writable(&locked.to_string_lossy()).unwrap());
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_plain_relative() {
+        let base = temp_base();
+        assert_eq!(resolve_within(&base, "sub/a.txt").unwrap(), base.join("sub").join("a.txt"));
+        assert_eq!(resolve_within(&base, "./new/file.md").unwrap(), base.join("new").join("file.md"));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_relative_with_inner_parent_dir() {
+        let base = temp_base();
+        assert_eq!(resolve_within(&base, "sub/../b.txt").unwrap(), base.join("b.txt"));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_absolute_inside_base() {
+        let base = temp_base();
+        let inside = base.join("sub").join("c.txt");
+        assert_eq!(resolve_within(&base, inside.to_str().unwrap()).unwrap(), inside);
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_absolute_outside_base() {
+        let base = temp_base();
+        let outside = std::env::temp_dir().join("elsewhere.txt");
+        assert!(resolve_within(&base, outside.to_str().unwrap()).is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_parent_traversal_rejected() {
+        let base = temp_base();
+        let result = resolve_within(&base, "../../etc/passwd");
+        assert!(result.is_err());
+        assert!(result.unwrap_err().contains("作業フォルダの外"));
+        assert!(resolve_within(&base, "sub/../../x").is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_sibling_with_common_prefix_rejected() {
+        let base = temp_base();
+        let sibling = format!("{}-other/file.txt", base.display());
+        assert!(resolve_within(&base, &sibling).is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_escape_rejected() {
+        let base = temp_base();
+        std::os::unix::fs::symlink(std::env::temp_dir(), base.join("link")).unwrap();
+        assert!(resolve_within(&base, "link/escaped.txt").is_err());
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_resolve_against() {
+        let base = temp_base();
+        let wd = base.to_str().unwrap();
+        assert_eq!(
+            resolve_against(wd, "output/report.csv").unwrap(),
+            base.join("output").join("report.csv")
+        );
+        assert_eq!(resolve_against(wd, "./sub/../a.txt").unwrap(), base.join("a.txt"));
+
+        // Absolute paths pass through, even outside the working dir
+        let outside = std::env::temp_dir().join("elsewhere.txt");
+        assert_eq!(resolve_against(wd, outside.to_str().unwrap()).unwrap(), outside);
+
+        assert!(crate::util::is_no_working_dir_error(&resolve_against("", "a.txt").unwrap_err()));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_normalize_working_dir_expands_tilde() {
+        let home = std::fs::canonicalize(crate::claude::home_dir().unwrap()).unwrap();
+        assert_eq!(normalize_working_dir("~").unwrap(), strip_verbatim_prefix(&home));
+    }
+
+    #[test]
+    fn test_normalize_working_dir_makes_relative_absolute() {
+        let cwd = std::fs::canonicalize(std::env::current_dir().unwrap()).unwrap();
+        let normalized = normalize_working_dir(".").unwrap();
+        assert!(Path::new(&normalized).is_absolute());
+        assert_eq!(normalized, strip_verbatim_prefix(&cwd));
+    }
+
+    #[test]
+    fn test_normalize_working_dir_rejects_missing_and_files() {
+        let result = normalize_working_dir("/nonexistent_dir_xyz/project");
+        assert!(result.unwrap_err().contains("フォルダが見つかりません"));
+
+        let base = temp_base();
+        let file = base.join("file.txt");
+        std::fs::write(&file, "x").unwrap();
+        let result = normalize_working_dir(file.to_str().unwrap());
+        assert!(result.unwrap_err().contains("フォルダではありません"));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
+    #[test]
+    fn test_normalize_working_dir_empty() {
+        assert_eq!(normalize_working_dir("  ").unwrap(), "");
+    }
+}
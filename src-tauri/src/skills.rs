@@ -1,20 +1,68 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::sync::Mutex;
 
+use crate::util::no_working_dir_error;
+
+/// Where a skill is stored
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SkillSource {
+    /// {working_dir}/.claude/skills
+    #[default]
+    Project,
+    /// ~/.claude/skills
+    User,
+}
+
 /// A Claude Code skill (stored as .claude/skills/{name}/SKILL.md)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoworkSkill {
     pub name: String,        // Directory name = skill name
     pub description: String, // From YAML frontmatter
     pub body: String,        // Markdown body (instructions with $ARGUMENTS)
+    #[serde(default)]
+    pub source: SkillSource,
+    /// Positional arguments declared in frontmatter (`arguments: [folder, action?]`)
+    #[serde(default)]
+    pub arguments: Vec<SkillArgument>,
+}
+
+/// One declared skill argument; a trailing `?` in frontmatter makes it optional
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkillArgument {
+    pub name: String,
+    pub required: bool,
+}
+
+/// Outcome of a skill migration run
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationReport {
+    pub migrated: usize,
+    /// Files left alone because a skill with the same name already exists
+    #[serde(default)]
+    pub skipped: Vec<String>,
+    /// Per-file failures ("file name: reason")
+    pub errors: Vec<String>,
+}
+
+impl MigrationReport {
+    pub fn merge(&mut self, other: MigrationReport) {
+        self.migrated += other.migrated;
+        self.skipped.extend(other.skipped);
+        self.errors.extend(other.errors);
+    }
 }
 
 /// Manages Claude Code skills stored as SKILL.md files
 pub struct SkillStore {
     working_dir: Mutex<String>,
     legacy_dir: PathBuf,
+    /// Saved display order (pinned skill names, first to last)
+    order_file: PathBuf,
+    /// User-level skills (~/.claude/skills), shared by every project
+    user_dir: Option<PathBuf>,
 }
 
 impl SkillStore {
@@ -22,9 +70,22 @@ impl SkillStore {
         Self {
             working_dir: Mutex::new(String::new()),
             legacy_dir: app_data_dir.join("skills"),
+            order_file: app_data_dir.join("skill_order.json"),
+            user_dir: None,
         }
     }
 
+    /// Also read skills from a user-level skills directory
+    pub fn with_user_dir(mut self, dir: PathBuf) -> Self {
+        self.user_dir = Some(dir);
+        self
+    }
+
+    /// The user-level skills directory, if one is configured
+    pub fn user_dir(&self) -> Option<&Path> {
+        self.user_dir.as_deref()
+    }
+
     pub async fn set_working_dir(&self, dir: String) {
         let mut wd = self.working_dir.lock().await;
         *wd = dir;
@@ -34,96 +95,167 @@ impl SkillStore {
     async fn skills_dir(&self) -> Result<PathBuf, String> {
         let wd = self.working_dir.lock().await;
         if wd.is_empty() {
-            return Err("作業フォルダが設定されていません".to_string());
+            return Err(no_working_dir_error());
         }
         Ok(PathBuf::from(wd.as_str()).join(".claude").join("skills"))
     }
 
-    /// Ensure the skills directory exists
-    async fn ensure_dir(&self) -> Result<PathBuf, String> {
-        let dir = self.skills_dir().await?;
+    /// The skills directory for `source`
+    async fn dir_for(&self, source: SkillSource) -> Result<PathBuf, String> {
+        match source {
+            SkillSource::Project => self.skills_dir().await,
+            SkillSource::User => self
+                .user_dir
+                .clone()
+                .ok_or_else(|| "ユーザーのスキルフォルダが設定されていません".to_string()),
+        }
+    }
+
+    /// Directories to read skills from, project first
+    async fn search_dirs(&self) -> Vec<(PathBuf, SkillSource)> {
+        let mut dirs = Vec::new();
+        if let Ok(dir) = self.skills_dir().await {
+            dirs.push((dir, SkillSource::Project));
+        }
+        if let Some(dir) = &self.user_dir {
+            dirs.push((dir.clone(), SkillSource::User));
+        }
+        dirs
+    }
+
+    /// Ensure the skills directory for `source` exists
+    async fn ensure_dir(&self, source: SkillSource) -> Result<PathBuf, String> {
+        let dir = self.dir_for(source).await?;
         fs::create_dir_all(&dir)
             .await
             .map_err(|e| format!("スキルフォルダを作成できませんでした: {}", e))?;
         Ok(dir)
     }
 
-    /// List all skills from .claude/skills/*/SKILL.md
+    /// List all skills from .claude/skills/*/SKILL.md of the project and the
+    /// user. A project skill hides a user skill of the same name.
     pub async fn list(&self) -> Result<Vec<CoworkSkill>, String> {
-        let dir = match self.skills_dir().await {
-            Ok(d) => d,
-            Err(_) => return Ok(Vec::new()),
-        };
-
-        if !dir.exists() {
-            return Ok(Vec::new());
-        }
-
-        let mut skills = Vec::new();
-        let mut entries = fs::read_dir(&dir)
-            .await
-            .map_err(|e| format!("スキルフォルダを読み込めませんでした: {}", e))?;
-
-        while let Some(entry) = entries
-            .next_entry()
-            .await
-            .map_err(|e| format!("ファイル情報を読み込めませんでした: {}", e))?
-        {
-            let path = entry.path();
-            if !path.is_dir() {
-                continue;
-            }
-            let skill_file = path.join("SKILL.md");
-            if !skill_file.exists() {
-                continue;
-            }
-            let content = match fs::read_to_string(&skill_file).await {
-                Ok(c) => c,
-                Err(e) => {
-                    log::warn!("Failed to read skill file {:?}: {}", skill_file, e);
-                    continue;
-                }
-            };
-            match parse_skill_md(&content) {
-                Ok(mut skill) => {
-                    // Use directory name as skill name if not set in frontmatter
-                    if skill.name.is_empty() {
-                        if let Some(dir_name) = path.file_name().and_then(|s| s.to_str()) {
-                            skill.name = dir_name.to_string();
-                        }
-                    }
+        let mut skills: Vec<CoworkSkill> = Vec::new();
+        for (dir, source) in self.search_dirs().await {
+            for skill in list_dir(&dir, source).await? {
+                if !skills.iter().any(|s| s.name == skill.name) {
                     skills.push(skill);
                 }
-                Err(e) => {
-                    log::warn!("Failed to parse skill {:?}: {}", skill_file, e);
+            }
+        }
+        let order = self.load_order().await;
+        skills.sort_by(|a, b| order_key(&order, &a.name).cmp(&order_key(&order, &b.name)));
+        Ok(skills)
+    }
+
+    /// List all skills together with validation warnings.
+    /// Unlike `list`, skills that fail to load are included (with an empty body)
+    /// so the UI can flag them as broken.
+    pub async fn list_with_status(&self) -> Result<Vec<(CoworkSkill, Vec<String>)>, String> {
+        let mut results: Vec<(CoworkSkill, Vec<String>)> = Vec::new();
+        for (dir, source) in self.search_dirs().await {
+            for result in list_dir_with_status(&dir, source).await? {
+                if !results.iter().any(|(s, _)| s.name == result.0.name) {
+                    results.push(result);
                 }
             }
         }
+        let order = self.load_order().await;
+        results.sort_by(|a, b| order_key(&order, &a.0.name).cmp(&order_key(&order, &b.0.name)));
+        Ok(results)
+    }
+
+    /// Pinned skill names in display order; empty if none were saved
+    async fn load_order(&self) -> Vec<String> {
+        match fs::read_to_string(&self.order_file).await {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    }
 
-        skills.sort_by(|a, b| a.name.cmp(&b.name));
-        Ok(skills)
+    /// Save the display order: these skills first, the rest alphabetically after
+    pub async fn reorder(&self, ordered_names: &[String]) -> Result<(), String> {
+        if let Some(parent) = self.order_file.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+        }
+        let content = serde_json::to_string_pretty(ordered_names)
+            .map_err(|e| format!("データの変換に失敗しました: {}", e))?;
+        fs::write(&self.order_file, content)
+            .await
+            .map_err(|e| format!("スキルの並び順を保存できませんでした: {}", e))
     }
 
-    /// Get a single skill by name
+    /// Get a single skill by name, preferring the project's over the user's
     pub async fn get(&self, name: &str) -> Result<CoworkSkill, String> {
-        let dir = self.skills_dir().await?;
-        let skill_file = dir.join(name).join("SKILL.md");
-        if !skill_file.exists() {
-            return Err(format!("スキル '{}' が見つかりません", name));
+        let mut last_err = None;
+        for (dir, source) in self.search_dirs().await {
+            match get_in(&dir, name, source).await {
+                Ok(skill) => return Ok(skill),
+                Err(e) => last_err = Some(e),
+            }
         }
-        let content = fs::read_to_string(&skill_file)
+        Err(last_err.unwrap_or_else(no_working_dir_error))
+    }
+
+    /// The skill's body with `$ARGUMENTS` expanded to `context`, ready to be
+    /// sent to any backend that doesn't expand `/name` itself
+    pub async fn resolve_skill(&self, name: &str, context: &str) -> Result<String, String> {
+        let skill = self.get(name).await?;
+        Ok(expand_arguments(&skill.body, context))
+    }
+
+    /// The skill file's exact contents, frontmatter formatting included
+    pub async fn get_raw(&self, name: &str) -> Result<String, String> {
+        let skill_file = self
+            .find_raw(name)
+            .await?
+            .ok_or_else(|| format!("スキル '{}' が見つかりません", name))?;
+        fs::read_to_string(&skill_file)
             .await
-            .map_err(|e| format!("スキルファイルを読み込めませんでした: {}", e))?;
-        let mut skill = parse_skill_md(&content)?;
-        if skill.name.is_empty() {
-            skill.name = name.to_string();
+            .map_err(|e| format!("スキルファイルを読み込めませんでした: {}", e))
+    }
+
+    /// Write `content` verbatim to the skill's file, creating a project
+    /// skill if none exists. The content must parse as a SKILL.md.
+    pub async fn save_raw(&self, name: &str, content: &str) -> Result<(), String> {
+        parse_skill_md(content)?;
+        let path = match self.find_raw(name).await? {
+            Some(path) => path,
+            None => {
+                let dir = self.ensure_dir(SkillSource::Project).await?;
+                let skill_dir = dir.join(sanitize_filename(name));
+                fs::create_dir_all(&skill_dir)
+                    .await
+                    .map_err(|e| format!("スキルディレクトリを作成できませんでした: {}", e))?;
+                skill_dir.join("SKILL.md")
+            }
+        };
+        fs::write(&path, content)
+            .await
+            .map_err(|e| format!("スキルファイルを書き込めませんでした: {}", e))
+    }
+
+    /// The skill file for `name`: its folder as named, or as `save_raw`
+    /// would have created it. Names that could leave the skills folder are
+    /// rejected.
+    async fn find_raw(&self, name: &str) -> Result<Option<PathBuf>, String> {
+        let dir_names = raw_skill_dir_names(name)?;
+        for (dir, _) in self.search_dirs().await {
+            for dir_name in &dir_names {
+                if let Some(path) = find_skill_file(&dir.join(dir_name)) {
+                    return Ok(Some(path));
+                }
+            }
         }
-        Ok(skill)
+        Ok(None)
     }
 
-    /// Save a skill as .claude/skills/{name}/SKILL.md
+    /// Save a skill as .claude/skills/{name}/SKILL.md in the directory of
+    /// its `source` (the project unless set otherwise)
     pub async fn save(&self, skill: &CoworkSkill) -> Result<(), String> {
-        let dir = self.ensure_dir().await?;
+        let dir = self.ensure_dir(skill.source).await?;
         let dirname = sanitize_filename(&skill.name);
         let skill_dir = dir.join(&dirname);
         fs::create_dir_all(&skill_dir)
@@ -137,8 +269,9 @@ impl SkillStore {
     }
 
     /// Delete a skill by name (removes entire directory)
-    pub async fn delete(&self, name: &str) -> Result<(), String> {
-        let dir = self.skills_dir().await?;
+    pub async fn delete(&self, name: &str, source: SkillSource) -> Result<(), String> {
+        raw_skill_dir_names(name)?;
+        let dir = self.dir_for(source).await?;
         let skill_dir = dir.join(name);
         if skill_dir.exists() && skill_dir.is_dir() {
             fs::remove_dir_all(&skill_dir)
@@ -148,15 +281,68 @@ impl SkillStore {
         Ok(())
     }
 
+    /// Write one migrated skill and rename its source to `{source}.migrated`.
+    /// An existing skill of the same name is never clobbered unless
+    /// `overwrite` is set, and the source is only renamed once the new
+    /// SKILL.md is confirmed on disk.
+    async fn migrate_one(
+        &self,
+        skill: &CoworkSkill,
+        source: &Path,
+        overwrite: bool,
+        report: &mut MigrationReport,
+    ) {
+        let file_name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let skill_file = match self.skills_dir().await {
+            Ok(dir) => dir.join(sanitize_filename(&skill.name)).join("SKILL.md"),
+            Err(e) => {
+                report.errors.push(format!("{}: {}", file_name, e));
+                return;
+            }
+        };
+        let target_dir = skill_file.parent().map(Path::to_path_buf).unwrap_or_default();
+        if target_dir.exists() && !overwrite {
+            report.skipped.push(format!(
+                "{}: 同名のスキル「{}」が既にあるためスキップしました",
+                file_name, skill.name
+            ));
+            return;
+        }
+
+        if let Err(e) = self.save(skill).await {
+            log::warn!("Failed to migrate '{}': {}", skill.name, e);
+            report.errors.push(format!("{}: {}", file_name, e));
+            return;
+        }
+        let expected = serialize_skill_md(skill);
+        match fs::read_to_string(&skill_file).await {
+            Ok(written) if written == expected => {}
+            _ => {
+                report
+                    .errors
+                    .push(format!("{}: スキルファイルの書き込みを確認できませんでした", file_name));
+                return;
+            }
+        }
+
+        let mut backup = source.as_os_str().to_os_string();
+        backup.push(".migrated");
+        let _ = fs::rename(source, PathBuf::from(backup)).await;
+        report.migrated += 1;
+    }
+
     /// Migrate legacy JSON skills to .claude/skills/ format
-    pub async fn migrate_legacy_skills(&self) -> Result<usize, String> {
+    pub async fn migrate_legacy_skills(&self, overwrite: bool) -> Result<MigrationReport, String> {
+        let mut report = MigrationReport::default();
         if !self.legacy_dir.exists() {
-            return Ok(0);
+            return Ok(report);
         }
 
         let _ = self.skills_dir().await?;
 
-        let mut migrated = 0;
         let mut entries = fs::read_dir(&self.legacy_dir)
             .await
             .map_err(|e| format!("レガシースキルフォルダを読み込めませんでした: {}", e))?;
@@ -168,47 +354,53 @@ impl SkillStore {
         {
             let path = entry.path();
             if path.extension().map_or(false, |ext| ext == "json") {
+                let file_name = entry.file_name().to_string_lossy().to_string();
                 let content = match fs::read_to_string(&path).await {
                     Ok(c) => c,
-                    Err(_) => continue,
+                    Err(e) => {
+                        report.errors.push(format!("{}: 読み込めませんでした: {}", file_name, e));
+                        continue;
+                    }
                 };
 
-                if let Ok(legacy) = serde_json::from_str::<LegacyJsonSkill>(&content) {
-                    let skill = CoworkSkill {
-                        name: legacy.name.clone(),
-                        description: legacy.description,
-                        body: convert_template_to_body(&legacy.prompt_template),
-                    };
-
-                    if let Err(e) = self.save(&skill).await {
-                        log::warn!("Failed to migrate skill '{}': {}", legacy.name, e);
+                let legacy = match serde_json::from_str::<LegacyJsonSkill>(&content) {
+                    Ok(l) => l,
+                    Err(e) => {
+                        report.errors.push(format!("{}: 形式が正しくありません: {}", file_name, e));
                         continue;
                     }
-
-                    let backup = path.with_extension("json.migrated");
-                    let _ = fs::rename(&path, &backup).await;
-                    migrated += 1;
-                }
+                };
+                let skill = CoworkSkill {
+                    name: legacy.name.clone(),
+                    description: legacy.description,
+                    body: convert_template_to_body(&legacy.prompt_template),
+                    source: SkillSource::Project,
+                    arguments: Vec::new(),
+                };
+                self.migrate_one(&skill, &path, overwrite, &mut report).await;
             }
         }
 
-        Ok(migrated)
+        Ok(report)
     }
 
     /// Migrate old .claude/commands/*.md to .claude/skills/ format
-    pub async fn migrate_commands_to_skills(&self) -> Result<usize, String> {
+    pub async fn migrate_commands_to_skills(
+        &self,
+        overwrite: bool,
+    ) -> Result<MigrationReport, String> {
+        let mut report = MigrationReport::default();
         let wd = self.working_dir.lock().await;
         if wd.is_empty() {
-            return Ok(0);
+            return Ok(report);
         }
         let commands_dir = PathBuf::from(wd.as_str()).join(".claude").join("commands");
         drop(wd);
 
         if !commands_dir.exists() {
-            return Ok(0);
+            return Ok(report);
         }
 
-        let mut migrated = 0;
         let mut entries = fs::read_dir(&commands_dir)
             .await
             .map_err(|e| format!("コマンドフォルダを読み込めませんでした: {}", e))?;
@@ -220,9 +412,13 @@ impl SkillStore {
         {
             let path = entry.path();
             if path.extension().map_or(false, |ext| ext == "md") {
+                let file_name = entry.file_name().to_string_lossy().to_string();
                 let content = match fs::read_to_string(&path).await {
                     Ok(c) => c,
-                    Err(_) => continue,
+                    Err(e) => {
+                        report.errors.push(format!("{}: 読み込めませんでした: {}", file_name, e));
+                        continue;
+                    }
                 };
 
                 match parse_old_command_md(&content) {
@@ -231,23 +427,72 @@ impl SkillStore {
                             skill.name = stem.to_string();
                         }
 
-                        if let Err(e) = self.save(&skill).await {
-                            log::warn!("Failed to migrate command '{}': {}", skill.name, e);
-                            continue;
-                        }
-
-                        let backup = path.with_extension("md.migrated");
-                        let _ = fs::rename(&path, &backup).await;
-                        migrated += 1;
+                        self.migrate_one(&skill, &path, overwrite, &mut report).await;
                     }
                     Err(e) => {
                         log::warn!("Failed to parse command {:?}: {}", path, e);
+                        report.errors.push(format!("{}: {}", file_name, e));
                     }
                 }
             }
         }
 
-        Ok(migrated)
+        Ok(report)
+    }
+
+    /// Delete `*.json.migrated` / `*.md.migrated` backups left by migration.
+    /// A backup is only removed when the skill it became still loads.
+    pub async fn cleanup_migration_backups(&self) -> Result<usize, String> {
+        let mut dirs = vec![(self.legacy_dir.clone(), "json")];
+        let wd = self.working_dir.lock().await.clone();
+        if !wd.is_empty() {
+            dirs.push((PathBuf::from(wd).join(".claude").join("commands"), "md"));
+        }
+
+        let mut removed = 0;
+        for (dir, ext) in dirs {
+            let Ok(mut entries) = fs::read_dir(&dir).await else {
+                continue;
+            };
+            let suffix = format!(".{}.migrated", ext);
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| format!("ファイル情報を読み込めませんでした: {}", e))?
+            {
+                let path = entry.path();
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let Some(stem) = file_name.strip_suffix(&suffix) else {
+                    continue;
+                };
+                let skill_name = if ext == "json" {
+                    // Legacy skills are named by their JSON content, not the file
+                    match fs::read_to_string(&path)
+                        .await
+                        .ok()
+                        .and_then(|c| serde_json::from_str::<LegacyJsonSkill>(&c).ok())
+                    {
+                        Some(legacy) => legacy.name,
+                        None => continue,
+                    }
+                } else {
+                    stem.to_string()
+                };
+                let project_skill = match self.skills_dir().await {
+                    Ok(dir) => get_in(&dir, &sanitize_filename(&skill_name), SkillSource::Project).await,
+                    Err(e) => Err(e),
+                };
+                if project_skill.is_err() {
+                    log::info!("Keeping {:?}: skill '{}' not found", path, skill_name);
+                    continue;
+                }
+                match fs::remove_file(&path).await {
+                    Ok(()) => removed += 1,
+                    Err(e) => log::warn!("Failed to remove {:?}: {}", path, e),
+                }
+            }
+        }
+        Ok(removed)
     }
 }
 
@@ -267,11 +512,205 @@ struct LegacyJsonSkillParam {
     name: String,
 }
 
+/// Load the skills of one skills directory, skipping ones that fail to parse
+async fn list_dir(dir: &Path, source: SkillSource) -> Result<Vec<CoworkSkill>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut skills = Vec::new();
+    let mut entries = fs::read_dir(dir)
+        .await
+        .map_err(|e| format!("スキルフォルダを読み込めませんでした: {}", e))?;
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("ファイル情報を読み込めませんでした: {}", e))?
+    {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let Some(skill_file) = find_skill_file(&path) else {
+            continue;
+        };
+        let content = match fs::read_to_string(&skill_file).await {
+            Ok(c) => c,
+            Err(e) => {
+                log::warn!("Failed to read skill file {:?}: {}", skill_file, e);
+                continue;
+            }
+        };
+        match parse_skill_md(&content) {
+            Ok(mut skill) => {
+                // Use directory name as skill name if not set in frontmatter
+                // (always, for README-style fallbacks)
+                if skill.name.is_empty() || !is_canonical_skill_file(&skill_file) {
+                    if let Some(dir_name) = path.file_name().and_then(|s| s.to_str()) {
+                        skill.name = dir_name.to_string();
+                    }
+                }
+                skill.source = source;
+                skills.push(skill);
+            }
+            Err(e) => {
+                log::warn!("Failed to parse skill {:?}: {}", skill_file, e);
+            }
+        }
+    }
+
+    Ok(skills)
+}
+
+/// `list_dir` including broken skills, with validation warnings
+async fn list_dir_with_status(
+    dir: &Path,
+    source: SkillSource,
+) -> Result<Vec<(CoworkSkill, Vec<String>)>, String> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut results = Vec::new();
+    let mut entries = fs::read_dir(dir)
+        .await
+        .map_err(|e| format!("スキルフォルダを読み込めませんでした: {}", e))?;
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("ファイル情報を読み込めませんでした: {}", e))?
+    {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+        let Some(skill_file) = find_skill_file(&path) else {
+            continue;
+        };
+        let dir_name = path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default()
+            .to_string();
+        let broken = |error: String| {
+            let skill = CoworkSkill {
+                name: dir_name.clone(),
+                description: String::new(),
+                body: String::new(),
+                source,
+                arguments: Vec::new(),
+            };
+            (skill, vec![error])
+        };
+
+        let content = match fs::read_to_string(&skill_file).await {
+            Ok(c) => c,
+            Err(e) => {
+                results.push(broken(format!("スキルファイルを読み込めませんでした: {}", e)));
+                continue;
+            }
+        };
+        match parse_skill_md(&content) {
+            Ok(mut skill) => {
+                if !is_canonical_skill_file(&skill_file) {
+                    skill.name = dir_name.clone();
+                }
+                let warnings = validate_skill(&skill, &dir_name);
+                if skill.name.is_empty() {
+                    skill.name = dir_name.clone();
+                }
+                skill.source = source;
+                results.push((skill, warnings));
+            }
+            Err(e) => results.push(broken(e)),
+        }
+    }
+
+    Ok(results)
+}
+
+/// Load the skill `name` from one skills directory
+async fn get_in(dir: &Path, name: &str, source: SkillSource) -> Result<CoworkSkill, String> {
+    raw_skill_dir_names(name)?;
+    let skill_file = find_skill_file(&dir.join(name))
+        .ok_or_else(|| format!("スキル '{}' が見つかりません", name))?;
+    let content = fs::read_to_string(&skill_file)
+        .await
+        .map_err(|e| format!("スキルファイルを読み込めませんでした: {}", e))?;
+    let mut skill = parse_skill_md(&content)?;
+    if skill.name.is_empty() || !is_canonical_skill_file(&skill_file) {
+        skill.name = name.to_string();
+    }
+    skill.source = source;
+    Ok(skill)
+}
+
+/// Folder names to look up for a raw skill `name`, as given first and then
+/// sanitized (how `save_raw` creates new skills)
+fn raw_skill_dir_names(name: &str) -> Result<Vec<String>, String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty()
+        || trimmed == "."
+        || trimmed == ".."
+        || trimmed.contains(['/', '\\'])
+    {
+        return Err(format!("スキル名が正しくありません: {}", name));
+    }
+    let mut names = vec![trimmed.to_string()];
+    let sanitized = sanitize_filename(trimmed);
+    if sanitized != trimmed {
+        names.push(sanitized);
+    }
+    Ok(names)
+}
+
+/// Sort key: pinned skills by their position, then the rest by name
+fn order_key<'a>(order: &[String], name: &'a str) -> (usize, &'a str) {
+    let pos = order.iter().position(|n| n == name).unwrap_or(usize::MAX);
+    (pos, name)
+}
+
+/// Check a parsed skill for problems worth showing in the UI
+fn validate_skill(skill: &CoworkSkill, dir_name: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if skill.description.trim().is_empty() {
+        warnings.push("説明(description)が設定されていません".to_string());
+    }
+    if skill.body.trim().is_empty() {
+        warnings.push("スキルの本文が空です".to_string());
+    }
+    if !skill.name.is_empty() && skill.name != dir_name {
+        warnings.push(format!(
+            "nameの「{}」がフォルダ名「{}」と一致しません",
+            skill.name, dir_name
+        ));
+    }
+    warnings
+}
+
 /// Expand $ARGUMENTS in a skill body
 pub fn expand_arguments(body: &str, arguments: &str) -> String {
     body.replace("$ARGUMENTS", arguments)
 }
 
+/// Files a skill directory's instructions are read from, in order of
+/// preference. `save` always writes SKILL.md.
+const SKILL_FILE_NAMES: &[&str] = &["SKILL.md", "README.md", "index.md"];
+
+/// The instructions file of a skill directory, if it has one
+fn find_skill_file(skill_dir: &Path) -> Option<PathBuf> {
+    SKILL_FILE_NAMES
+        .iter()
+        .map(|name| skill_dir.join(name))
+        .find(|p| p.is_file())
+}
+
+fn is_canonical_skill_file(path: &Path) -> bool {
+    path.file_name().is_some_and(|n| n == SKILL_FILE_NAMES[0])
+}
+
 /// Parse a SKILL.md file with optional YAML frontmatter
 fn parse_skill_md(content: &str) -> Result<CoworkSkill, String> {
     let trimmed = content.trim();
@@ -284,11 +723,14 @@ fn parse_skill_md(content: &str) -> Result<CoworkSkill, String> {
 
             let name = extract_field(frontmatter, "name");
             let description = extract_field(frontmatter, "description");
+            let arguments = parse_arguments(frontmatter);
 
             Ok(CoworkSkill {
                 name,
                 description,
                 body: body.to_string(),
+                source: SkillSource::Project,
+                arguments,
             })
         } else {
             Err("YAML frontmatterの終了マーカー(---)が見つかりません".to_string())
@@ -298,6 +740,8 @@ fn parse_skill_md(content: &str) -> Result<CoworkSkill, String> {
             name: String::new(),
             description: String::new(),
             body: trimmed.to_string(),
+            source: SkillSource::Project,
+            arguments: Vec::new(),
         })
     }
 }
@@ -318,6 +762,8 @@ fn parse_old_command_md(content: &str) -> Result<CoworkSkill, String> {
                 name: String::new(),
                 description,
                 body: body.to_string(),
+                source: SkillSource::Project,
+                arguments: Vec::new(),
             })
         } else {
             Err("YAML frontmatterの終了マーカー(---)が見つかりません".to_string())
@@ -327,6 +773,8 @@ fn parse_old_command_md(content: &str) -> Result<CoworkSkill, String> {
             name: String::new(),
             description: String::new(),
             body: trimmed.to_string(),
+            source: SkillSource::Project,
+            arguments: Vec::new(),
         })
     }
 }
@@ -343,14 +791,74 @@ fn extract_field(frontmatter: &str, key: &str) -> String {
     String::new()
 }
 
+/// Parse the `arguments` frontmatter list, either inline (`[folder, action?]`)
+/// or as a block of `- name` lines
+fn parse_arguments(frontmatter: &str) -> Vec<SkillArgument> {
+    let mut names = Vec::new();
+    let mut lines = frontmatter.lines();
+    while let Some(line) = lines.next() {
+        let Some(rest) = line.trim().strip_prefix("arguments:") else {
+            continue;
+        };
+        let rest = rest.trim();
+        if let Some(inline) = rest.strip_prefix('[').and_then(|r| r.strip_suffix(']')) {
+            names.extend(inline.split(',').map(|n| n.to_string()));
+        } else if rest.is_empty() {
+            for item in lines.by_ref() {
+                match item.trim().strip_prefix('-') {
+                    Some(name) => names.push(name.to_string()),
+                    None => break,
+                }
+            }
+        }
+        break;
+    }
+
+    names
+        .iter()
+        .map(|n| n.trim().trim_matches('"').trim_matches('\''))
+        .filter(|n| !n.is_empty() && *n != "?")
+        .map(|n| match n.strip_suffix('?') {
+            Some(name) => SkillArgument {
+                name: name.trim().to_string(),
+                required: false,
+            },
+            None => SkillArgument {
+                name: n.to_string(),
+                required: true,
+            },
+        })
+        .collect()
+}
+
+/// Check positional `provided` values against the skill's declared arguments.
+/// Blank values count as missing.
+pub fn validate_skill_args(skill: &CoworkSkill, provided: &[String]) -> Result<(), String> {
+    if provided.len() > skill.arguments.len() {
+        return Err(format!(
+            "引数が多すぎます（最大{}個、指定{}個）",
+            skill.arguments.len(),
+            provided.len()
+        ));
+    }
+    for (i, arg) in skill.arguments.iter().enumerate() {
+        let given = provided.get(i).is_some_and(|v| !v.trim().is_empty());
+        if arg.required && !given {
+            return Err(format!("引数「{}」が指定されていません", arg.name));
+        }
+    }
+    Ok(())
+}
+
 /// Serialize a CoworkSkill to SKILL.md format with YAML frontmatter
 fn serialize_skill_md(skill: &CoworkSkill) -> String {
     let mut content = String::new();
 
     let has_name = !skill.name.is_empty();
     let has_desc = !skill.description.is_empty();
+    let has_args = !skill.arguments.is_empty();
 
-    if has_name || has_desc {
+    if has_name || has_desc || has_args {
         content.push_str("---\n");
         if has_name {
             content.push_str(&format!("name: {}\n", skill.name));
@@ -358,6 +866,14 @@ fn serialize_skill_md(skill: &CoworkSkill) -> String {
         if has_desc {
             content.push_str(&format!("description: {}\n", skill.description));
         }
+        if has_args {
+            let names: Vec<String> = skill
+                .arguments
+                .iter()
+                .map(|a| if a.required { a.name.clone() } else { format!("{}?", a.name) })
+                .collect();
+            content.push_str(&format!("arguments: [{}]\n", names.join(", ")));
+        }
         content.push_str("---\n\n");
     }
 
@@ -442,6 +958,8 @@ $ARGUMENTS
             name: "test-skill".to_string(),
             description: "テストスキル".to_string(),
             body: "ファイルを$ARGUMENTSで処理して".to_string(),
+            source: SkillSource::Project,
+            arguments: Vec::new(),
         };
 
         let md = serialize_skill_md(&skill);
@@ -458,6 +976,8 @@ $ARGUMENTS
             name: String::new(),
             description: String::new(),
             body: "ファイルを整理して".to_string(),
+            source: SkillSource::Project,
+            arguments: Vec::new(),
         };
 
         let md = serialize_skill_md(&skill);
@@ -465,6 +985,38 @@ $ARGUMENTS
         assert!(md.contains("ファイルを整理して"));
     }
 
+    #[test]
+    fn test_parse_arguments_frontmatter() {
+        let skill = parse_skill_md("---\nname: sort\narguments: [folder, action?]\n---\n\n本文").unwrap();
+        assert_eq!(
+            skill.arguments,
+            vec![
+                SkillArgument { name: "folder".to_string(), required: true },
+                SkillArgument { name: "action".to_string(), required: false },
+            ]
+        );
+
+        let block = parse_skill_md("---\nname: sort\narguments:\n  - folder\n  - action\ndescription: 整理\n---\n本文").unwrap();
+        assert_eq!(block.arguments.len(), 2);
+        assert_eq!(block.description, "整理");
+
+        // Round trip through SKILL.md
+        let reparsed = parse_skill_md(&serialize_skill_md(&skill)).unwrap();
+        assert_eq!(reparsed.arguments, skill.arguments);
+    }
+
+    #[test]
+    fn test_validate_skill_args() {
+        let skill = parse_skill_md("---\nname: sort\narguments: [folder, action]\n---\n本文").unwrap();
+        let args = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+        assert!(validate_skill_args(&skill, &args(&["docs", "move"])).is_ok());
+        let err = validate_skill_args(&skill, &args(&["docs"])).unwrap_err();
+        assert!(err.contains("action"));
+        assert!(validate_skill_args(&skill, &args(&["docs", " "])).is_err());
+        assert!(validate_skill_args(&skill, &args(&["a", "b", "c"])).is_err());
+    }
+
     #[test]
     fn test_expand_arguments() {
         let body = "以下のファイルを処理してください:\n$ARGUMENTS";
@@ -537,4 +1089,446 @@ description: テスト用コマンド
         assert_eq!(skill.description, "テスト用コマンド");
         assert!(skill.body.contains("ファイルを整理してください"));
     }
+
+    #[tokio::test]
+    async fn test_list_with_status_includes_broken_skills() {
+        let wd = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let skills_dir = wd.join(".claude").join("skills");
+        fs::create_dir_all(skills_dir.join("good")).await.unwrap();
+        fs::create_dir_all(skills_dir.join("broken")).await.unwrap();
+        fs::write(
+            skills_dir.join("good").join("SKILL.md"),
+            "---\nname: good\ndescription: 正常なスキル\n---\n\n本文\n",
+        )
+        .await
+        .unwrap();
+        fs::write(
+            skills_dir.join("broken").join("SKILL.md"),
+            "---\ndescription: 終了マーカーなし\n",
+        )
+        .await
+        .unwrap();
+
+        let store = SkillStore::new(wd.join("data"));
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+
+        // The plain list silently drops the broken skill
+        assert_eq!(store.list().await.unwrap().len(), 1);
+
+        let results = store.list_with_status().await.unwrap();
+        assert_eq!(results.len(), 2);
+
+        let (broken, broken_warnings) = &results[0];
+        assert_eq!(broken.name, "broken");
+        assert!(broken.body.is_empty());
+        assert_eq!(broken_warnings.len(), 1);
+        assert!(broken_warnings[0].contains("終了マーカー"));
+
+        let (good, good_warnings) = &results[1];
+        assert_eq!(good.name, "good");
+        assert!(good_warnings.is_empty());
+
+        let _ = fs::remove_dir_all(&wd).await;
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_migration_backups() {
+        let root = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let data_dir = root.join("data");
+        let wd = root.join("work");
+        let legacy = data_dir.join("skills");
+        let commands = wd.join(".claude").join("commands");
+        fs::create_dir_all(&legacy).await.unwrap();
+        fs::create_dir_all(&commands).await.unwrap();
+        let legacy_json = r#"{"name":"summary","description":"要約","prompt_template":"{{input}}を要約","parameters":[]}"#;
+        fs::write(legacy.join("summary.json"), legacy_json).await.unwrap();
+        fs::write(commands.join("review.md"), "---\ndescription: レビュー\n---\n\nレビューして").await.unwrap();
+
+        let store = SkillStore::new(data_dir);
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+        assert_eq!(store.migrate_legacy_skills(false).await.unwrap().migrated, 1);
+        assert_eq!(store.migrate_commands_to_skills(false).await.unwrap().migrated, 1);
+        assert!(legacy.join("summary.json.migrated").exists());
+        assert!(commands.join("review.md.migrated").exists());
+
+        // A backup whose skill is gone is kept
+        fs::write(
+            legacy.join("orphan.json.migrated"),
+            r#"{"name":"orphan","description":"","prompt_template":"","parameters":[]}"#,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(store.cleanup_migration_backups().await.unwrap(), 2);
+        assert!(!legacy.join("summary.json.migrated").exists());
+        assert!(!commands.join("review.md.migrated").exists());
+        assert!(legacy.join("orphan.json.migrated").exists());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_readme_only_skill_discovered() {
+        let wd = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let skill_dir = wd.join(".claude").join("skills").join("weekly-report");
+        fs::create_dir_all(&skill_dir).await.unwrap();
+        fs::write(skill_dir.join("README.md"), "# 週報
+
+今週の作業をまとめてください。
+")
+            .await
+            .unwrap();
+
+        let store = SkillStore::new(wd.join("data"));
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+
+        let skills = store.list().await.unwrap();
+        assert_eq!(skills.len(), 1);
+        assert_eq!(skills[0].name, "weekly-report");
+        assert!(skills[0].body.contains("今週の作業"));
+
+        let skill = store.get("weekly-report").await.unwrap();
+        assert_eq!(skill.name, "weekly-report");
+
+        // Saving writes the canonical SKILL.md, which then takes precedence
+        store.save(&skill).await.unwrap();
+        assert!(skill_dir.join("SKILL.md").exists());
+        assert_eq!(find_skill_file(&skill_dir).unwrap(), skill_dir.join("SKILL.md"));
+
+        let _ = fs::remove_dir_all(&wd).await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_skill_expands_arguments() {
+        let wd = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let skill_dir = wd.join(".claude").join("skills").join("summarize");
+        fs::create_dir_all(&skill_dir).await.unwrap();
+        fs::write(
+            skill_dir.join("SKILL.md"),
+            "---\nname: summarize\ndescription: 要約\n---\n\n$ARGUMENTS を要約してください\n",
+        )
+        .await
+        .unwrap();
+
+        let store = SkillStore::new(wd.join("data"));
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+
+        let resolved = store.resolve_skill("summarize", "report.pdf").await.unwrap();
+        assert!(resolved.contains("report.pdf を要約してください"));
+        assert!(!resolved.contains("$ARGUMENTS"));
+
+        let err = store.resolve_skill("missing", "x").await.unwrap_err();
+        assert!(err.contains("見つかりません"));
+
+        let _ = fs::remove_dir_all(&wd).await;
+    }
+
+    #[tokio::test]
+    async fn test_get_and_save_skill_raw() {
+        let wd = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let skill_dir = wd.join(".claude").join("skills").join("weekly");
+        fs::create_dir_all(&skill_dir).await.unwrap();
+        // Quoting and key order that a parse/serialize round trip would lose
+        let original = "---\ndescription: \"週報\"\nname: weekly\n---\n\n# 手順\n";
+        fs::write(skill_dir.join("SKILL.md"), original).await.unwrap();
+
+        let store = SkillStore::new(wd.join("data"));
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+        assert_eq!(store.get_raw("weekly").await.unwrap(), original);
+
+        let edited = "---\nname: weekly\ndescription: 週報 v2\n---\n本文\n";
+        store.save_raw("weekly", edited).await.unwrap();
+        assert_eq!(fs::read_to_string(skill_dir.join("SKILL.md")).await.unwrap(), edited);
+
+        let err = store
+            .save_raw("weekly", "---\nname: weekly\n本文だけ\n")
+            .await
+            .unwrap_err();
+        assert!(err.contains("終了マーカー"));
+        assert_eq!(store.get_raw("weekly").await.unwrap(), edited);
+
+        // Unknown names become new project skills
+        store.save_raw("new-one", "---\nname: new-one\n---\nx\n").await.unwrap();
+        assert!(wd.join(".claude/skills/new-one/SKILL.md").is_file());
+        assert!(store.get_raw("missing").await.unwrap_err().contains("見つかりません"));
+
+        // A name with spaces is found again under the folder it was created as
+        store.save_raw("my skill", "---\nname: my skill\n---\nx\n").await.unwrap();
+        assert!(wd.join(".claude/skills/my-skill/SKILL.md").is_file());
+        assert_eq!(store.get_raw("my skill").await.unwrap(), "---\nname: my skill\n---\nx\n");
+
+        let _ = fs::remove_dir_all(&wd).await;
+    }
+
+    #[tokio::test]
+    async fn test_raw_skill_names_cannot_escape_skills_dir() {
+        let wd = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(wd.join(".claude").join("skills")).await.unwrap();
+        // README.md is a valid skill file name, so `../..` would reach it
+        fs::write(wd.join("README.md"), "# project\n").await.unwrap();
+
+        let store = SkillStore::new(wd.join("data"));
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+
+        for name in ["../..", "..", "../x", "a/b", "a\\b", ""] {
+            assert!(store.save_raw(name, "本文").await.is_err(), "accepted {:?}", name);
+            assert!(store.get_raw(name).await.is_err(), "read {:?}", name);
+        }
+        assert_eq!(fs::read_to_string(wd.join("README.md")).await.unwrap(), "# project\n");
+
+        let _ = fs::remove_dir_all(&wd).await;
+    }
+
+    #[tokio::test]
+    async fn test_skill_ops_without_working_dir() {
+        let data = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let store = SkillStore::new(data.clone());
+        let skill = parse_skill_md("---\nname: report\n---\n本文").unwrap();
+        let is_typed = |r: Result<_, String>| crate::util::is_no_working_dir_error(&r.unwrap_err());
+
+        assert!(is_typed(store.get("report").await.map(|_| ())));
+        assert!(is_typed(store.resolve_skill("report", "").await.map(|_| ())));
+        assert!(is_typed(store.save(&skill).await));
+        assert!(is_typed(store.delete("report", SkillSource::Project).await));
+        let _ = fs::remove_dir_all(&data).await;
+    }
+
+    #[tokio::test]
+    async fn test_reorder_skills() {
+        let wd = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let skills_dir = wd.join(".claude").join("skills");
+        for name in ["alpha", "beta", "gamma", "delta"] {
+            fs::create_dir_all(skills_dir.join(name)).await.unwrap();
+            fs::write(
+                skills_dir.join(name).join("SKILL.md"),
+                format!("---\nname: {}\ndescription: d\n---\n本文\n", name),
+            )
+            .await
+            .unwrap();
+        }
+        let store = SkillStore::new(wd.join("data"));
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+        let names = |skills: Vec<CoworkSkill>| skills.into_iter().map(|s| s.name).collect::<Vec<_>>();
+
+        assert_eq!(names(store.list().await.unwrap()), vec!["alpha", "beta", "delta", "gamma"]);
+
+        store
+            .reorder(&["gamma".to_string(), "beta".to_string(), "removed".to_string()])
+            .await
+            .unwrap();
+        assert_eq!(names(store.list().await.unwrap()), vec!["gamma", "beta", "alpha", "delta"]);
+
+        // A new skill that isn't pinned goes after the pinned ones, alphabetically
+        fs::create_dir_all(skills_dir.join("aardvark")).await.unwrap();
+        fs::write(skills_dir.join("aardvark").join("SKILL.md"), "---\nname: aardvark\n---\n本文\n")
+            .await
+            .unwrap();
+        assert_eq!(
+            names(store.list().await.unwrap()),
+            vec!["gamma", "beta", "aardvark", "alpha", "delta"]
+        );
+        let with_status: Vec<String> =
+            store.list_with_status().await.unwrap().into_iter().map(|(s, _)| s.name).collect();
+        assert_eq!(with_status[..2], ["gamma", "beta"]);
+
+        let _ = fs::remove_dir_all(&wd).await;
+    }
+
+    #[tokio::test]
+    async fn test_project_skills_shadow_user_skills() {
+        let root = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let wd = root.join("work");
+        let user_dir = root.join("home").join(".claude").join("skills");
+        let project_dir = wd.join(".claude").join("skills");
+        for (dir, name, description) in [
+            (&project_dir, "review", "プロジェクト版"),
+            (&user_dir, "review", "ユーザー版"),
+            (&user_dir, "translate", "翻訳"),
+        ] {
+            fs::create_dir_all(dir.join(name)).await.unwrap();
+            fs::write(
+                dir.join(name).join("SKILL.md"),
+                format!("---\nname: {}\ndescription: {}\n---\n\n本文\n", name, description),
+            )
+            .await
+            .unwrap();
+        }
+
+        let store = SkillStore::new(root.join("data")).with_user_dir(user_dir.clone());
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+
+        let skills = store.list().await.unwrap();
+        assert_eq!(skills.len(), 2);
+        assert_eq!(skills[0].name, "review");
+        assert_eq!(skills[0].description, "プロジェクト版");
+        assert_eq!(skills[0].source, SkillSource::Project);
+        assert_eq!(skills[1].name, "translate");
+        assert_eq!(skills[1].source, SkillSource::User);
+
+        let statuses = store.list_with_status().await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert_eq!(statuses[0].0.source, SkillSource::Project);
+
+        assert_eq!(store.get("review").await.unwrap().source, SkillSource::Project);
+        assert_eq!(store.get("translate").await.unwrap().source, SkillSource::User);
+
+        // Deleting the project skill uncovers the user one
+        store.delete("review", SkillSource::Project).await.unwrap();
+        assert_eq!(store.get("review").await.unwrap().description, "ユーザー版");
+
+        // Saving targets the skill's source
+        let skill = CoworkSkill {
+            name: "notes".to_string(),
+            description: "メモ".to_string(),
+            body: "本文".to_string(),
+            source: SkillSource::User,
+            arguments: Vec::new(),
+        };
+        store.save(&skill).await.unwrap();
+        assert!(user_dir.join("notes").join("SKILL.md").exists());
+        assert!(!project_dir.join("notes").exists());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_get_reject_escaping_names() {
+        let root = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let user_dir = root.join("home").join(".claude").join("skills");
+        fs::create_dir_all(&user_dir).await.unwrap();
+        fs::write(root.join("home").join(".claude").join("settings.json"), "{}").await.unwrap();
+        let store = SkillStore::new(root.join("data")).with_user_dir(user_dir.clone());
+
+        for name in ["..", ".", "../..", "a/b", ""] {
+            assert!(store.delete(name, SkillSource::User).await.is_err(), "{}", name);
+            assert!(get_in(&user_dir, name, SkillSource::User).await.is_err(), "{}", name);
+        }
+        assert!(user_dir.exists());
+        assert!(root.join("home").join(".claude").join("settings.json").exists());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[test]
+    fn test_validate_skill_warnings() {
+        let skill = CoworkSkill {
+            name: "other-name".to_string(),
+            description: String::new(),
+            body: String::new(),
+            source: SkillSource::Project,
+            arguments: Vec::new(),
+        };
+        let warnings = validate_skill(&skill, "dir-name");
+        assert_eq!(warnings.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_migrate_legacy_skills_reports_errors() {
+        let root = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let data_dir = root.join("data");
+        let wd = root.join("work");
+        fs::create_dir_all(data_dir.join("skills")).await.unwrap();
+        fs::create_dir_all(&wd).await.unwrap();
+        fs::write(
+            data_dir.join("skills").join("good.json"),
+            r#"{"name":"good","description":"移行できる","prompt_template":"{{input}}を要約","parameters":[]}"#,
+        )
+        .await
+        .unwrap();
+        fs::write(data_dir.join("skills").join("bad.json"), "{ not json").await.unwrap();
+
+        let store = SkillStore::new(data_dir.clone());
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+
+        let report = store.migrate_legacy_skills(false).await.unwrap();
+        assert_eq!(report.migrated, 1);
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].starts_with("bad.json"));
+        assert!(store.get("good").await.is_ok());
+        assert!(data_dir.join("skills").join("good.json.migrated").exists());
+        assert!(data_dir.join("skills").join("bad.json").exists());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[test]
+    fn test_migration_report_merge() {
+        let mut a = MigrationReport {
+            migrated: 1,
+            skipped: Vec::new(),
+            errors: vec!["a.json: x".to_string()],
+        };
+        a.merge(MigrationReport {
+            migrated: 2,
+            skipped: vec!["c.md: s".to_string()],
+            errors: vec!["b.md: y".to_string()],
+        });
+        assert_eq!(a.skipped.len(), 1);
+        assert_eq!(a.migrated, 3);
+        assert_eq!(a.errors.len(), 2);
+    }
+
+    async fn legacy_fixture() -> (PathBuf, SkillStore) {
+        let root = std::env::temp_dir().join(format!("cowork-skills-test-{}", uuid::Uuid::new_v4()));
+        let data_dir = root.join("data");
+        let wd = root.join("work");
+        fs::create_dir_all(data_dir.join("skills")).await.unwrap();
+        fs::create_dir_all(&wd).await.unwrap();
+        fs::write(
+            data_dir.join("skills").join("summary.json"),
+            r#"{"name":"summary","description":"移行版","prompt_template":"要約して","parameters":[]}"#,
+        )
+        .await
+        .unwrap();
+        let store = SkillStore::new(data_dir);
+        store.set_working_dir(wd.to_string_lossy().to_string()).await;
+        (root, store)
+    }
+
+    #[tokio::test]
+    async fn test_migration_skips_existing_skill() {
+        let (root, store) = legacy_fixture().await;
+        let handwritten = CoworkSkill {
+            name: "summary".to_string(),
+            description: "手書き版".to_string(),
+            body: "自分で書いた本文".to_string(),
+            source: SkillSource::Project,
+            arguments: Vec::new(),
+        };
+        store.save(&handwritten).await.unwrap();
+
+        let report = store.migrate_legacy_skills(false).await.unwrap();
+        assert_eq!(report.migrated, 0);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(store.get("summary").await.unwrap().description, "手書き版");
+        // The legacy file stays in place so it can be migrated later with overwrite
+        assert!(root.join("data").join("skills").join("summary.json").exists());
+
+        let report = store.migrate_legacy_skills(true).await.unwrap();
+        assert_eq!(report.migrated, 1);
+        assert_eq!(store.get("summary").await.unwrap().description, "移行版");
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
+
+    #[tokio::test]
+    async fn test_fresh_migration_renames_source() {
+        let (root, store) = legacy_fixture().await;
+
+        let report = store.migrate_legacy_skills(false).await.unwrap();
+        assert_eq!(report.migrated, 1);
+        assert!(report.skipped.is_empty());
+        assert!(report.errors.is_empty());
+        assert_eq!(store.get("summary").await.unwrap().description, "移行版");
+        let legacy = root.join("data").join("skills");
+        assert!(!legacy.join("summary.json").exists());
+        assert!(legacy.join("summary.json.migrated").exists());
+
+        // Running again is a no-op
+        let again = store.migrate_legacy_skills(false).await.unwrap();
+        assert_eq!(again.migrated, 0);
+        assert!(again.skipped.is_empty());
+
+        let _ = fs::remove_dir_all(&root).await;
+    }
 }
@@ -1,5 +1,6 @@
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use tokio::fs;
 
@@ -7,6 +8,8 @@ use crate::oauth_server;
 
 const AUTH_URL: &str = "https://slack.com/oauth/v2/authorize";
 const TOKEN_URL: &str = "https://slack.com/api/oauth.v2.access";
+/// Pause between item creations in a batch (slackLists.items.create is Tier 3, ~50/min)
+const BATCH_CREATE_INTERVAL: std::time::Duration = std::time::Duration::from_millis(1200);
 
 /// OAuth credentials (embedded or user-provided)
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,6 +26,108 @@ pub struct SlackTokens {
     pub team_name: Option<String>,
 }
 
+/// A connected workspace, as listed in the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackWorkspace {
+    pub team_id: String,
+    pub team_name: Option<String>,
+    pub active: bool,
+}
+
+/// On-disk format of slack_tokens.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct SlackTokenFile {
+    active_team_id: Option<String>,
+    workspaces: Vec<SlackTokens>,
+}
+
+/// Tokens of every authenticated workspace, keyed by team id
+#[derive(Debug, Default)]
+struct Workspaces {
+    tokens: HashMap<String, SlackTokens>,
+    active: Option<String>,
+}
+
+impl Workspaces {
+    fn key(tokens: &SlackTokens) -> String {
+        tokens.team_id.clone().unwrap_or_default()
+    }
+
+    fn active_tokens(&self) -> Option<&SlackTokens> {
+        self.tokens.get(self.active.as_deref()?)
+    }
+
+    /// Add or replace a workspace and make it the active one
+    fn insert(&mut self, tokens: SlackTokens) {
+        let key = Self::key(&tokens);
+        self.tokens.insert(key.clone(), tokens);
+        self.active = Some(key);
+    }
+
+    /// Drop a workspace; if it was active, another one (if any) takes over
+    fn remove(&mut self, team_id: &str) -> bool {
+        if self.tokens.remove(team_id).is_none() {
+            return false;
+        }
+        if self.active.as_deref() == Some(team_id) {
+            self.active = self.list().first().map(|w| w.team_id.clone());
+        }
+        true
+    }
+
+    /// Workspaces sorted by name
+    fn list(&self) -> Vec<SlackWorkspace> {
+        let mut list: Vec<SlackWorkspace> = self
+            .tokens
+            .iter()
+            .map(|(id, t)| SlackWorkspace {
+                team_id: id.clone(),
+                team_name: t.team_name.clone(),
+                active: self.active.as_deref() == Some(id.as_str()),
+            })
+            .collect();
+        list.sort_by(|a, b| a.team_name.cmp(&b.team_name).then(a.team_id.cmp(&b.team_id)));
+        list
+    }
+
+    fn from_file(file: SlackTokenFile) -> Self {
+        let mut workspaces = Self::default();
+        for tokens in file.workspaces {
+            workspaces.tokens.insert(Self::key(&tokens), tokens);
+        }
+        workspaces.active = file
+            .active_team_id
+            .filter(|id| workspaces.tokens.contains_key(id))
+            .or_else(|| workspaces.list().first().map(|w| w.team_id.clone()));
+        workspaces
+    }
+
+    fn to_file(&self) -> SlackTokenFile {
+        let workspaces = self
+            .list()
+            .into_iter()
+            .filter_map(|w| self.tokens.get(&w.team_id).cloned())
+            .collect();
+        SlackTokenFile {
+            active_team_id: self.active.clone(),
+            workspaces,
+        }
+    }
+}
+
+/// Parse slack_tokens.json, accepting the older single-workspace format
+fn parse_token_file(content: &str) -> Result<SlackTokenFile, String> {
+    if let Ok(file) = serde_json::from_str::<SlackTokenFile>(content) {
+        return Ok(file);
+    }
+    let single: SlackTokens = serde_json::from_str(content)
+        .map_err(|e| format!("Slack認証情報の形式が正しくありません: {}", e))?;
+    Ok(SlackTokenFile {
+        active_team_id: single.team_id.clone(),
+        workspaces: vec![single],
+    })
+}
+
 /// User-configurable settings (e.g. which list to use)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SlackSettings {
@@ -36,28 +141,61 @@ pub struct SlackListItem {
     pub completed: bool,
     pub assignee: Option<String>,
     pub due_date: Option<String>,
+    /// The item's raw `fields`, including any custom columns
+    #[serde(default)]
+    pub fields: serde_json::Value,
+}
+
+/// An item that could not be created during a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackBatchFailure {
+    pub title: String,
+    pub error: String,
+}
+
+/// Outcome of a batch create: what was created and what failed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlackBatchResult {
+    pub created: Vec<SlackListItem>,
+    pub failed: Vec<SlackBatchFailure>,
 }
 
+/// A channel message, simplified for giving Claude context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlackMessage {
+    pub user: Option<String>,
+    pub text: String,
+    pub ts: String,
+}
+
+/// Callback invoked with the new authentication state whenever tokens change
+pub type AuthListener = Box<dyn Fn(bool) + Send + Sync>;
+
 pub struct SlackClient {
-    http: Client,
+    http: std::sync::RwLock<Client>,
     data_dir: PathBuf,
     resource_dir: Option<PathBuf>,
     oauth_config: tokio::sync::Mutex<Option<SlackOAuthConfig>>,
-    tokens: tokio::sync::Mutex<Option<SlackTokens>>,
+    tokens: tokio::sync::Mutex<Workspaces>,
     settings: tokio::sync::Mutex<SlackSettings>,
+    auth_listener: std::sync::Mutex<Option<AuthListener>>,
+    /// Cancel handle of the OAuth flow currently waiting for a callback
+    auth_flow: tokio::sync::Mutex<Option<oauth_server::OAuthCancelHandle>>,
 }
 
 impl SlackClient {
     pub fn new(data_dir: PathBuf, resource_dir: Option<PathBuf>) -> Self {
         Self {
-            http: Client::new(),
+            http: std::sync::RwLock::new(Client::new()),
             data_dir,
             resource_dir,
             oauth_config: tokio::sync::Mutex::new(None),
-            tokens: tokio::sync::Mutex::new(None),
+            tokens: tokio::sync::Mutex::new(Workspaces::default()),
             settings: tokio::sync::Mutex::new(SlackSettings {
                 default_list_id: None,
             }),
+            auth_listener: std::sync::Mutex::new(None),
+            auth_flow: tokio::sync::Mutex::new(None),
         }
     }
 
@@ -73,9 +211,7 @@ impl SlackClient {
             let content = fs::read_to_string(&tokens_path)
                 .await
                 .map_err(|e| format!("Slack認証情報を読み込めませんでした: {}", e))?;
-            let tokens: SlackTokens = serde_json::from_str(&content)
-                .map_err(|e| format!("Slack認証情報の形式が正しくありません: {}", e))?;
-            *self.tokens.lock().await = Some(tokens);
+            *self.tokens.lock().await = Workspaces::from_file(parse_token_file(&content)?);
         }
 
         // Load settings
@@ -119,18 +255,55 @@ impl SlackClient {
         None
     }
 
+    /// Replace the HTTP client (e.g. after proxy settings change)
+    pub fn set_http_client(&self, client: Client) {
+        *self.http.write().unwrap() = client;
+    }
+
+    fn http(&self) -> Client {
+        self.http.read().unwrap().clone()
+    }
+
+    /// Register a callback notified when tokens are saved, refreshed, or cleared.
+    pub fn set_auth_listener(&self, listener: AuthListener) {
+        *self.auth_listener.lock().unwrap() = Some(listener);
+    }
+
+    fn notify_auth_state(&self, authenticated: bool) {
+        if let Some(listener) = self.auth_listener.lock().unwrap().as_ref() {
+            listener(authenticated);
+        }
+    }
+
+    /// Add (or refresh) a workspace's tokens and make it the active one
     async fn save_tokens(&self, tokens: &SlackTokens) -> Result<(), String> {
+        let mut workspaces = self.tokens.lock().await;
+        workspaces.insert(tokens.clone());
+        self.write_tokens(&workspaces).await?;
+        drop(workspaces);
+        self.notify_auth_state(true);
+        Ok(())
+    }
+
+    /// Persist every workspace; the file is removed once none are left
+    async fn write_tokens(&self, workspaces: &Workspaces) -> Result<(), String> {
+        let path = self.data_dir.join("slack_tokens.json");
+        if workspaces.tokens.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .await
+                    .map_err(|e| format!("ログアウトに失敗しました: {}", e))?;
+            }
+            return Ok(());
+        }
         fs::create_dir_all(&self.data_dir)
             .await
             .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
-        let path = self.data_dir.join("slack_tokens.json");
-        let content = serde_json::to_string_pretty(tokens)
+        let content = serde_json::to_string_pretty(&workspaces.to_file())
             .map_err(|e| format!("データの変換に失敗しました: {}", e))?;
         fs::write(&path, &content)
             .await
-            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
-        *self.tokens.lock().await = Some(tokens.clone());
-        Ok(())
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))
     }
 
     pub async fn save_settings(&self, settings: SlackSettings) -> Result<(), String> {
@@ -147,16 +320,55 @@ impl SlackClient {
         Ok(())
     }
 
+    /// Write user-provided OAuth credentials to the data dir and apply them
+    /// without requiring a restart.
+    pub async fn set_oauth_config(&self, client_id: &str, client_secret: &str) -> Result<(), String> {
+        let client_id = client_id.trim();
+        let client_secret = client_secret.trim();
+        if client_id.is_empty() || client_secret.is_empty() {
+            return Err("クライアントIDとクライアントシークレットを入力してください".to_string());
+        }
+        let config = SlackOAuthConfig {
+            client_id: client_id.to_string(),
+            client_secret: client_secret.to_string(),
+        };
+        fs::create_dir_all(&self.data_dir)
+            .await
+            .map_err(|e| format!("フォルダを作成できませんでした: {}", e))?;
+        let path = self.data_dir.join("slack_oauth_config.json");
+        let content = serde_json::to_string_pretty(&config)
+            .map_err(|e| format!("データの変換に失敗しました: {}", e))?;
+        fs::write(&path, &content)
+            .await
+            .map_err(|e| format!("ファイルの書き込みに失敗しました: {}", e))?;
+        *self.oauth_config.lock().await = Some(config);
+        Ok(())
+    }
+
     pub async fn is_configured(&self) -> bool {
         self.oauth_config.lock().await.is_some()
     }
 
     pub async fn is_authenticated(&self) -> bool {
-        self.tokens.lock().await.is_some()
+        self.tokens.lock().await.active_tokens().is_some()
     }
 
     pub async fn get_team_name(&self) -> Option<String> {
-        self.tokens.lock().await.as_ref()?.team_name.clone()
+        self.tokens.lock().await.active_tokens()?.team_name.clone()
+    }
+
+    pub async fn list_workspaces(&self) -> Vec<SlackWorkspace> {
+        self.tokens.lock().await.list()
+    }
+
+    /// Switch the workspace used by the List and channel APIs
+    pub async fn set_active_workspace(&self, team_id: &str) -> Result<(), String> {
+        let mut workspaces = self.tokens.lock().await;
+        if !workspaces.tokens.contains_key(team_id) {
+            return Err(format!("ワークスペースが見つかりません: {}", team_id));
+        }
+        workspaces.active = Some(team_id.to_string());
+        self.write_tokens(&workspaces).await
     }
 
     pub async fn get_settings(&self) -> SlackSettings {
@@ -172,10 +384,12 @@ impl SlackClient {
             .as_ref()
             .ok_or("SlackのOAuth設定が見つかりません。開発者に連絡してください。")?;
 
-        let (port, rx) = oauth_server::wait_for_oauth_callback().await?;
+        let (port, rx, handle) = oauth_server::wait_for_oauth_callback().await?;
+        // Starting a new flow replaces (and shuts down) any previous one
+        *self.auth_flow.lock().await = Some(handle);
 
-        // Bot scopes for Lists API
-        let scopes = "lists:read,lists:write";
+        // Bot scopes for Lists API and reading channel history
+        let scopes = "lists:read,lists:write,channels:history,groups:history";
         let url = format!(
             "{}?client_id={}&redirect_uri={}&scope={}&response_type=code",
             AUTH_URL,
@@ -187,6 +401,27 @@ impl SlackClient {
         Ok((url, port, rx))
     }
 
+    /// Abort the in-flight OAuth flow. Its receiver resolves with a
+    /// cancellation error. Returns false if no flow was in progress.
+    pub async fn cancel_auth_flow(&self) -> bool {
+        match self.auth_flow.lock().await.take() {
+            Some(handle) => {
+                handle.cancel().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Release the callback server once the flow on `port` has finished.
+    /// A newer flow started in the meantime is left untouched.
+    pub async fn finish_auth_flow(&self, port: u16) {
+        let mut flow = self.auth_flow.lock().await;
+        if flow.as_ref().is_some_and(|h| h.port() == port) {
+            flow.take();
+        }
+    }
+
     /// Exchange an authorization code for a bot token.
     pub async fn exchange_code(&self, code: &str, port: u16) -> Result<(), String> {
         let (client_id, client_secret) = {
@@ -196,7 +431,7 @@ impl SlackClient {
         };
 
         let resp = self
-            .http
+            .http()
             .post(TOKEN_URL)
             .form(&[
                 ("code", code),
@@ -249,30 +484,44 @@ impl SlackClient {
         Ok(())
     }
 
+    /// Log out of the active workspace
     pub async fn logout(&self) -> Result<(), String> {
-        let path = self.data_dir.join("slack_tokens.json");
-        if path.exists() {
-            fs::remove_file(&path)
-                .await
-                .map_err(|e| format!("ログアウトに失敗しました: {}", e))?;
+        let active = self.tokens.lock().await.active.clone();
+        match active {
+            Some(team_id) => self.logout_workspace(&team_id).await,
+            None => Ok(()),
         }
-        *self.tokens.lock().await = None;
+    }
+
+    /// Forget one workspace's tokens; the others stay signed in
+    pub async fn logout_workspace(&self, team_id: &str) -> Result<(), String> {
+        let mut workspaces = self.tokens.lock().await;
+        if !workspaces.remove(team_id) {
+            return Ok(());
+        }
+        self.write_tokens(&workspaces).await?;
+        let authenticated = workspaces.active_tokens().is_some();
+        drop(workspaces);
+        self.notify_auth_state(authenticated);
         Ok(())
     }
 
-    fn get_bot_token(&self, tokens: &SlackTokens) -> String {
-        tokens.bot_token.clone()
+    /// Bot token of the active workspace
+    async fn active_token(&self) -> Result<String, String> {
+        self.tokens
+            .lock()
+            .await
+            .active_tokens()
+            .map(|t| t.bot_token.clone())
+            .ok_or_else(|| "Slackの認証が必要です".to_string())
     }
 
     /// Fetch items from a Slack List.
     pub async fn list_items(&self, list_id: &str) -> Result<Vec<SlackListItem>, String> {
-        let tokens = self.tokens.lock().await;
-        let tokens = tokens.as_ref().ok_or("Slackの認証が必要です")?;
-        let token = self.get_bot_token(tokens);
-        let _ = tokens;
+        let token = self.active_token().await?;
 
         let resp = self
-            .http
+            .http()
             .post("https://slack.com/api/slackLists.items.list")
             .bearer_auth(&token)
             .json(&serde_json::json!({ "list_id": list_id }))
@@ -296,58 +545,80 @@ impl SlackClient {
         let items = body
             .get("items")
             .and_then(|v| v.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|item| {
-                        let id = item.get("id")?.as_str()?.to_string();
-                        let title = item
-                            .get("title")
-                            .and_then(|v| v.as_str())
-                            .unwrap_or("(無題)")
-                            .to_string();
-                        let fields = item.get("fields").cloned().unwrap_or_default();
-                        let completed = fields
-                            .get("completion")
-                            .and_then(|v| v.get("checked"))
-                            .and_then(|v| v.as_bool())
-                            .unwrap_or(false);
-                        let assignee = fields
-                            .get("assignee")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-                        let due_date = fields
-                            .get("due_date")
-                            .and_then(|v| v.as_str())
-                            .map(|s| s.to_string());
-
-                        Some(SlackListItem {
-                            id,
-                            title,
-                            completed,
-                            assignee,
-                            due_date,
-                        })
-                    })
-                    .collect()
-            })
+            .map(|arr| arr.iter().filter_map(parse_list_item).collect())
             .unwrap_or_default();
 
         Ok(items)
     }
 
+    /// Fetch a single List item with all of its fields, as returned by Slack.
+    pub async fn get_item(&self, list_id: &str, item_id: &str) -> Result<serde_json::Value, String> {
+        let token = self.active_token().await?;
+
+        let resp = self
+            .http()
+            .post("https://slack.com/api/slackLists.items.info")
+            .bearer_auth(&token)
+            .json(&serde_json::json!({ "list_id": list_id, "id": item_id }))
+            .send()
+            .await
+            .map_err(|e| format!("Slack APIエラー: {}", e))?;
+
+        let body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("応答の解析に失敗しました: {}", e))?;
+
+        if body.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+            let err = body
+                .get("error")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown");
+            return Err(format!("Slack APIエラー: {}", err));
+        }
+
+        body.get("record")
+            .or_else(|| body.get("item"))
+            .cloned()
+            .ok_or_else(|| "アイテムが見つかりません".to_string())
+    }
+
+    /// Fetch the most recent messages of a channel (newest first).
+    pub async fn conversations_history(
+        &self,
+        channel: &str,
+        limit: usize,
+    ) -> Result<Vec<SlackMessage>, String> {
+        let token = self.active_token().await?;
+
+        let limit = limit.clamp(1, 200).to_string();
+        let resp = self
+            .http()
+            .post("https://slack.com/api/conversations.history")
+            .bearer_auth(&token)
+            .form(&[("channel", channel), ("limit", limit.as_str())])
+            .send()
+            .await
+            .map_err(|e| format!("Slack APIエラー: {}", e))?;
+
+        let body: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| format!("応答の解析に失敗しました: {}", e))?;
+
+        parse_history(&body)
+    }
+
     /// Create a new item in a Slack List.
     pub async fn create_item(
         &self,
         list_id: &str,
         title: &str,
     ) -> Result<SlackListItem, String> {
-        let tokens = self.tokens.lock().await;
-        let tokens = tokens.as_ref().ok_or("Slackの認証が必要です")?;
-        let token = self.get_bot_token(tokens);
-        let _ = tokens;
+        let token = self.active_token().await?;
 
         let resp = self
-            .http
+            .http()
             .post("https://slack.com/api/slackLists.items.create")
             .bearer_auth(&token)
             .json(&serde_json::json!({
@@ -384,8 +655,130 @@ impl SlackClient {
             completed: false,
             assignee: None,
             due_date: None,
+            fields: serde_json::Value::Null,
         })
     }
+
+    /// Create several items one after another, pausing between requests to
+    /// stay under the rate limit. Failures are collected rather than aborting.
+    pub async fn create_items(
+        &self,
+        list_id: &str,
+        titles: Vec<String>,
+    ) -> Result<SlackBatchResult, String> {
+        if !self.is_authenticated().await {
+            return Err("Slackの認証が必要です".to_string());
+        }
+        Ok(create_sequentially(titles, BATCH_CREATE_INTERVAL, |title| async move {
+            self.create_item(list_id, &title).await
+        })
+        .await)
+    }
+}
+
+/// Map one entry of `slackLists.items.list` to a `SlackListItem`
+fn parse_list_item(item: &serde_json::Value) -> Option<SlackListItem> {
+    let id = item.get("id")?.as_str()?.to_string();
+    let title = item
+        .get("title")
+        .and_then(|v| v.as_str())
+        .unwrap_or("(無題)")
+        .to_string();
+    let fields = item.get("fields").cloned().unwrap_or_default();
+    let completed = fields
+        .get("completion")
+        .and_then(|v| v.get("checked"))
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    let assignee = fields
+        .get("assignee")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+    let due_date = fields
+        .get("due_date")
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string());
+
+    Some(SlackListItem {
+        id,
+        title,
+        completed,
+        assignee,
+        due_date,
+        fields,
+    })
+}
+
+/// Run `create` for each title in order, collecting successes and failures
+async fn create_sequentially<F, Fut>(
+    titles: Vec<String>,
+    interval: std::time::Duration,
+    create: F,
+) -> SlackBatchResult
+where
+    F: Fn(String) -> Fut,
+    Fut: std::future::Future<Output = Result<SlackListItem, String>>,
+{
+    let mut result = SlackBatchResult::default();
+    for (i, title) in titles.into_iter().enumerate() {
+        if i > 0 && !interval.is_zero() {
+            tokio::time::sleep(interval).await;
+        }
+        match create(title.clone()).await {
+            Ok(item) => result.created.push(item),
+            Err(error) => {
+                log::warn!("Failed to create Slack list item {:?}: {}", title, error);
+                result.failed.push(SlackBatchFailure { title, error });
+            }
+        }
+    }
+    result
+}
+
+/// Extract messages from a `conversations.history` response
+fn parse_history(body: &serde_json::Value) -> Result<Vec<SlackMessage>, String> {
+    if body.get("ok").and_then(|v| v.as_bool()) != Some(true) {
+        let err = body
+            .get("error")
+            .and_then(|v| v.as_str())
+            .unwrap_or("unknown");
+        return Err(match err {
+            "not_in_channel" => {
+                "アプリがこのチャンネルに参加していません。チャンネルにアプリを追加してください。"
+                    .to_string()
+            }
+            "channel_not_found" => "チャンネルが見つかりません".to_string(),
+            "missing_scope" => {
+                "チャンネル履歴を読む権限がありません。Slackに再接続してください。".to_string()
+            }
+            _ => format!("Slack APIエラー: {}", err),
+        });
+    }
+
+    let messages = body
+        .get("messages")
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|m| {
+                    let ts = m.get("ts")?.as_str()?.to_string();
+                    let text = m
+                        .get("text")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or("")
+                        .to_string();
+                    let user = m
+                        .get("user")
+                        .or_else(|| m.get("bot_id"))
+                        .and_then(|v| v.as_str())
+                        .map(|s| s.to_string());
+                    Some(SlackMessage { user, text, ts })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(messages)
 }
 
 fn urlencoding(s: &str) -> String {
@@ -472,6 +865,7 @@ mod tests {
             completed: false,
             assignee: Some("U123".to_string()),
             due_date: Some("2026-03-01".to_string()),
+            fields: serde_json::Value::Null,
         };
         let json = serde_json::to_string(&item).unwrap();
         let parsed: SlackListItem = serde_json::from_str(&json).unwrap();
@@ -488,6 +882,7 @@ mod tests {
             completed: true,
             assignee: None,
             due_date: None,
+            fields: serde_json::Value::Null,
         };
         let json = serde_json::to_string(&item).unwrap();
         let parsed: SlackListItem = serde_json::from_str(&json).unwrap();
@@ -496,6 +891,31 @@ mod tests {
         assert!(parsed.due_date.is_none());
     }
 
+    #[test]
+    fn test_parse_list_item_keeps_custom_fields() {
+        let raw = serde_json::json!({
+            "id": "Rec01",
+            "title": "見積書を送る",
+            "fields": {
+                "completion": {"checked": true},
+                "assignee": "U123",
+                "due_date": "2026-05-01",
+                "Col_priority": {"select": ["高"]}
+            }
+        });
+        let item = parse_list_item(&raw).unwrap();
+        assert!(item.completed);
+        assert_eq!(item.assignee.as_deref(), Some("U123"));
+        assert_eq!(item.due_date.as_deref(), Some("2026-05-01"));
+        assert_eq!(item.fields["Col_priority"]["select"][0], "高");
+
+        // Items serialized before the catch-all existed still deserialize
+        let old = r#"{"id":"a","title":"t","completed":false,"assignee":null,"due_date":null}"#;
+        assert!(serde_json::from_str::<SlackListItem>(old).unwrap().fields.is_null());
+
+        assert!(parse_list_item(&serde_json::json!({"title": "idなし"})).is_none());
+    }
+
     #[test]
     fn test_urlencoding() {
         assert_eq!(urlencoding("hello"), "hello");
@@ -658,6 +1078,70 @@ mod tests {
         let _ = fs::remove_dir_all(&dir).await;
     }
 
+    fn workspace_tokens(team_id: &str, team_name: &str) -> SlackTokens {
+        SlackTokens {
+            bot_token: format!("xoxb-{}", team_id),
+            team_id: Some(team_id.to_string()),
+            team_name: Some(team_name.to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slack_multiple_workspaces() {
+        let dir = temp_dir();
+        let client = SlackClient::new(dir.clone(), None);
+        client.save_tokens(&workspace_tokens("T1", "営業部")).await.unwrap();
+        client.save_tokens(&workspace_tokens("T2", "開発部")).await.unwrap();
+
+        // The most recently added workspace becomes active
+        assert_eq!(client.get_team_name().await.as_deref(), Some("開発部"));
+        assert_eq!(client.active_token().await.unwrap(), "xoxb-T2");
+        assert_eq!(client.list_workspaces().await.len(), 2);
+
+        client.set_active_workspace("T1").await.unwrap();
+        assert_eq!(client.active_token().await.unwrap(), "xoxb-T1");
+        assert!(client.set_active_workspace("T404").await.is_err());
+
+        // Both workspaces and the active pointer survive a reload
+        let reloaded = SlackClient::new(dir.clone(), None);
+        reloaded.load().await.unwrap();
+        let workspaces = reloaded.list_workspaces().await;
+        assert_eq!(workspaces.len(), 2);
+        assert!(workspaces.iter().any(|w| w.team_id == "T1" && w.active));
+        assert!(workspaces.iter().any(|w| w.team_id == "T2" && !w.active));
+
+        // Logging out of one leaves the other signed in
+        reloaded.logout_workspace("T2").await.unwrap();
+        assert!(reloaded.is_authenticated().await);
+        assert_eq!(reloaded.active_token().await.unwrap(), "xoxb-T1");
+        reloaded.logout_workspace("T1").await.unwrap();
+        assert!(!reloaded.is_authenticated().await);
+        assert!(!dir.join("slack_tokens.json").exists());
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_slack_logout_active_switches_to_remaining() {
+        let client = SlackClient::new(temp_dir(), None);
+        client.save_tokens(&workspace_tokens("T1", "営業部")).await.unwrap();
+        client.save_tokens(&workspace_tokens("T2", "開発部")).await.unwrap();
+
+        client.logout().await.unwrap();
+        assert_eq!(client.get_team_name().await.as_deref(), Some("営業部"));
+        let _ = fs::remove_dir_all(&client.data_dir).await;
+    }
+
+    #[test]
+    fn test_parse_legacy_single_token_file() {
+        let legacy = r#"{"bot_token":"xoxb-old","team_id":"T9","team_name":"旧形式"}"#;
+        let workspaces = Workspaces::from_file(parse_token_file(legacy).unwrap());
+        assert_eq!(workspaces.active.as_deref(), Some("T9"));
+        assert_eq!(workspaces.active_tokens().unwrap().bot_token, "xoxb-old");
+
+        assert!(parse_token_file("not json").is_err());
+    }
+
     #[tokio::test]
     async fn test_slack_client_logout_no_tokens() {
         let client = SlackClient::new(temp_dir(), None);
@@ -717,4 +1201,123 @@ mod tests {
         let _ = fs::remove_dir_all(&data_dir).await;
         let _ = fs::remove_dir_all(&resource_dir).await;
     }
+
+    #[tokio::test]
+    async fn test_slack_client_set_oauth_config() {
+        let dir = temp_dir();
+        let client = SlackClient::new(dir.clone(), None);
+        assert!(!client.is_configured().await);
+
+        client.set_oauth_config("my-client-id", "my-secret").await.unwrap();
+        assert!(client.is_configured().await);
+        assert!(dir.join("slack_oauth_config.json").exists());
+
+        // A fresh client picks the written config up on load
+        let reloaded = SlackClient::new(dir.clone(), None);
+        reloaded.load().await.unwrap();
+        assert!(reloaded.is_configured().await);
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_slack_client_set_oauth_config_rejects_empty() {
+        let dir = temp_dir();
+        let client = SlackClient::new(dir.clone(), None);
+        assert!(client.set_oauth_config("  ", "secret").await.is_err());
+        assert!(client.set_oauth_config("id", "").await.is_err());
+        assert!(!client.is_configured().await);
+        assert!(!dir.join("slack_oauth_config.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_slack_client_auth_listener_notified() {
+        let dir = temp_dir();
+        let client = SlackClient::new(dir.clone(), None);
+        let events = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let events_ref = events.clone();
+        client.set_auth_listener(Box::new(move |authenticated| {
+            events_ref.lock().unwrap().push(authenticated);
+        }));
+
+        let tokens = SlackTokens {
+            bot_token: "xoxb-listener".to_string(),
+            team_id: None,
+            team_name: None,
+        };
+        client.save_tokens(&tokens).await.unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![true]);
+
+        client.logout().await.unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![true, false]);
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_slack_client_cancel_auth_flow() {
+        let dir = temp_dir();
+        let client = SlackClient::new(dir.clone(), None);
+        assert!(!client.cancel_auth_flow().await);
+
+        client.set_oauth_config("my-client-id", "my-secret").await.unwrap();
+        let (_url, _port, rx) = client.start_auth_flow().await.unwrap();
+        assert!(client.cancel_auth_flow().await);
+
+        let result = rx.await.unwrap();
+        assert_eq!(result.unwrap_err(), oauth_server::CANCELLED_MESSAGE);
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn test_parse_history() {
+        let body = serde_json::json!({
+            "ok": true,
+            "messages": [
+                {"type": "message", "user": "U123", "text": "デプロイ完了しました", "ts": "1700000002.000200"},
+                {"type": "message", "bot_id": "B9", "text": "ビルド成功", "ts": "1700000001.000100"},
+                {"type": "message", "text": "tsなしは無視"}
+            ],
+            "has_more": false
+        });
+        let messages = parse_history(&body).unwrap();
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].user.as_deref(), Some("U123"));
+        assert_eq!(messages[0].text, "デプロイ完了しました");
+        assert_eq!(messages[0].ts, "1700000002.000200");
+        assert_eq!(messages[1].user.as_deref(), Some("B9"));
+    }
+
+    #[test]
+    fn test_parse_history_not_in_channel() {
+        let body = serde_json::json!({"ok": false, "error": "not_in_channel"});
+        let err = parse_history(&body).unwrap_err();
+        assert!(err.contains("参加していません"));
+    }
+
+    #[tokio::test]
+    async fn test_create_sequentially_collects_partial_failures() {
+        let titles = vec!["資料作成".to_string(), "失敗する".to_string(), "レビュー".to_string()];
+        let result = create_sequentially(titles, std::time::Duration::ZERO, |title| async move {
+            if title == "失敗する" {
+                return Err("Slack APIエラー: ratelimited".to_string());
+            }
+            Ok(SlackListItem {
+                id: format!("id-{}", title),
+                title,
+                completed: false,
+                assignee: None,
+                due_date: None,
+                fields: serde_json::Value::Null,
+            })
+        })
+        .await;
+
+        let created: Vec<&str> = result.created.iter().map(|i| i.title.as_str()).collect();
+        assert_eq!(created, vec!["資料作成", "レビュー"]);
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.failed[0].title, "失敗する");
+        assert!(result.failed[0].error.contains("ratelimited"));
+    }
 }
@@ -0,0 +1,175 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+
+/// A long-lived background task, as shown to the user
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskInfo {
+    pub id: String,
+    pub label: String,
+    pub started_at: String,
+}
+
+type CancelFn = Box<dyn FnOnce() + Send>;
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: CancelFn,
+}
+
+/// Registry of spawned background tasks (OAuth flows, servers, ...).
+/// Tasks unregister themselves when they finish.
+#[derive(Default)]
+pub struct TaskRegistry {
+    tasks: Arc<Mutex<HashMap<String, TaskEntry>>>,
+}
+
+impl TaskRegistry {
+    /// Spawn `fut` and track it under `label`; cancelling aborts it.
+    pub fn spawn<F>(&self, label: &str, fut: F) -> String
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_inner(label, fut, None)
+    }
+
+    /// Like `spawn`, but cancelling runs `cancel` instead of aborting, so the
+    /// task can wind down itself (e.g. report "cancelled" to the UI).
+    pub fn spawn_with_cancel<F, C>(&self, label: &str, fut: F, cancel: C) -> String
+    where
+        F: Future<Output = ()> + Send + 'static,
+        C: FnOnce() + Send + 'static,
+    {
+        self.spawn_inner(label, fut, Some(Box::new(cancel)))
+    }
+
+    fn spawn_inner<F>(&self, label: &str, fut: F, cancel: Option<CancelFn>) -> String
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let id = uuid::Uuid::new_v4().to_string();
+        let tasks = Arc::clone(&self.tasks);
+        let task_id = id.clone();
+
+        // Register before the task can run to completion and unregister itself
+        let mut map = self.tasks.lock().unwrap();
+        let handle = tokio::spawn(async move {
+            fut.await;
+            tasks.lock().unwrap().remove(&task_id);
+        });
+        let cancel: CancelFn = match cancel {
+            Some(cancel) => cancel,
+            None => {
+                let abort = handle.abort_handle();
+                Box::new(move || abort.abort())
+            }
+        };
+        map.insert(
+            id.clone(),
+            TaskEntry {
+                info: TaskInfo {
+                    id: id.clone(),
+                    label: label.to_string(),
+                    started_at: chrono::Utc::now().to_rfc3339(),
+                },
+                cancel,
+            },
+        );
+        id
+    }
+
+    /// Running tasks, oldest first
+    pub fn list(&self) -> Vec<TaskInfo> {
+        let mut tasks: Vec<TaskInfo> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .values()
+            .map(|e| e.info.clone())
+            .collect();
+        tasks.sort_by(|a, b| a.started_at.cmp(&b.started_at));
+        tasks
+    }
+
+    /// Cancel a task. Returns false if it already finished or is unknown.
+    pub fn cancel(&self, id: &str) -> bool {
+        let entry = self.tasks.lock().unwrap().remove(id);
+        match entry {
+            Some(entry) => {
+                log::info!("Cancelling task: {} ({})", entry.info.label, id);
+                (entry.cancel)();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::oneshot;
+
+    #[tokio::test]
+    async fn test_cancel_aborts_and_removes() {
+        let registry = TaskRegistry::default();
+        let (tx, rx) = oneshot::channel::<()>();
+        let id = registry.spawn("dummy", async move {
+            // Held until the task is dropped by the abort
+            let _tx = tx;
+            std::future::pending::<()>().await;
+        });
+
+        let listed = registry.list();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, id);
+        assert_eq!(listed[0].label, "dummy");
+
+        assert!(registry.cancel(&id));
+        assert!(registry.list().is_empty());
+        // The sender is dropped only if the future was aborted
+        assert!(rx.await.is_err());
+
+        assert!(!registry.cancel(&id));
+    }
+
+    #[tokio::test]
+    async fn test_finished_task_unregisters() {
+        let registry = TaskRegistry::default();
+        let (tx, rx) = oneshot::channel::<()>();
+        registry.spawn("quick", async move {
+            let _ = rx.await;
+        });
+        assert_eq!(registry.list().len(), 1);
+
+        tx.send(()).unwrap();
+        for _ in 0..50 {
+            if registry.list().is_empty() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(registry.list().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_cancel_runs() {
+        let registry = TaskRegistry::default();
+        let (stop_tx, stop_rx) = oneshot::channel::<()>();
+        let (done_tx, done_rx) = oneshot::channel::<&str>();
+        let id = registry.spawn_with_cancel(
+            "auth",
+            async move {
+                let _ = stop_rx.await;
+                let _ = done_tx.send("wound down");
+            },
+            move || {
+                let _ = stop_tx.send(());
+            },
+        );
+
+        assert!(registry.cancel(&id));
+        assert_eq!(done_rx.await.unwrap(), "wound down");
+    }
+}
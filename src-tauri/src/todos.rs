@@ -82,6 +82,53 @@ impl TodoManager {
         Ok(item)
     }
 
+    /// Like `add`, but returns None without inserting when an incomplete item
+    /// with the same text already exists
+    pub async fn add_unique(
+        &self,
+        text: String,
+        due_date: Option<String>,
+    ) -> Result<Option<TodoItem>, String> {
+        let exists = self
+            .items
+            .lock()
+            .await
+            .iter()
+            .any(|i| !i.done && i.text == text);
+        if exists {
+            return Ok(None);
+        }
+        self.add(text, due_date).await.map(Some)
+    }
+
+    /// Add several (text, due_date) entries at once, skipping any whose text
+    /// matches an existing item (or an earlier entry). Returns the number added.
+    pub async fn import_items(&self, entries: Vec<(String, Option<String>)>) -> Result<usize, String> {
+        let added = {
+            let mut items = self.items.lock().await;
+            let mut added = 0;
+            for (text, due_date) in entries {
+                let text = text.trim().to_string();
+                if text.is_empty() || items.iter().any(|i| i.text == text) {
+                    continue;
+                }
+                items.push(TodoItem {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    text,
+                    done: false,
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    due_date,
+                });
+                added += 1;
+            }
+            added
+        };
+        if added > 0 {
+            self.save().await?;
+        }
+        Ok(added)
+    }
+
     pub async fn toggle(&self, id: &str) -> Result<Option<TodoItem>, String> {
         let toggled = {
             let mut items = self.items.lock().await;
@@ -111,6 +158,39 @@ impl TodoManager {
         Ok(removed)
     }
 
+    /// Add the `- [ ]` / `- [x]` lines of a Markdown document as todos,
+    /// keeping their checked state. Other lines, and items whose text already
+    /// exists, are ignored. Returns the number added.
+    pub async fn import_markdown(&self, markdown: &str) -> Result<usize, String> {
+        let added = {
+            let mut items = self.items.lock().await;
+            let mut added = 0;
+            for (text, done) in parse_markdown_checkboxes(markdown) {
+                if items.iter().any(|i| i.text == text) {
+                    continue;
+                }
+                items.push(TodoItem {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    text,
+                    done,
+                    created_at: chrono::Utc::now().to_rfc3339(),
+                    due_date: None,
+                });
+                added += 1;
+            }
+            added
+        };
+        if added > 0 {
+            self.save().await?;
+        }
+        Ok(added)
+    }
+
+    /// Serialize all items as "markdown", "csv" or "json"
+    pub async fn export(&self, format: &str) -> Result<String, String> {
+        format_todos(&self.items.lock().await, format)
+    }
+
     #[allow(dead_code)]
     pub async fn update_text(&self, id: &str, text: String) -> Result<Option<TodoItem>, String> {
         let updated = {
@@ -129,6 +209,64 @@ impl TodoManager {
     }
 }
 
+/// (text, done) of each checkbox list item (`- [ ]`, `* [x]`, ...)
+fn parse_markdown_checkboxes(markdown: &str) -> Vec<(String, bool)> {
+    markdown
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim_start();
+            let rest = rest
+                .strip_prefix("- ")
+                .or_else(|| rest.strip_prefix("* "))
+                .or_else(|| rest.strip_prefix("+ "))?;
+            let (done, text) = if let Some(text) = rest.strip_prefix("[ ]") {
+                (false, text)
+            } else if let Some(text) = rest.strip_prefix("[x]").or_else(|| rest.strip_prefix("[X]")) {
+                (true, text)
+            } else {
+                return None;
+            };
+            let text = text.trim();
+            (!text.is_empty()).then(|| (text.to_string(), done))
+        })
+        .collect()
+}
+
+/// Render todos in an export format
+fn format_todos(items: &[TodoItem], format: &str) -> Result<String, String> {
+    match format {
+        "markdown" => Ok(items
+            .iter()
+            .map(|i| format!("- [{}] {}\n", if i.done { "x" } else { " " }, i.text))
+            .collect()),
+        "csv" => {
+            let mut out = String::from("text,done,due_date,created_at\n");
+            for i in items {
+                out.push_str(&format!(
+                    "{},{},{},{}\n",
+                    csv_field(&i.text),
+                    i.done,
+                    csv_field(i.due_date.as_deref().unwrap_or("")),
+                    csv_field(&i.created_at),
+                ));
+            }
+            Ok(out)
+        }
+        "json" => serde_json::to_string_pretty(items)
+            .map_err(|e| format!("TODOリストの変換に失敗しました: {}", e)),
+        other => Err(format!("対応していない形式です: {}", other)),
+    }
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,6 +372,122 @@ mod tests {
         assert_eq!(item.due_date, Some("2026-03-01".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_add_unique() {
+        let mgr = temp_manager();
+        assert!(mgr.add_unique("週報を書く".to_string(), None).await.unwrap().is_some());
+        assert!(mgr.add_unique("週報を書く".to_string(), None).await.unwrap().is_none());
+        assert_eq!(mgr.list().await.len(), 1);
+
+        // A completed item with the same text doesn't block a new one
+        let id = mgr.list().await[0].id.clone();
+        mgr.toggle(&id).await.unwrap();
+        assert!(mgr.add_unique("週報を書く".to_string(), None).await.unwrap().is_some());
+        assert_eq!(mgr.list().await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_items_skips_duplicates() {
+        let mgr = temp_manager();
+        mgr.add("既存タスク".to_string(), None).await.unwrap();
+
+        let added = mgr
+            .import_items(vec![
+                ("既存タスク".to_string(), None),
+                ("新規タスク".to_string(), Some("2026-04-01".to_string())),
+                ("新規タスク".to_string(), None),
+                ("  ".to_string(), None),
+            ])
+            .await
+            .unwrap();
+        assert_eq!(added, 1);
+
+        let list = mgr.list().await;
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[1].text, "新規タスク");
+        assert_eq!(list[1].due_date.as_deref(), Some("2026-04-01"));
+    }
+
+    fn export_fixture() -> Vec<TodoItem> {
+        vec![
+            TodoItem {
+                id: "1".to_string(),
+                text: "資料作成, レビュー依頼".to_string(),
+                done: false,
+                created_at: "2026-02-07T00:00:00Z".to_string(),
+                due_date: Some("2026-03-01".to_string()),
+            },
+            TodoItem {
+                id: "2".to_string(),
+                text: "\"見積\"を送る".to_string(),
+                done: true,
+                created_at: "2026-02-08T00:00:00Z".to_string(),
+                due_date: None,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_export_markdown() {
+        let md = format_todos(&export_fixture(), "markdown").unwrap();
+        assert_eq!(md, "- [ ] 資料作成, レビュー依頼\n- [x] \"見積\"を送る\n");
+    }
+
+    #[test]
+    fn test_export_csv_escapes() {
+        let csv = format_todos(&export_fixture(), "csv").unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "text,done,due_date,created_at");
+        assert_eq!(lines[1], "\"資料作成, レビュー依頼\",false,2026-03-01,2026-02-07T00:00:00Z");
+        assert_eq!(lines[2], "\"\"\"見積\"\"を送る\",true,,2026-02-08T00:00:00Z");
+    }
+
+    #[test]
+    fn test_export_json_and_unknown_format() {
+        let json = format_todos(&export_fixture(), "json").unwrap();
+        let parsed: Vec<TodoItem> = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].text, "資料作成, レビュー依頼");
+        assert!(parsed[1].done);
+
+        assert!(format_todos(&[], "xlsx").unwrap_err().contains("xlsx"));
+    }
+
+    #[tokio::test]
+    async fn test_import_markdown_checkboxes_only() {
+        let mgr = temp_manager();
+        let markdown = "# 今週のタスク\n\
+\n\
+打ち合わせメモ:\n\
+- [ ] 議事録を共有\n\
+- [x] 会議室を予約\n\
+  * [X] 資料を印刷\n\
+- 普通の箇条書き\n\
+- [ ]   \n\
+1. [ ] 番号付きは対象外\n";
+
+        let added = mgr.import_markdown(markdown).await.unwrap();
+        assert_eq!(added, 3);
+
+        let list = mgr.list().await;
+        let summary: Vec<(&str, bool)> = list.iter().map(|i| (i.text.as_str(), i.done)).collect();
+        assert_eq!(
+            summary,
+            vec![("議事録を共有", false), ("会議室を予約", true), ("資料を印刷", true)]
+        );
+
+        // Importing the same document again adds nothing
+        assert_eq!(mgr.import_markdown(markdown).await.unwrap(), 0);
+    }
+
+    #[test]
+    fn test_markdown_export_roundtrip() {
+        let md = format_todos(&export_fixture(), "markdown").unwrap();
+        let parsed = parse_markdown_checkboxes(&md);
+        assert_eq!(parsed[0], ("資料作成, レビュー依頼".to_string(), false));
+        assert_eq!(parsed[1], ("\"見積\"を送る".to_string(), true));
+    }
+
     #[test]
     fn test_serialization() {
         let item = TodoItem {
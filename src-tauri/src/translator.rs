@@ -1,4 +1,5 @@
 use serde_json::Value;
+use crate::util::truncate_chars;
 
 pub struct TranslatedTool {
     pub description: String,
@@ -56,12 +57,12 @@ fn translate_bash(input: &Value) -> String {
 
     // npm/node
     if cmd.starts_with("npm ") || cmd.starts_with("npx ") || cmd.starts_with("node ") {
-        return format!("コマンドを実行しています: {}", truncate(cmd, 60));
+        return format!("コマンドを実行しています: {}", truncate_chars(cmd, 60));
     }
 
     // Python
     if cmd.starts_with("python") || cmd.starts_with("pip") {
-        return format!("Pythonコマンドを実行しています: {}", truncate(cmd, 60));
+        return format!("Pythonコマンドを実行しています: {}", truncate_chars(cmd, 60));
     }
 
     // ls / listing
@@ -70,7 +71,7 @@ fn translate_bash(input: &Value) -> String {
     }
 
     // Generic
-    format!("コマンドを実行しています: {}", truncate(cmd, 60))
+    format!("コマンドを実行しています: {}", truncate_chars(cmd, 60))
 }
 
 fn translate_read(input: &Value) -> String {
@@ -98,22 +99,22 @@ fn translate_glob(input: &Value) -> String {
 
 fn translate_grep(input: &Value) -> String {
     let pattern = input.get("pattern").and_then(|v| v.as_str()).unwrap_or("");
-    format!("🔍 ファイル内を検索しています: 「{}」", truncate(pattern, 40))
+    format!("🔍 ファイル内を検索しています: 「{}」", truncate_chars(pattern, 40))
 }
 
 fn translate_web_fetch(input: &Value) -> String {
     let url = input.get("url").and_then(|v| v.as_str()).unwrap_or("URL");
-    format!("🌐 Webページを取得しています: {}", truncate(url, 50))
+    format!("🌐 Webページを取得しています: {}", truncate_chars(url, 50))
 }
 
 fn translate_web_search(input: &Value) -> String {
     let query = input.get("query").and_then(|v| v.as_str()).unwrap_or("");
-    format!("🔍 Web検索しています: 「{}」", truncate(query, 40))
+    format!("🔍 Web検索しています: 「{}」", truncate_chars(query, 40))
 }
 
 fn translate_task(input: &Value) -> String {
     let desc = input.get("description").and_then(|v| v.as_str()).unwrap_or("タスク");
-    format!("⚙️ サブタスクを実行中: {}", truncate(desc, 50))
+    format!("⚙️ サブタスクを実行中: {}", truncate_chars(desc, 50))
 }
 
 fn translate_notebook(input: &Value) -> String {
@@ -147,7 +148,7 @@ fn translate_git(cmd: &str) -> String {
     if cmd.contains("checkout") || cmd.contains("switch") {
         return "ブランチを切り替えています".to_string();
     }
-    format!("Git操作を実行しています: {}", truncate(cmd, 50))
+    format!("Git操作を実行しています: {}", truncate_chars(cmd, 50))
 }
 
 fn extract_file_op(cmd: &str, op_desc: &str) -> String {
@@ -191,15 +192,6 @@ fn summarize_path(cmd: &str) -> String {
         .join(", ")
 }
 
-fn truncate(s: &str, max_len: usize) -> String {
-    if s.chars().count() <= max_len {
-        s.to_string()
-    } else {
-        let truncated: String = s.chars().take(max_len).collect();
-        format!("{}...", truncated)
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -422,23 +414,6 @@ mod tests {
         assert_eq!(extract_filename("file.rs"), "file.rs");
     }
 
-    #[test]
-    fn test_truncate_short() {
-        assert_eq!(truncate("hello", 10), "hello");
-    }
-
-    #[test]
-    fn test_truncate_long() {
-        let result = truncate("abcdefghij", 5);
-        assert_eq!(result, "abcde...");
-    }
-
-    #[test]
-    fn test_truncate_unicode() {
-        let result = truncate("あいうえおかきくけこ", 5);
-        assert_eq!(result, "あいうえお...");
-    }
-
     // ── raw field ──
 
     #[test]
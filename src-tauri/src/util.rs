@@ -0,0 +1,203 @@
+/// Clip `s` to at most `max_chars` characters for display, appending "..."
+/// when something was cut. Counts chars, so multibyte text is never split.
+pub(crate) fn truncate_chars(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}...", &s[..end]),
+        None => s.to_string(),
+    }
+}
+
+/// Code prefixed to the error returned when an operation needs a working
+/// directory and none is set, so the frontend can recognize it without
+/// matching the message text
+pub(crate) const NO_WORKING_DIR_CODE: &str = "NO_WORKING_DIR";
+
+/// The "no working directory" error: `NO_WORKING_DIR: <message>`
+pub(crate) fn no_working_dir_error() -> String {
+    format!("{}: 作業フォルダが設定されていません", NO_WORKING_DIR_CODE)
+}
+
+#[cfg(test)]
+pub(crate) fn is_no_working_dir_error(error: &str) -> bool {
+    error.starts_with(&format!("{}:", NO_WORKING_DIR_CODE))
+}
+
+/// Most cells of the LCS table `unified_diff` will allocate
+const MAX_DIFF_CELLS: usize = 16 * 1024 * 1024;
+
+#[derive(Clone, Copy, PartialEq)]
+enum DiffOp {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Line-based unified diff of `old` and `new` with `context` unchanged lines
+/// around each change. Returns an empty string when the texts are equal.
+pub(crate) fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> Result<String, String> {
+    let a: Vec<&str> = old.lines().collect();
+    let b: Vec<&str> = new.lines().collect();
+    let ops = diff_ops(&a, &b)?;
+    if ops.iter().all(|(op, _)| *op == DiffOp::Equal) {
+        return Ok(String::new());
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    // Line numbers (0-based) in `a` and `b` before each op
+    let mut positions = Vec::with_capacity(ops.len() + 1);
+    let (mut ai, mut bi) = (0usize, 0usize);
+    for (op, _) in &ops {
+        positions.push((ai, bi));
+        match op {
+            DiffOp::Equal => {
+                ai += 1;
+                bi += 1;
+            }
+            DiffOp::Delete => ai += 1,
+            DiffOp::Insert => bi += 1,
+        }
+    }
+    positions.push((ai, bi));
+
+    let changes: Vec<usize> = (0..ops.len()).filter(|&i| ops[i].0 != DiffOp::Equal).collect();
+    let mut i = 0;
+    while i < changes.len() {
+        let start = changes[i].saturating_sub(context);
+        let mut last = changes[i];
+        while i + 1 < changes.len() && changes[i + 1] - last <= context * 2 + 1 {
+            i += 1;
+            last = changes[i];
+        }
+        let end = (last + context + 1).min(ops.len());
+        i += 1;
+
+        let (a_start, b_start) = positions[start];
+        let (a_end, b_end) = positions[end];
+        let range = |start: usize, count: usize| {
+            let first = if count == 0 { start } else { start + 1 };
+            format!("{},{}", first, count)
+        };
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            range(a_start, a_end - a_start),
+            range(b_start, b_end - b_start)
+        ));
+        for (op, line) in &ops[start..end] {
+            let prefix = match op {
+                DiffOp::Equal => ' ',
+                DiffOp::Delete => '-',
+                DiffOp::Insert => '+',
+            };
+            out.push(prefix);
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    Ok(out)
+}
+
+/// Edit script turning `a` into `b` (longest common subsequence)
+fn diff_ops<'a>(a: &[&'a str], b: &[&'a str]) -> Result<Vec<(DiffOp, &'a str)>, String> {
+    // Common prefix and suffix don't need the table
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let ma = &a[prefix..a.len() - suffix];
+    let mb = &b[prefix..b.len() - suffix];
+    let (n, m) = (ma.len(), mb.len());
+    if (n + 1).saturating_mul(m + 1) > MAX_DIFF_CELLS {
+        return Err("差分が大きすぎるため比較できません".to_string());
+    }
+
+    // lcs[i][j] = LCS length of ma[i..] and mb[j..]
+    let width = m + 1;
+    let mut lcs = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i * width + j] = if ma[i] == mb[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops: Vec<(DiffOp, &str)> = a[..prefix].iter().map(|l| (DiffOp::Equal, *l)).collect();
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && ma[i] == mb[j] {
+            ops.push((DiffOp::Equal, ma[i]));
+            i += 1;
+            j += 1;
+        } else if i < n && (j == m || lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+            // Deletions come before insertions, as in `diff -u`
+            ops.push((DiffOp::Delete, ma[i]));
+            i += 1;
+        } else {
+            ops.push((DiffOp::Insert, mb[j]));
+            j += 1;
+        }
+    }
+    ops.extend(a[a.len() - suffix..].iter().map(|l| (DiffOp::Equal, *l)));
+    Ok(ops)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n";
+        let new = "a\nb\nC\nd\ne\nf\ng\nh\ni\nj\nk\n";
+        let diff = unified_diff(old, new, "old", "new", 1).unwrap();
+        assert_eq!(
+            diff,
+            "--- old\n+++ new\n@@ -2,3 +2,3 @@\n b\n-c\n+C\n d\n@@ -10,1 +10,2 @@\n j\n+k\n"
+        );
+
+        // Nearby changes share one hunk
+        let diff = unified_diff(old, new, "old", "new", 10).unwrap();
+        assert_eq!(diff.matches("@@ -").count(), 1);
+
+        assert_eq!(unified_diff(old, old, "old", "new", 3).unwrap(), "");
+    }
+
+    #[test]
+    fn test_unified_diff_empty_sides() {
+        let diff = unified_diff("", "x\n", "a", "b", 3).unwrap();
+        assert!(diff.contains("@@ -0,0 +1,1 @@\n+x\n"));
+        let diff = unified_diff("x\n", "", "a", "b", 3).unwrap();
+        assert!(diff.contains("@@ -1,1 +0,0 @@\n-x\n"));
+    }
+
+    #[test]
+    fn test_truncate_chars_ascii() {
+        assert_eq!(truncate_chars("hello", 10), "hello");
+        assert_eq!(truncate_chars("abcdefghij", 5), "abcde...");
+    }
+
+    #[test]
+    fn test_truncate_chars_japanese() {
+        assert_eq!(truncate_chars("あいうえおかきくけこ", 5), "あいうえお...");
+        assert_eq!(truncate_chars("日本語", 10), "日本語");
+    }
+
+    #[test]
+    fn test_truncate_chars_at_limit() {
+        assert_eq!(truncate_chars("abcde", 5), "abcde");
+        assert_eq!(truncate_chars("あいうえお", 5), "あいうえお");
+        assert_eq!(truncate_chars("", 0), "");
+        assert_eq!(truncate_chars("a", 0), "...");
+    }
+}
@@ -7,6 +7,7 @@ interface MessageBubbleProps {
 
 export function MessageBubble({ message }: MessageBubbleProps) {
   const isUser = message.role === "user";
+  const isTool = message.role === "tool";
 
   return (
     <div
@@ -26,7 +27,7 @@ export function MessageBubble({ message }: MessageBubbleProps) {
         }}
       >
         {!isUser && (
-          <div style={styles.roleLabel}>Claude</div>
+          <div style={styles.roleLabel}>{isTool ? "ツール結果" : "Claude"}</div>
         )}
         <div style={styles.content}>
           {renderContent(message.content)}
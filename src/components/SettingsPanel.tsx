@@ -10,6 +10,8 @@ export function SettingsPanel() {
         <GDriveSettings />
         <div style={styles.divider} />
         <SlackSettings />
+        <div style={styles.divider} />
+        <RepoRulesSettings />
       </div>
     </div>
   );
@@ -114,6 +116,96 @@ function GDriveSettings() {
   );
 }
 
+// ── Repository approval rules ──
+
+interface RepoApprovalRules {
+  commands: string[];
+  write_paths: string[];
+  accepted: boolean;
+}
+
+function RepoRulesSettings() {
+  const [rules, setRules] = useState<RepoApprovalRules | null>(null);
+  const [message, setMessage] = useState("");
+
+  const loadRules = useCallback(async () => {
+    try {
+      setRules(await invoke<RepoApprovalRules>("get_repo_approval_rules"));
+    } catch (e) {
+      console.error("Failed to load repo approval rules:", e);
+    }
+  }, []);
+
+  useEffect(() => {
+    loadRules();
+    const unlisten = listen("workspace:changed", () => {
+      setMessage("");
+      loadRules();
+    });
+    return () => {
+      unlisten.then((f) => f());
+    };
+  }, [loadRules]);
+
+  const accept = async () => {
+    try {
+      setRules(await invoke<RepoApprovalRules>("accept_repo_approval_rules"));
+      setMessage("承認しました");
+    } catch (e) {
+      setMessage(`エラー: ${e}`);
+    }
+  };
+
+  return (
+    <div style={styles.section}>
+      <h3 style={styles.sectionTitle}>フォルダの自動承認ルール</h3>
+
+      {!rules ||
+      (rules.commands.length === 0 && rules.write_paths.length === 0) ? (
+        <p style={styles.desc}>
+          この作業フォルダには .cowork/approvals.json がありません。
+        </p>
+      ) : (
+        <div style={styles.configSection}>
+          <p style={styles.desc}>
+            .cowork/approvals.json
+            の内容です。承認すると、次の操作は確認なしで実行されます。ファイルが変更されると再度承認が必要です。
+          </p>
+          {rules.commands.length > 0 && (
+            <>
+              <label style={styles.label}>コマンド</label>
+              {rules.commands.map((c) => (
+                <code key={`c:${c}`} style={styles.desc}>
+                  {c}
+                </code>
+              ))}
+            </>
+          )}
+          {rules.write_paths.length > 0 && (
+            <>
+              <label style={styles.label}>書き込み先</label>
+              {rules.write_paths.map((p) => (
+                <code key={`w:${p}`} style={styles.desc}>
+                  {p}
+                </code>
+              ))}
+            </>
+          )}
+          {rules.accepted ? (
+            <span style={styles.statusOk}>承認済み</span>
+          ) : (
+            <button onClick={accept} style={styles.saveButton}>
+              承認する
+            </button>
+          )}
+        </div>
+      )}
+
+      {message && <div style={styles.message}>{message}</div>}
+    </div>
+  );
+}
+
 // ── Slack ──
 
 function SlackSettings() {
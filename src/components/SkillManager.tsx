@@ -1,10 +1,18 @@
 import { useState, useEffect, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+interface SkillArgument {
+  name: string;
+  required: boolean;
+}
 
 interface CoworkSkill {
   name: string;
   description: string;
   body: string;
+  source?: "project" | "user";
+  arguments?: SkillArgument[];
 }
 
 interface SkillManagerProps {
@@ -18,6 +26,8 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
   const [selectedSkill, setSelectedSkill] = useState<CoworkSkill | null>(null);
   const [showForm, setShowForm] = useState(false);
   const [additionalInput, setAdditionalInput] = useState("");
+  const [argValues, setArgValues] = useState<Record<string, string>>({});
+  const [argError, setArgError] = useState<string | null>(null);
   const [newSkill, setNewSkill] = useState({
     name: "",
     description: "",
@@ -37,6 +47,18 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
     loadSkills();
   }, [loadSkills, workingDir]);
 
+  useEffect(() => {
+    // workspace:changed also fires after legacy skill migrations finish
+    const unlisteners = ["skills:changed", "workspace:changed"].map((event) =>
+      listen(event, () => {
+        loadSkills();
+      })
+    );
+    return () => {
+      unlisteners.forEach((u) => u.then((f) => f()));
+    };
+  }, [loadSkills]);
+
   const buildContext = (): string => {
     const parts: string[] = [];
 
@@ -45,6 +67,11 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
       parts.push(...selectedFiles.map((f) => `- ${f}`));
     }
 
+    for (const arg of selectedSkill?.arguments ?? []) {
+      const value = argValues[arg.name]?.trim();
+      if (value) parts.push(`${arg.name}: ${value}`);
+    }
+
     if (additionalInput.trim()) {
       parts.push(additionalInput.trim());
     }
@@ -54,6 +81,13 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
 
   const executeSkill = async () => {
     if (!selectedSkill) return;
+    const args = (selectedSkill.arguments ?? []).map((a) => argValues[a.name] ?? "");
+    try {
+      await invoke("validate_skill_args", { name: selectedSkill.name, args });
+    } catch (e) {
+      setArgError(String(e));
+      return;
+    }
     const context = buildContext();
     try {
       await invoke("execute_skill", { name: selectedSkill.name, context });
@@ -62,6 +96,8 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
     }
     setSelectedSkill(null);
     setAdditionalInput("");
+    setArgValues({});
+    setArgError(null);
   };
 
   const saveNewSkill = async () => {
@@ -85,7 +121,8 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
 
   const deleteSkill = async (name: string) => {
     try {
-      await invoke("delete_skill", { name });
+      const source = skills.find((s) => s.name === name)?.source;
+      await invoke("delete_skill", { name, source });
       if (selectedSkill?.name === name) setSelectedSkill(null);
       await loadSkills();
     } catch (e) {
@@ -177,6 +214,23 @@ export function SkillManager({ workingDir, selectedFiles, onExecute }: SkillMana
             </div>
           </div>
 
+          {(selectedSkill.arguments ?? []).map((arg) => (
+            <div key={arg.name} style={styles.paramGroup}>
+              <label style={styles.paramLabel}>
+                {arg.name}
+                {arg.required ? "" : "（任意）"}
+              </label>
+              <input
+                value={argValues[arg.name] ?? ""}
+                onChange={(e) =>
+                  setArgValues((prev) => ({ ...prev, [arg.name]: e.target.value }))
+                }
+                style={styles.paramInput}
+              />
+            </div>
+          ))}
+          {argError && <div style={styles.contextHint}>{argError}</div>}
+
           <div style={styles.paramGroup}>
             <label style={styles.paramLabel}>追加の指示（任意）</label>
             <textarea
@@ -33,7 +33,7 @@ export function TodoPanel() {
     if (!newTodo.trim()) return;
     setLoading(true);
     try {
-      await invoke<TodoItem>("todo_add", {
+      await invoke<TodoItem | null>("todo_add", {
         text: newTodo.trim(),
         dueDate: null,
       });
@@ -0,0 +1,12 @@
+// Error codes the backend prefixes to some messages ("CODE: message")
+
+export const NO_WORKING_DIR = "NO_WORKING_DIR";
+
+export function isNoWorkingDirError(e: unknown): boolean {
+  return String(e).startsWith(`${NO_WORKING_DIR}:`);
+}
+
+// The message to show the user, without any code prefix
+export function errorMessage(e: unknown): string {
+  return String(e).replace(/^[A-Z_]+:\s*/, "");
+}
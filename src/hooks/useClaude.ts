@@ -3,6 +3,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import type { UnlistenFn } from "@tauri-apps/api/event";
 import type { ChatMessage, ActivityItem, ApprovalRequest } from "../types";
+import { errorMessage, isNoWorkingDirError } from "../errors";
 
 export function useClaude() {
   const [messages, setMessages] = useState<ChatMessage[]>([]);
@@ -12,8 +13,13 @@ export function useClaude() {
   const [workingDir, setWorkingDir] = useState("");
   const [lastWorkingDir, setLastWorkingDir] = useState("");
   const [error, setError] = useState<string | null>(null);
-  const [pendingApproval, setPendingApproval] = useState<ApprovalRequest | null>(null);
+  // Outstanding approvals, oldest first; the dialog shows the head of the queue
+  const [approvalQueue, setApprovalQueue] = useState<ApprovalRequest[]>([]);
+  const pendingApproval = approvalQueue[0] ?? null;
   const initialLoadDone = useRef(false);
+  // The folder the current messages belong to, read by the debounced save
+  const workingDirRef = useRef("");
+  const pendingSave = useRef<(() => void) | null>(null);
 
   // Restore saved messages and last working dir on mount
   useEffect(() => {
@@ -38,10 +44,17 @@ export function useClaude() {
   useEffect(() => {
     if (!initialLoadDone.current) return;
     if (messages.length === 0) return;
-    const timer = setTimeout(() => {
-      invoke("chat_save_messages", { messages }).catch(console.error);
-    }, 500);
-    return () => clearTimeout(timer);
+    const workingDir = workingDirRef.current;
+    const save = () => {
+      pendingSave.current = null;
+      invoke("chat_save_messages", { messages, workingDir }).catch(console.error);
+    };
+    pendingSave.current = save;
+    const timer = setTimeout(save, 500);
+    return () => {
+      clearTimeout(timer);
+      if (pendingSave.current === save) pendingSave.current = null;
+    };
   }, [messages]);
 
   useEffect(() => {
@@ -72,9 +85,15 @@ export function useClaude() {
 
       // Listen for text deltas (streaming)
       unlistens.push(
-        await listen<string>("claude:text_delta", (event) => {
+        await listen<{ id: string }>("claude:message_start", () => {
+          if (!active) return;
+          setStreamingText("");
+        })
+      );
+      unlistens.push(
+        await listen<{ id: string; text: string }>("claude:text_delta", (event) => {
           if (!active) return;
-          setStreamingText((prev) => prev + event.payload);
+          setStreamingText((prev) => prev + event.payload.text);
         })
       );
 
@@ -119,7 +138,19 @@ export function useClaude() {
       unlistens.push(
         await listen<ApprovalRequest>("claude:approval_request", (event) => {
           if (!active) return;
-          setPendingApproval(event.payload);
+          setApprovalQueue((prev) =>
+            prev.some((a) => a.id === event.payload.id)
+              ? prev
+              : [...prev, event.payload]
+          );
+        })
+      );
+
+      // The backend re-sends the whole queue whenever it changes
+      unlistens.push(
+        await listen<ApprovalRequest[]>("claude:approval_queue", (event) => {
+          if (!active) return;
+          setApprovalQueue(event.payload);
         })
       );
 
@@ -148,7 +179,11 @@ export function useClaude() {
       try {
         await invoke("send_message", { message });
       } catch (e) {
-        setError(String(e));
+        setError(
+          isNoWorkingDirError(e)
+            ? "作業フォルダが選択されていません。フォルダを選択してから送信してください"
+            : errorMessage(e)
+        );
         setIsLoading(false);
       }
     },
@@ -157,8 +192,17 @@ export function useClaude() {
 
   const changeWorkingDir = useCallback(async (path: string) => {
     try {
-      await invoke("set_working_directory", { path });
-      setWorkingDir(path);
+      // Write the old folder's chat before the backend switches histories
+      pendingSave.current?.();
+      // The backend returns the normalized absolute path
+      const normalized = await invoke<string>("set_working_directory", { path });
+      workingDirRef.current = normalized;
+      setWorkingDir(normalized);
+      // Each working directory keeps its own chat history
+      const saved = await invoke<ChatMessage[]>("chat_load_messages");
+      setMessages(saved);
+      setActivities([]);
+      setStreamingText("");
     } catch (e) {
       setError(String(e));
     }
@@ -174,7 +218,8 @@ export function useClaude() {
     } catch (e) {
       console.error("Failed to respond to approval:", e);
     }
-    setPendingApproval(null);
+    const answeredId = pendingApproval.id;
+    setApprovalQueue((prev) => prev.filter((a) => a.id !== answeredId));
   }, [pendingApproval]);
 
   const cancelMessage = useCallback(async () => {
@@ -204,6 +249,7 @@ export function useClaude() {
     lastWorkingDir,
     error,
     pendingApproval,
+    approvalQueue,
     sendMessage,
     cancelMessage,
     changeWorkingDir,
@@ -32,6 +32,21 @@ describe("MessageBubble", () => {
     expect(screen.getByText("こんにちは")).toBeInTheDocument();
   });
 
+  it("renders tool result message with tool label", () => {
+    render(
+      <MessageBubble
+        message={{
+          id: "t1",
+          role: "tool",
+          content: "Bash: ok",
+          timestamp: new Date().toISOString(),
+        }}
+      />
+    );
+    expect(screen.getByText("ツール結果")).toBeInTheDocument();
+    expect(screen.queryByText("Claude")).not.toBeInTheDocument();
+  });
+
   it("does not show Claude label for user messages", () => {
     render(
       <MessageBubble